@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+
+pub const AGENT_PROTOCOL_VERSION: u32 = 1;
+pub const JSONRPC_VERSION: &str = "2.0";
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const EXECUTION_ERROR: i64 = -32000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum AgentCall {
+    Hello,
+    Status {
+        base_path: PathBuf,
+        jobs_from: PathBuf,
+        submission_log: Option<PathBuf>,
+    },
+    TailLog {
+        path: PathBuf,
+        lines: u32,
+    },
+    Cancel {
+        scheduler: String,
+        ids: Vec<u32>,
+    },
+    WriteFile {
+        path: PathBuf,
+        content: String,
+    },
+    ListArtifacts {
+        base_path: PathBuf,
+    },
+    SubmitPlan {
+        plan_file: PathBuf,
+        args: Vec<String>,
+    },
+}
+
+impl AgentCall {
+    pub fn is_idempotent(&self) -> bool {
+        !matches!(self, AgentCall::SubmitPlan { .. })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AgentRequest {
+    pub jsonrpc: String,
+    pub id: u64,
+    #[serde(flatten)]
+    pub call: AgentCall,
+}
+
+impl AgentRequest {
+    pub fn new(id: u64, call: AgentCall) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id,
+            call,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AgentError {
+    pub code: i64,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AgentResponse {
+    pub jsonrpc: String,
+    pub id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<AgentError>,
+}
+
+impl AgentResponse {
+    pub fn success(id: u64, result: Value) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id: Some(id),
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    pub fn failure(id: Option<u64>, code: i64, message: impl Into<String>) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id,
+            result: None,
+            error: Some(AgentError {
+                code,
+                message: message.into(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requests_use_jsonrpc_method_and_params() {
+        let request = AgentRequest::new(
+            7,
+            AgentCall::TailLog {
+                path: PathBuf::from("/scratch/outputs/job/repx/stdout.log"),
+                lines: 20,
+            },
+        );
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 7,
+                "method": "tail_log",
+                "params": {"path": "/scratch/outputs/job/repx/stdout.log", "lines": 20}
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<AgentRequest>(json).unwrap(),
+            request
+        );
+
+        let hello: AgentRequest =
+            serde_json::from_str(r#"{"jsonrpc":"2.0","id":1,"method":"hello"}"#).unwrap();
+        assert_eq!(hello.call, AgentCall::Hello);
+    }
+}
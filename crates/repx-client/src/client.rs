@@ -1,15 +1,19 @@
 use crate::{
     error::{ClientError, Result},
     inputs,
-    targets::{local::LocalTarget, ssh::SshTarget, Target},
+    targets::{
+        agent::AgentConnection, local::LocalTarget, retry::RetryPolicy, ssh::SshTarget,
+        ssh_master::ControlMaster, Target,
+    },
 };
 use fs_err;
 use repx_core::{
-    config::{Config, Resources},
+    config::{self, Config, Resources},
     engine,
     error::AppError,
-    lab, log_info,
+    lab, log_info, log_warn,
     model::{Job, JobId, Lab, RunId},
+    store::progress::WorkerProgress,
 };
 use sha2::{Digest, Sha256};
 use std::path::Path;
@@ -21,9 +25,20 @@ use std::{
 };
 use xdg;
 
+mod batch;
+pub mod condor;
 pub mod local;
+pub mod pbs;
+mod preflight;
+pub mod scheduler;
 pub mod slurm;
+mod slurm_map;
+pub mod ssh_pool;
 pub mod status;
+mod sync;
+
+use scheduler::{Scheduler, JOB_STDOUT_LOG_FILE};
+use slurm_map::{SlurmMapStore, SLURM_MAP_FILE};
 
 #[derive(Debug)]
 pub enum ClientEvent {
@@ -33,10 +48,14 @@ pub enum ClientEvent {
     },
     ExecutingOrchestrator,
     SyncingArtifacts {
-        total: u64,
+        files: u64,
+        total_bytes: u64,
     },
     SyncingArtifactProgress {
         path: PathBuf,
+        bytes_transferred: u64,
+        total_bytes: u64,
+        bytes_per_second: u64,
     },
     SyncingFinished,
     SubmittingJobs {
@@ -59,8 +78,12 @@ pub enum ClientEvent {
         wave: usize,
         num_jobs: usize,
     },
+    WorkerProgress {
+        job_id: JobId,
+        progress: WorkerProgress,
+    },
 }
-type SlurmIdMap = Arc<Mutex<HashMap<JobId, (String, u32)>>>;
+type SlurmIdMap = Arc<Mutex<slurm_map::TrackedSlurmJobs>>;
 
 #[derive(Default)]
 pub struct SubmitOptions {
@@ -69,6 +92,14 @@ pub struct SubmitOptions {
     pub num_jobs: Option<usize>,
     pub event_sender: Option<Sender<ClientEvent>>,
 }
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    pub checked: usize,
+    pub missing: Vec<PathBuf>,
+    pub mismatched: Vec<PathBuf>,
+    pub resynced: Vec<PathBuf>,
+}
 #[derive(Clone)]
 pub struct Client {
     pub(crate) config: Arc<Config>,
@@ -76,6 +107,9 @@ pub struct Client {
     pub(crate) lab: Arc<Lab>,
     pub(crate) targets: Arc<HashMap<String, Arc<dyn Target>>>,
     pub(crate) slurm_map: SlurmIdMap,
+    slurm_map_store: Arc<SlurmMapStore>,
+    schedulers: Arc<Vec<Arc<dyn Scheduler>>>,
+    remote_status: Arc<status::RemoteStatusState>,
 }
 
 impl Client {
@@ -92,12 +126,24 @@ impl Client {
                     local_tools_path: lab_arc.host_tools_path.clone(),
                 })
             } else if let Some(address) = &target_config.address {
+                let retry_policy = RetryPolicy::from_config(target_config.ssh.as_ref());
                 Arc::new(SshTarget {
                     name: name.clone(),
                     address: address.clone(),
                     config: target_config.clone(),
                     local_tools_path: lab_arc.host_tools_path.clone(),
                     host_tools_dir_name: lab_arc.host_tools_dir_name.clone(),
+                    control_master: ControlMaster::new(
+                        name,
+                        address,
+                        target_config
+                            .ssh
+                            .as_ref()
+                            .map(|ssh| ssh.to_ssh_options())
+                            .unwrap_or_default(),
+                    ),
+                    retry_policy: retry_policy.clone(),
+                    agent: AgentConnection::new(name, retry_policy.command_timeout),
                 })
             } else {
                 return Err(ClientError::Core(AppError::ConfigurationError(format!(
@@ -115,12 +161,20 @@ impl Client {
                 "Could not find state home directory",
             ))
         })?;
-        let map_path = state_home.join("slurm_map.json");
-        let slurm_map_data = fs_err::read_to_string(map_path)
-            .ok()
-            .as_deref()
-            .and_then(|s| serde_json::from_str(s).ok())
-            .unwrap_or_default();
+        let schedulers = scheduler::default_schedulers();
+        let bare_id_schedulers = config
+            .targets
+            .iter()
+            .filter_map(|(name, target_config)| {
+                let scheduler = scheduler::tracking_scheduler(&schedulers, target_config)?;
+                Some((name.clone(), scheduler.name().to_string()))
+            })
+            .collect();
+        let slurm_map_store =
+            SlurmMapStore::new(&state_home, &lab_arc.content_hash, bare_id_schedulers);
+        let slurm_map_data = slurm_map_store.load(config.targets.keys(), |job_id| {
+            lab_arc.jobs.contains_key(job_id)
+        });
 
         Ok(Self {
             config: Arc::new(config),
@@ -128,9 +182,46 @@ impl Client {
             lab: lab_arc,
             targets: Arc::new(targets),
             slurm_map: Arc::new(Mutex::new(slurm_map_data)),
+            slurm_map_store: Arc::new(slurm_map_store),
+            schedulers: Arc::new(schedulers),
+            remote_status: Arc::new(status::RemoteStatusState::default()),
         })
     }
 
+    pub fn register_scheduler(&mut self, scheduler: Arc<dyn Scheduler>) {
+        let schedulers = Arc::make_mut(&mut self.schedulers);
+        if let Some(existing) = schedulers.iter_mut().find(|s| s.name() == scheduler.name()) {
+            *existing = scheduler;
+        } else {
+            schedulers.push(scheduler);
+        }
+    }
+
+    pub fn scheduler(&self, name: &str) -> Option<Arc<dyn Scheduler>> {
+        self.schedulers.iter().find(|s| s.name() == name).cloned()
+    }
+
+    pub(crate) fn tracking_scheduler(
+        &self,
+        target_config: &config::Target,
+    ) -> Option<Arc<dyn Scheduler>> {
+        scheduler::tracking_scheduler(&self.schedulers, target_config).cloned()
+    }
+
+    pub(crate) fn tracked_jobs(
+        &self,
+        target_name: &str,
+        scheduler_name: &str,
+    ) -> Vec<(JobId, u32)> {
+        self.slurm_map
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, job)| job.target == target_name && job.scheduler == scheduler_name)
+            .map(|(job_id, job)| (job_id.clone(), job.id))
+            .collect()
+    }
+
     pub fn config(&self) -> &Config {
         &self.config
     }
@@ -147,14 +238,39 @@ impl Client {
         self.targets.get(name).cloned()
     }
 
+    pub fn verify_artifacts(&self, target_name: &str, resync: bool) -> Result<VerifyReport> {
+        let target = self
+            .targets
+            .get(target_name)
+            .ok_or_else(|| ClientError::TargetNotFound(target_name.to_string()))?;
+        sync::verify_lab_artifacts(self, target.as_ref(), self.lab.jobs.keys(), resync)
+    }
+
     pub(crate) fn save_slurm_map(&self) -> Result<()> {
-        let xdg_dirs = xdg::BaseDirectories::with_prefix("repx");
-        let map_path = xdg_dirs
-            .place_state_file("slurm_map.json")
-            .map_err(AppError::from)?;
-        let data = self.slurm_map.lock().unwrap();
-        let json_string = serde_json::to_string_pretty(&*data).map_err(AppError::from)?;
-        fs_err::write(map_path, json_string).map_err(AppError::from)?;
+        let changed_targets = {
+            let mut data = self.slurm_map.lock().unwrap();
+            self.slurm_map_store.save(&mut data)?
+        };
+
+        for (target_name, jobs) in changed_targets {
+            let Some(target) = self.targets.get(&target_name) else {
+                continue;
+            };
+            let remote_path = target
+                .base_path()
+                .join("submissions")
+                .join(&self.lab.content_hash)
+                .join(SLURM_MAP_FILE);
+            let json_string = serde_json::to_string_pretty(&jobs).map_err(AppError::from)?;
+            if let Err(e) = target.write_remote_file(&remote_path, &json_string) {
+                log_warn!(
+                    "Could not write SLURM job map to '{}' on target '{}': {}",
+                    remote_path.display(),
+                    target_name,
+                    e
+                );
+            }
+        }
         Ok(())
     }
 
@@ -175,6 +291,14 @@ impl Client {
         status::get_statuses_for_active_target(self, active_target_name, active_scheduler)
     }
 
+    pub fn get_worker_progress(
+        &self,
+        target_name: &str,
+        job_statuses: &HashMap<JobId, engine::JobStatus>,
+    ) -> Result<HashMap<JobId, WorkerProgress>> {
+        status::get_worker_progress(self, target_name, job_statuses)
+    }
+
     pub fn submit_run(
         &self,
         run_spec: String,
@@ -188,7 +312,7 @@ impl Client {
         &self,
         run_specs: Vec<String>,
         target_name: &str,
-        scheduler: &str,
+        scheduler_name: &str,
         options: SubmitOptions,
     ) -> Result<String> {
         let send = |event: ClientEvent| {
@@ -200,6 +324,14 @@ impl Client {
             .targets
             .get(target_name)
             .ok_or_else(|| ClientError::TargetNotFound(target_name.to_string()))?;
+        let scheduler = self.scheduler(scheduler_name).ok_or_else(|| {
+            let known: Vec<&str> = self.schedulers.iter().map(|s| s.name()).collect();
+            ClientError::Core(AppError::ConfigurationError(format!(
+                "Unsupported scheduler: '{}'. Must be one of: {}.",
+                scheduler_name,
+                known.join(", ")
+            )))
+        })?;
 
         let lab_path_abs =
             fs_err::canonicalize(&*self.lab_path).unwrap_or(self.lab_path.to_path_buf());
@@ -248,16 +380,8 @@ impl Client {
             remote_repx_binary_path.display()
         );
 
-        send(ClientEvent::SyncingArtifacts { total: 1 });
-        target.sync_lab_root(&self.lab_path)?;
-
-        if let Err(e) = target.register_gc_root(&project_id, &self.lab.content_hash) {
-            log_info!("Warning: Failed to register GC root: {}", e);
-        }
-
-        send(ClientEvent::SyncingFinished);
-
-        let raw_statuses = self.get_statuses_for_active_target(target_name, Some(scheduler))?;
+        let raw_statuses =
+            self.get_statuses_for_active_target(target_name, Some(scheduler_name))?;
         let job_statuses = engine::determine_job_statuses(&self.lab, &raw_statuses);
         let jobs_to_run: HashMap<JobId, &Job> = full_dependency_set
             .into_iter()
@@ -265,6 +389,7 @@ impl Client {
                 !matches!(
                     job_statuses.get(job_id),
                     Some(engine::JobStatus::Succeeded { .. })
+                        | Some(engine::JobStatus::PartiallySucceeded { .. })
                 )
             })
             .map(|job_id| (job_id.clone(), self.lab.jobs.get(&job_id).unwrap()))
@@ -273,46 +398,28 @@ impl Client {
         if jobs_to_run.is_empty() {
             return Ok("All required jobs for this submission are already complete.".to_string());
         }
-        let jobs_to_run_ids: std::collections::HashSet<JobId> =
-            jobs_to_run.keys().cloned().collect();
 
-        let jobs_to_submit: HashMap<JobId, &Job> = if scheduler == "slurm" {
-            jobs_to_run
-                .iter()
-                .map(|(id, job)| (id.clone(), *job))
-                .collect()
-        } else {
-            jobs_to_run
-                .iter()
-                .filter(|(_job_id, job)| {
-                    let entrypoint_exe = job
-                        .executables
-                        .get("main")
-                        .or_else(|| job.executables.get("scatter"))
-                        .ok_or_else(|| {
-                            AppError::ConfigurationError(format!(
-                                "Job '{}' has no 'main' or 'scatter' executable defined.",
-                                _job_id
-                            ))
-                        })
-                        .unwrap();
-
-                    let has_deps_in_batch = entrypoint_exe
-                        .inputs
-                        .iter()
-                        .filter_map(|m| m.job_id.as_ref())
-                        .any(|job_id| jobs_to_run_ids.contains(job_id));
-                    !has_deps_in_batch
-                })
-                .map(|(id, job)| (id.clone(), *job))
-                .collect()
-        };
-        if jobs_to_submit.is_empty() && scheduler == "slurm" {
-            return Ok(
-                "All schedulable jobs for this submission are already complete.".to_string(),
-            );
+        let pending = sync::pending_artifacts(self, target.as_ref(), jobs_to_run.keys())?;
+        preflight::check_disk_space(
+            target.as_ref(),
+            &self.lab,
+            &pending,
+            &job_statuses,
+            &jobs_to_run,
+        )?;
+        sync::sync_lab_artifacts(
+            self,
+            target.as_ref(),
+            pending,
+            options.event_sender.as_ref(),
+        )?;
+
+        if let Err(e) = target.register_gc_root(&project_id, &self.lab.content_hash) {
+            log_info!("Warning: Failed to register GC root: {}", e);
         }
 
+        send(ClientEvent::SyncingFinished);
+
         for (job_id, job) in &jobs_to_run {
             if job.stage_type == "scatter-gather" {
                 inputs::generate_and_write_inputs_json(
@@ -335,30 +442,14 @@ impl Client {
             }
         }
 
-        match scheduler {
-            "slurm" => slurm::submit_slurm_batch_run(
-                self,
-                jobs_to_submit,
-                target.clone(),
-                target_name,
-                &remote_repx_binary_path,
-                &options,
-                send,
-            ),
-            "local" => local::submit_local_batch_run(
-                self,
-                jobs_to_run,
-                target.clone(),
-                target_name,
-                &remote_repx_binary_path,
-                &options,
-                send,
-            ),
-            _ => Err(ClientError::Core(AppError::ConfigurationError(format!(
-                "Unsupported scheduler: '{}'. Must be 'slurm' or 'local'.",
-                scheduler
-            )))),
-        }
+        scheduler.submit(
+            self,
+            jobs_to_run,
+            target.clone(),
+            &remote_repx_binary_path,
+            &options,
+            &send,
+        )
     }
     pub fn get_log_tail(
         &self,
@@ -371,48 +462,66 @@ impl Client {
             .get(target_name)
             .ok_or_else(|| ClientError::TargetNotFound(target_name.to_string()))?;
 
-        let slurm_info = {
+        let tracked = {
             let slurm_map_guard = self.slurm_map.lock().unwrap();
             slurm_map_guard.get(&job_id).cloned()
         };
 
-        if let Some((slurm_target_name, slurm_id)) = slurm_info {
-            if slurm_target_name == target_name {
-                let log_path = target
-                    .base_path()
-                    .join("outputs")
-                    .join(&job_id.0)
-                    .join("repx")
-                    .join(format!("slurm-{}.out", slurm_id));
-
-                return target.read_remote_file_tail(&log_path, line_count);
+        if let Some(tracked) = tracked {
+            if tracked.target == target_name {
+                if let Some(scheduler) = self.scheduler(&tracked.scheduler) {
+                    let log_path = scheduler.log_path(target.as_ref(), &job_id, tracked.id);
+                    return target.read_remote_file_tail(&log_path, line_count);
+                }
             }
         }
 
-        let log_path = target
-            .base_path()
-            .join("outputs")
-            .join(&job_id.0)
-            .join("repx")
-            .join("stdout.log");
+        let log_path = scheduler::job_repx_dir(target.as_ref(), &job_id).join(JOB_STDOUT_LOG_FILE);
 
         target.read_remote_file_tail(&log_path, line_count)
     }
 
+    /// Cancelling one job of a SLURM bundle only marks it to be skipped; the allocation
+    /// itself is cancelled once every job in it has been cancelled.
     pub fn cancel_job(&self, job_id: JobId) -> Result<()> {
-        let slurm_info = {
+        let (tracked, mut bundle_members) = {
             let slurm_map_guard = self.slurm_map.lock().unwrap();
-            slurm_map_guard.get(&job_id).cloned()
+            let tracked = slurm_map_guard.get(&job_id).cloned();
+            let members: Vec<JobId> = slurm_map_guard
+                .iter()
+                .filter(|(_, other)| Some(*other) == tracked.as_ref())
+                .map(|(member, _)| member.clone())
+                .collect();
+            (tracked, members)
         };
+        bundle_members.sort();
 
-        if let Some((target_name, slurm_id)) = slurm_info {
-            let target = self.targets.get(&target_name).ok_or_else(|| {
+        if let Some(tracked) = tracked {
+            let target = self.targets.get(&tracked.target).ok_or_else(|| {
                 ClientError::Core(AppError::ConfigurationError(format!(
                     "Inconsistent state: target '{}' from slurm_map not found.",
-                    target_name
+                    tracked.target
                 )))
             })?;
-            return target.scancel(slurm_id);
+            let scheduler = self.scheduler(&tracked.scheduler).ok_or_else(|| {
+                ClientError::Core(AppError::ConfigurationError(format!(
+                    "Job '{}' was submitted with scheduler '{}', which is not registered.",
+                    job_id, tracked.scheduler
+                )))
+            })?;
+            if bundle_members.len() > 1 {
+                let all_cancelled =
+                    slurm::cancel_bundle_member(target.as_ref(), &job_id, &bundle_members)?;
+                if !all_cancelled {
+                    log_info!(
+                        "Job '{}' shares SLURM allocation {} with other jobs; it will be skipped instead of cancelling the allocation.",
+                        job_id,
+                        tracked.id
+                    );
+                    return Ok(());
+                }
+            }
+            return scheduler.cancel(target.as_ref(), &job_id, tracked.id);
         }
         Ok(())
     }
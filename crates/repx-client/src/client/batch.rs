@@ -0,0 +1,254 @@
+use super::{slurm_map::TrackedJob, Client, ClientEvent, SubmitOptions};
+use crate::error::{ClientError, Result};
+use crate::resources::{self, SbatchDirectives};
+use crate::targets::Target;
+use repx_core::{
+    config::SchedulerConfig,
+    error::AppError,
+    model::{Job, JobId},
+};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WorkerDispatch {
+    Slurm,
+    InAllocation,
+    Condor,
+}
+
+pub(crate) struct BatchContext<'a> {
+    pub client: &'a Client,
+    pub target: &'a dyn Target,
+    pub target_name: &'a str,
+    pub scheduler_config: &'a SchedulerConfig,
+    pub remote_repx_command: &'a str,
+    pub options: &'a SubmitOptions,
+}
+
+pub(crate) fn job_command(
+    ctx: &BatchContext,
+    job_id: &JobId,
+    job: &Job,
+    worker_dispatch: WorkerDispatch,
+) -> Result<(String, SbatchDirectives)> {
+    let target = ctx.target;
+    let image_path_opt = ctx
+        .client
+        .lab
+        .runs
+        .values()
+        .find(|r| r.jobs.contains(job_id))
+        .and_then(|r| r.image.as_deref());
+    let image_tag = image_path_opt
+        .and_then(|p| p.file_stem())
+        .and_then(|s| s.to_str());
+
+    let execution_type = if ctx.options.execution_type.is_none() && image_tag.is_none() {
+        "native"
+    } else {
+        ctx.options.execution_type.as_deref().unwrap_or_else(|| {
+            target
+                .config()
+                .default_execution_type
+                .as_deref()
+                .filter(|&et| {
+                    ctx.scheduler_config
+                        .execution_types
+                        .contains(&et.to_string())
+                })
+                .or_else(|| {
+                    ctx.scheduler_config
+                        .execution_types
+                        .first()
+                        .map(|s| s.as_str())
+                })
+                .unwrap_or("native")
+        })
+    };
+    let mut repx_args = format!(
+        "--job-id {} --runtime {} {} --base-path {} --host-tools-dir {}",
+        job_id,
+        execution_type,
+        image_tag
+            .map(|t| format!("--image-tag {}", t))
+            .unwrap_or_default(),
+        target.base_path().display(),
+        ctx.client.lab.host_tools_dir_name
+    );
+    if let Some(local_path) = &target.config().node_local_path {
+        repx_args.push_str(&format!(" --node-local-path {}", local_path.display()));
+    }
+    if target.config().mount_host_paths {
+        if !target.config().mount_paths.is_empty() {
+            return Err(ClientError::Core(AppError::ConfigurationError(
+                "Cannot specify both 'mount_host_paths = true' and 'mount_paths'.".into(),
+            )));
+        }
+        repx_args.push_str(" --mount-host-paths");
+    } else {
+        for path in &target.config().mount_paths {
+            repx_args.push_str(&format!(" --mount-paths {}", path));
+        }
+    }
+
+    let resources = &ctx.options.resources;
+    if job.stage_type == "scatter-gather" {
+        let scatter_exe = job.executables.get("scatter").ok_or_else(|| {
+            AppError::ConfigurationError("Scatter-gather job missing 'scatter' executable".into())
+        })?;
+        let worker_exe = job.executables.get("worker").ok_or_else(|| {
+            AppError::ConfigurationError("Scatter-gather job missing 'worker' executable".into())
+        })?;
+        let gather_exe = job.executables.get("gather").ok_or_else(|| {
+            AppError::ConfigurationError("Scatter-gather job missing 'gather' executable".into())
+        })?;
+
+        let artifacts_base = target.artifacts_base_path();
+        let scatter_exe_path = artifacts_base.join(&scatter_exe.path);
+        let worker_exe_path = artifacts_base.join(&worker_exe.path);
+        let gather_exe_path = artifacts_base.join(&gather_exe.path);
+
+        let worker_outputs_json =
+            serde_json::to_string(&worker_exe.outputs).map_err(AppError::from)?;
+
+        let scatter_gather_args = format!(
+            "--job-package-path {} --scatter-exe-path {} --worker-exe-path {} --gather-exe-path {} --worker-outputs-json '{}' {}",
+            target.artifacts_base_path().join(format!("jobs/{}", job_id)).display(),
+            scatter_exe_path.display(),
+            worker_exe_path.display(),
+            gather_exe_path.display(),
+            worker_outputs_json,
+            if target.config().mount_host_paths {
+                "--mount-host-paths".to_string()
+            } else {
+                target
+                    .config()
+                    .mount_paths
+                    .iter()
+                    .map(|p| format!("--mount-paths {}", p))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            }
+        );
+        let main_directives = resources::resolve_for_job(job_id, ctx.target_name, resources);
+
+        let mut command = match worker_dispatch {
+            WorkerDispatch::Slurm => {
+                let worker_directives =
+                    resources::resolve_worker_resources(job_id, ctx.target_name, resources);
+                format!(
+                    "{} internal-scatter-gather {} {} --worker-sbatch-opts='{}' --scheduler slurm --anchor-id $REPX_ANCHOR_ID",
+                    ctx.remote_repx_command,
+                    repx_args,
+                    scatter_gather_args,
+                    worker_directives.to_shell_string()
+                )
+            }
+            WorkerDispatch::InAllocation => {
+                let mut command = format!(
+                    "{} internal-scatter-gather {} {} --worker-sbatch-opts='' --scheduler local",
+                    ctx.remote_repx_command, repx_args, scatter_gather_args
+                );
+                if let Some(cpus) = main_directives.cpus_per_task {
+                    command.push_str(&format!(" --worker-concurrency {}", cpus));
+                }
+                command
+            }
+            WorkerDispatch::Condor => format!(
+                "{} internal-scatter-gather {} {} --worker-sbatch-opts='' --scheduler condor",
+                ctx.remote_repx_command, repx_args, scatter_gather_args
+            ),
+        };
+        if let Some(threshold) =
+            resources::resolve_min_worker_success(job_id, ctx.target_name, resources)
+        {
+            command.push_str(&format!(" --min-worker-success {}", threshold));
+        }
+        Ok((command, main_directives))
+    } else {
+        let main_exe = job.executables.get("main").ok_or_else(|| {
+            AppError::ConfigurationError(format!(
+                "Simple job '{}' missing 'main' executable",
+                job_id
+            ))
+        })?;
+        let executable_path_on_target = target.artifacts_base_path().join(&main_exe.path);
+
+        let repx_args = format!(
+            "{} --executable-path {}",
+            repx_args,
+            executable_path_on_target.display()
+        );
+        let directives = resources::resolve_for_job(job_id, ctx.target_name, resources);
+        let command = format!("{} internal-execute {}", ctx.remote_repx_command, repx_args);
+        Ok((command, directives))
+    }
+}
+
+pub(crate) fn record_submitted_jobs(
+    client: &Client,
+    target_name: &str,
+    scheduler_name: &str,
+    orchestrator_output: &str,
+    parse_id: impl Fn(&str) -> Option<u32>,
+    total_to_submit: usize,
+    send: &impl Fn(ClientEvent),
+) -> usize {
+    let mut submitted_count = 0;
+    for (repx_id, slurm_id) in parse_submission_log(orchestrator_output, parse_id) {
+        client.slurm_map.lock().unwrap().insert(
+            repx_id.clone(),
+            TrackedJob::new(target_name, scheduler_name, slurm_id),
+        );
+        submitted_count += 1;
+        send(ClientEvent::JobSubmitted {
+            job_id: repx_id,
+            slurm_id,
+            total: total_to_submit,
+            current: submitted_count,
+        });
+    }
+    submitted_count
+}
+
+fn parse_submission_log(output: &str, parse_id: impl Fn(&str) -> Option<u32>) -> Vec<(JobId, u32)> {
+    // A log read while the orchestrator is being killed can end in a half-written line.
+    let complete = match output.rfind('\n') {
+        Some(end) if !output.ends_with('\n') => &output[..end],
+        None if !output.is_empty() => "",
+        _ => output,
+    };
+    complete
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<_> = line.split_whitespace().collect();
+            match parts.as_slice() {
+                [job_id, scheduler_id] | [job_id, scheduler_id, _] => {
+                    Some((JobId::from_str(job_id).ok()?, parse_id(scheduler_id)?))
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_submission_log_is_ingested_up_to_the_last_complete_line() {
+        let parse = |log: &str| parse_submission_log(log, |id| id.parse().ok());
+
+        assert_eq!(
+            parse("job-a 101\njob-b 102\njob-c 10"),
+            vec![(JobId("job-a".into()), 101), (JobId("job-b".into()), 102)]
+        );
+        assert_eq!(
+            parse("job-a 101 slurm\n[REPX-ORCH] Submitting wave 1\n\njob-b 102\n"),
+            vec![(JobId("job-a".into()), 101), (JobId("job-b".into()), 102)]
+        );
+        assert!(parse("job-a 1").is_empty());
+        assert!(parse("").is_empty());
+    }
+}
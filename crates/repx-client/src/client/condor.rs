@@ -0,0 +1,665 @@
+use super::batch::{self, BatchContext, WorkerDispatch};
+use super::scheduler::{job_repx_dir, Scheduler};
+use super::slurm_map::TrackedJob;
+use super::{Client, ClientEvent, SubmitOptions};
+use crate::error::{ClientError, Result};
+use crate::orchestration::{OrchestrationPlan, CONDOR_WORKER_DAG_FILE};
+use crate::resources::{self, SbatchDirectives};
+use crate::targets::{CondorJobInfo, Target};
+use repx_core::{
+    config::{self, SchedulerConfig},
+    engine,
+    error::AppError,
+    log_debug, log_warn,
+    model::{Job, JobId},
+};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const CONDOR_LOG_FILE: &str = "condor.out";
+const CONDOR_GATHER_LOG_FILE: &str = "condor-gather.out";
+const GATHER_NODE_SUFFIX: &str = ".gather";
+const WORKERS_NODE_SUFFIX: &str = ".workers";
+
+fn node_names(job_id: &JobId) -> Vec<String> {
+    vec![
+        job_id.0.clone(),
+        format!("{}{}", job_id.0, WORKERS_NODE_SUFFIX),
+        format!("{}{}", job_id.0, GATHER_NODE_SUFFIX),
+    ]
+}
+
+fn node_job_id(node_name: &str) -> &str {
+    node_name
+        .strip_suffix(GATHER_NODE_SUFFIX)
+        .or_else(|| node_name.strip_suffix(WORKERS_NODE_SUFFIX))
+        .unwrap_or(node_name)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum CondorNodeState {
+    Active(engine::JobStatus),
+    Failed(String),
+}
+
+fn resolve_dag_job(
+    job_id: &JobId,
+    dag_id: u32,
+    live: &[CondorJobInfo],
+    history: Option<&[CondorJobInfo]>,
+) -> Option<CondorNodeState> {
+    let is_node = |info: &&CondorJobInfo| {
+        info.dagman_id == Some(dag_id)
+            && info.node_name.as_deref().map(node_job_id) == Some(job_id.0.as_str())
+    };
+    let live_nodes: Vec<&CondorJobInfo> = live.iter().filter(is_node).collect();
+    if live_nodes.iter().any(|n| matches!(n.job_status, 2 | 6)) {
+        return Some(CondorNodeState::Active(engine::JobStatus::Running));
+    }
+    if live_nodes.iter().any(|n| matches!(n.job_status, 1 | 5 | 7)) {
+        return Some(CondorNodeState::Active(engine::JobStatus::Queued));
+    }
+    if live
+        .iter()
+        .any(|info| info.cluster_id == dag_id && info.dagman_id.is_none())
+    {
+        return Some(CondorNodeState::Active(engine::JobStatus::Queued));
+    }
+
+    let finished: Vec<&CondorJobInfo> = history?.iter().filter(is_node).collect();
+    let reason = if finished.iter().any(|n| n.job_status == 3) {
+        "removed from the queue".to_string()
+    } else if let Some(code) = finished
+        .iter()
+        .filter_map(|n| n.exit_code)
+        .find(|&code| code != 0)
+    {
+        format!("exited with code {}", code)
+    } else if finished.is_empty() {
+        "DAG finished without running the job".to_string()
+    } else {
+        "completed without outcome markers".to_string()
+    };
+    Some(CondorNodeState::Failed(reason))
+}
+
+fn condor_memory(mem: &str) -> Option<String> {
+    let mem = mem.trim();
+    let split = mem.find(|c: char| !c.is_ascii_digit()).unwrap_or(mem.len());
+    let (amount, unit) = mem.split_at(split);
+    let amount: u64 = amount.parse().ok()?;
+    let unit = match unit.to_ascii_uppercase().as_str() {
+        "" | "M" | "MB" => "MB",
+        "K" | "KB" => "KB",
+        "G" | "GB" => "GB",
+        "T" | "TB" => "TB",
+        _ => return None,
+    };
+    Some(format!("{}{}", amount, unit))
+}
+
+fn resource_lines(job_id: &JobId, directives: &SbatchDirectives) -> Result<String> {
+    let mut s = String::new();
+    if let Some(c) = directives.cpus_per_task {
+        s.push_str(&format!("request_cpus = {}\n", c));
+    }
+    if let Some(m) = &directives.mem {
+        let mem = condor_memory(m).ok_or_else(|| {
+            AppError::ConfigurationError(format!(
+                "Cannot translate mem = '{}' of job '{}' into an HTCondor memory request.",
+                m, job_id
+            ))
+        })?;
+        s.push_str(&format!("request_memory = {}\n", mem));
+    }
+    if let Some(t) = &directives.time {
+        let seconds = resources::parse_walltime(t).ok_or_else(|| {
+            AppError::ConfigurationError(format!(
+                "Cannot translate time = '{}' of job '{}' into an HTCondor job duration.",
+                t, job_id
+            ))
+        })?;
+        s.push_str(&format!("allowed_job_duration = {}\n", seconds));
+    }
+    Ok(s)
+}
+
+fn generate_submit_description(
+    job_id: &JobId,
+    executable: &str,
+    log_path: &str,
+    directives: &SbatchDirectives,
+) -> Result<String> {
+    let mut s = String::from("universe = vanilla\n");
+    s.push_str(&format!("executable = {}\n", executable));
+    s.push_str(&format!("output = {}\n", log_path));
+    s.push_str(&format!("error = {}\n", log_path));
+    s.push_str("should_transfer_files = NO\n");
+    s.push_str(&resource_lines(job_id, directives)?);
+    s.push_str("queue\n");
+    Ok(s)
+}
+
+fn generate_node_script(job_root_on_target: &Path, repx_command_to_wrap: &str) -> String {
+    let mut s = String::from("#!/usr/bin/env bash\n");
+    s.push_str("set -e\n\n");
+    s.push_str(&format!("cd {}\n", job_root_on_target.display()));
+    s.push_str(repx_command_to_wrap);
+    s.push('\n');
+    s
+}
+
+fn generate_dag(plan: &OrchestrationPlan) -> Result<String> {
+    let order = plan.submission_order()?;
+    let exit_node = |job_id: &JobId| {
+        if plan.jobs[job_id].job_type == "scatter-gather" {
+            format!("{}{}", job_id.0, GATHER_NODE_SUFFIX)
+        } else {
+            job_id.0.clone()
+        }
+    };
+
+    let mut lines = Vec::new();
+    let mut edges = Vec::new();
+    let mut child_requires_success: BTreeMap<&JobId, bool> = BTreeMap::new();
+    for job_id in &order {
+        let job_plan = &plan.jobs[job_id];
+        let submit_file = plan
+            .submissions_dir
+            .join(format!("{}.sub", job_plan.script_hash));
+        lines.push(format!("JOB {} {}", job_id.0, submit_file.display()));
+        if job_plan.job_type == "scatter-gather" {
+            let workers_node = format!("{}{}", job_id.0, WORKERS_NODE_SUFFIX);
+            let gather_node = format!("{}{}", job_id.0, GATHER_NODE_SUFFIX);
+            let workers_dag = plan
+                .outputs_dir
+                .join(&job_id.0)
+                .join("repx")
+                .join(CONDOR_WORKER_DAG_FILE);
+            let gather_submit_file = plan
+                .submissions_dir
+                .join(format!("{}-gather.sub", job_plan.script_hash));
+            lines.push(format!(
+                "SUBDAG EXTERNAL {} {}",
+                workers_node,
+                workers_dag.display()
+            ));
+            lines.push(format!("SCRIPT POST {} /bin/true", workers_node));
+            lines.push(format!(
+                "JOB {} {}",
+                gather_node,
+                gather_submit_file.display()
+            ));
+            edges.push(format!("PARENT {} CHILD {}", job_id.0, workers_node));
+            edges.push(format!("PARENT {} CHILD {}", workers_node, gather_node));
+        }
+        for dep in &job_plan.dependencies {
+            edges.push(format!("PARENT {} CHILD {}", exit_node(dep), job_id.0));
+            *child_requires_success.entry(dep).or_insert(false) |=
+                job_plan.dependency_type(dep).requires_success();
+        }
+    }
+
+    for (dep, requires_success) in child_requires_success {
+        let has_afterany_dependent = order.iter().any(|job_id| {
+            plan.jobs[job_id].dependencies.contains(dep)
+                && !plan.jobs[job_id].dependency_type(dep).requires_success()
+        });
+        if requires_success && has_afterany_dependent {
+            log_warn!(
+                "Job '{}' has both afterok and afterany dependents; DAGMan runs all of them only if it succeeds.",
+                dep
+            );
+        } else if !requires_success {
+            lines.push(format!("SCRIPT POST {} /bin/true", exit_node(dep)));
+        }
+    }
+
+    lines.extend(edges);
+    lines.push(String::new());
+    Ok(lines.join("\n"))
+}
+
+fn parse_dag_cluster_id(output: &str) -> Option<u32> {
+    output.lines().find_map(|line| {
+        let (_, rest) = line.split_once("submitted to cluster ")?;
+        rest.trim().trim_end_matches('.').parse().ok()
+    })
+}
+
+pub fn submit_condor_batch_run(
+    client: &Client,
+    jobs_to_submit: HashMap<JobId, &Job>,
+    target: Arc<dyn Target>,
+    target_name: &str,
+    remote_repx_binary_path: &Path,
+    options: &SubmitOptions,
+    send: impl Fn(ClientEvent),
+) -> Result<String> {
+    let remote_repx_command = remote_repx_binary_path.to_string_lossy();
+    send(ClientEvent::GeneratingSlurmScripts {
+        num_jobs: jobs_to_submit.len(),
+    });
+    let xdg_dirs = xdg::BaseDirectories::with_prefix("repx");
+    let cache_home = xdg_dirs.get_cache_home().ok_or_else(|| {
+        ClientError::Core(AppError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "Could not find cache home directory",
+        )))
+    })?;
+    let local_batch_dir = cache_home
+        .join("submissions")
+        .join(&client.lab.content_hash);
+    fs_err::create_dir_all(&local_batch_dir).map_err(AppError::from)?;
+
+    let condor_config = target.config().condor.clone().unwrap_or_default();
+    let batch_ctx = BatchContext {
+        client,
+        target: target.as_ref(),
+        target_name,
+        scheduler_config: &condor_config,
+        remote_repx_command: &remote_repx_command,
+        options,
+    };
+    let mut plan = OrchestrationPlan::new(target.base_path(), &client.lab.content_hash);
+    let job_ids_in_batch: HashSet<JobId> = jobs_to_submit.keys().cloned().collect();
+    let mut ignored_opts = false;
+
+    let write_node = |name: &str, script: &str, submit: &str| -> Result<()> {
+        fs_err::write(local_batch_dir.join(format!("{}.sh", name)), script)
+            .map_err(AppError::from)?;
+        fs_err::write(local_batch_dir.join(format!("{}.sub", name)), submit)
+            .map_err(AppError::from)?;
+        Ok(())
+    };
+
+    for (job_id, job) in &jobs_to_submit {
+        let job_root_on_target = target.base_path().join("outputs").join(&job_id.0);
+        let log_path = job_root_on_target.join("repx").join(CONDOR_LOG_FILE);
+        let (repx_command_to_wrap, directives) =
+            batch::job_command(&batch_ctx, job_id, job, WorkerDispatch::Condor)?;
+        ignored_opts |= directives.partition.is_some() || !directives.sbatch_opts.is_empty();
+
+        let resources = resource_lines(job_id, &directives)?;
+        let mut hasher = Sha256::new();
+        hasher.update(&repx_command_to_wrap);
+        hasher.update(&resources);
+        let script_hash = format!("{:x}", hasher.finalize());
+        let node_path = |name: &str, extension: &str| -> PathBuf {
+            plan.submissions_dir.join(format!("{}.{}", name, extension))
+        };
+
+        if job.stage_type == "scatter-gather" {
+            let workers_name = format!("{}-workers", script_hash);
+            let worker_directives =
+                resources::resolve_worker_resources(job_id, target_name, &options.resources);
+            let workers_submit = generate_submit_description(
+                job_id,
+                "$(script)",
+                &format!("$(repx_dir)/{}", CONDOR_LOG_FILE),
+                &worker_directives,
+            )?;
+            fs_err::write(
+                local_batch_dir.join(format!("{}.sub", workers_name)),
+                workers_submit,
+            )
+            .map_err(AppError::from)?;
+
+            let gather_name = format!("{}-gather", script_hash);
+            let gather_command = format!("{} --phase gather", repx_command_to_wrap);
+            let gather_log = job_root_on_target.join("repx").join(CONDOR_GATHER_LOG_FILE);
+            write_node(
+                &gather_name,
+                &generate_node_script(&job_root_on_target, &gather_command),
+                &generate_submit_description(
+                    job_id,
+                    &node_path(&gather_name, "sh").to_string_lossy(),
+                    &gather_log.to_string_lossy(),
+                    &directives,
+                )?,
+            )?;
+
+            let scatter_command = format!(
+                "{} --worker-submit-description {}",
+                repx_command_to_wrap,
+                node_path(&workers_name, "sub").display()
+            );
+            write_node(
+                &script_hash,
+                &generate_node_script(&job_root_on_target, &scatter_command),
+                &generate_submit_description(
+                    job_id,
+                    &node_path(&script_hash, "sh").to_string_lossy(),
+                    &log_path.to_string_lossy(),
+                    &directives,
+                )?,
+            )?;
+        } else {
+            write_node(
+                &script_hash,
+                &generate_node_script(&job_root_on_target, &repx_command_to_wrap),
+                &generate_submit_description(
+                    job_id,
+                    &node_path(&script_hash, "sh").to_string_lossy(),
+                    &log_path.to_string_lossy(),
+                    &directives,
+                )?,
+            )?;
+        }
+
+        plan.add_job(
+            job_id.clone(),
+            job,
+            script_hash,
+            &directives,
+            &job_ids_in_batch,
+        )?;
+    }
+    if ignored_opts {
+        log_warn!(
+            "Ignoring partition and sbatch_opts for HTCondor target '{}': they are SLURM-specific.",
+            target_name
+        );
+    }
+
+    let dag_content = generate_dag(&plan)?;
+    let dag_hash = format!("{:x}", Sha256::digest(dag_content.as_bytes()));
+    let dag_filename = format!("repx-{}.dag", &dag_hash[..12]);
+    fs_err::write(local_batch_dir.join(&dag_filename), dag_content).map_err(AppError::from)?;
+    let plan_content = serde_json::to_string_pretty(&plan).map_err(AppError::from)?;
+    fs_err::write(local_batch_dir.join("plan.json"), plan_content).map_err(AppError::from)?;
+
+    send(ClientEvent::ExecutingOrchestrator);
+    send(ClientEvent::SubmittingJobs {
+        total: jobs_to_submit.len(),
+    });
+
+    let submission_dir_on_target = target
+        .base_path()
+        .join("submissions")
+        .join(&client.lab.content_hash);
+    target.sync_directory(&local_batch_dir, &submission_dir_on_target)?;
+
+    let submit_command = format!(
+        "cd {} && chmod +x *.sh && condor_submit_dag -f {}",
+        submission_dir_on_target.display(),
+        dag_filename
+    );
+    let submit_output = target.run_command_once("sh", &["-c", &submit_command])?;
+    log_debug!(
+        "condor_submit_dag output on target '{}':\n---\n{}\n---",
+        target.name(),
+        submit_output
+    );
+    let dag_id = parse_dag_cluster_id(&submit_output).ok_or_else(|| {
+        ClientError::SlurmIdParse(format!(
+            "could not find the DAGMan cluster ID in condor_submit_dag output: {}",
+            submit_output.trim()
+        ))
+    })?;
+
+    let total_to_submit = jobs_to_submit.len();
+    let mut job_ids: Vec<&JobId> = jobs_to_submit.keys().collect();
+    job_ids.sort();
+    {
+        let mut slurm_map_guard = client.slurm_map.lock().unwrap();
+        for job_id in &job_ids {
+            slurm_map_guard.insert(
+                (*job_id).clone(),
+                TrackedJob::new(target_name, "condor", dag_id),
+            );
+        }
+    }
+    for (i, job_id) in job_ids.into_iter().enumerate() {
+        send(ClientEvent::JobSubmitted {
+            job_id: job_id.clone(),
+            slurm_id: dag_id,
+            total: total_to_submit,
+            current: i + 1,
+        });
+    }
+
+    client.save_slurm_map()?;
+    Ok(format!(
+        "Successfully submitted {} jobs as HTCondor DAG {}.",
+        total_to_submit, dag_id
+    ))
+}
+
+pub struct CondorScheduler;
+
+impl Scheduler for CondorScheduler {
+    fn name(&self) -> &str {
+        "condor"
+    }
+
+    fn config<'a>(&self, target_config: &'a config::Target) -> Option<&'a SchedulerConfig> {
+        target_config.condor.as_ref()
+    }
+
+    fn submit(
+        &self,
+        client: &Client,
+        jobs: HashMap<JobId, &Job>,
+        target: Arc<dyn Target>,
+        remote_repx_binary_path: &Path,
+        options: &SubmitOptions,
+        send: &dyn Fn(ClientEvent),
+    ) -> Result<String> {
+        let target_name = target.name().to_string();
+        submit_condor_batch_run(
+            client,
+            jobs,
+            target,
+            &target_name,
+            remote_repx_binary_path,
+            options,
+            send,
+        )
+    }
+
+    fn query_states(
+        &self,
+        client: &Client,
+        target: &dyn Target,
+        job_statuses: &mut HashMap<JobId, engine::JobStatus>,
+    ) -> Result<()> {
+        apply_condor_statuses(client, target, job_statuses);
+        Ok(())
+    }
+
+    fn cancel(&self, target: &dyn Target, job_id: &JobId, dag_id: u32) -> Result<()> {
+        target.condor_rm(dag_id, &node_names(job_id))
+    }
+
+    fn log_path(&self, target: &dyn Target, job_id: &JobId, _dag_id: u32) -> PathBuf {
+        job_repx_dir(target, job_id).join(CONDOR_LOG_FILE)
+    }
+}
+
+fn apply_condor_statuses(
+    client: &Client,
+    target: &dyn Target,
+    job_statuses: &mut HashMap<JobId, engine::JobStatus>,
+) {
+    let unresolved: Vec<(JobId, u32)> = client
+        .tracked_jobs(target.name(), "condor")
+        .into_iter()
+        .filter(|(job_id, _)| !job_statuses.contains_key(job_id))
+        .collect();
+    if unresolved.is_empty() {
+        return;
+    }
+
+    let mut dag_ids: Vec<u32> = unresolved.iter().map(|(_, id)| *id).collect();
+    dag_ids.sort_unstable();
+    dag_ids.dedup();
+    let Ok(live) = target.condor_q(&dag_ids) else {
+        return;
+    };
+    let finished_dags: Vec<u32> = dag_ids
+        .into_iter()
+        .filter(|dag_id| {
+            !live
+                .iter()
+                .any(|info| info.cluster_id == *dag_id && info.dagman_id.is_none())
+        })
+        .collect();
+    let history = if finished_dags.is_empty() {
+        None
+    } else {
+        target.condor_history(&finished_dags).ok()
+    };
+
+    for (job_id, dag_id) in unresolved {
+        let state = resolve_dag_job(&job_id, dag_id, &live, history.as_deref());
+        let status = match state {
+            Some(CondorNodeState::Active(status)) => status,
+            Some(CondorNodeState::Failed(reason)) => engine::JobStatus::SchedulerFailed {
+                location: target.name().to_string(),
+                reason,
+            },
+            None => continue,
+        };
+        job_statuses.insert(job_id, status);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestration::JobPlan;
+    use repx_core::model::DependencyType;
+
+    fn job_plan(script_hash: &str, job_type: &str, dependencies: &[&str]) -> JobPlan {
+        JobPlan {
+            script_hash: script_hash.to_string(),
+            dependencies: dependencies.iter().map(|d| JobId(d.to_string())).collect(),
+            job_type: job_type.to_string(),
+            bundle: None,
+            dependency_types: BTreeMap::new(),
+            directives: SbatchDirectives::default(),
+            on_timeout: None,
+        }
+    }
+
+    fn node(cluster_id: u32, dagman_id: Option<u32>, name: &str, status: u32) -> CondorJobInfo {
+        CondorJobInfo {
+            cluster_id,
+            dagman_id,
+            node_name: Some(name.to_string()).filter(|n| !n.is_empty()),
+            job_status: status,
+            exit_code: None,
+        }
+    }
+
+    #[test]
+    fn test_dag_with_scatter_gather() {
+        let mut plan = OrchestrationPlan::new(Path::new("/store"), "lab");
+        plan.jobs
+            .insert(JobId("job-a".into()), job_plan("aaa", "simple", &[]));
+        plan.jobs.insert(
+            JobId("job-sg".into()),
+            job_plan("sss", "scatter-gather", &["job-a"]),
+        );
+        let mut job_c = job_plan("ccc", "simple", &["job-sg"]);
+        job_c
+            .dependency_types
+            .insert(JobId("job-sg".into()), DependencyType::AfterAny);
+        plan.jobs.insert(JobId("job-c".into()), job_c);
+
+        assert_eq!(
+            generate_dag(&plan).unwrap(),
+            "JOB job-a /store/submissions/lab/aaa.sub\n\
+             JOB job-sg /store/submissions/lab/sss.sub\n\
+             SUBDAG EXTERNAL job-sg.workers /store/outputs/job-sg/repx/workers.dag\n\
+             SCRIPT POST job-sg.workers /bin/true\n\
+             JOB job-sg.gather /store/submissions/lab/sss-gather.sub\n\
+             JOB job-c /store/submissions/lab/ccc.sub\n\
+             SCRIPT POST job-sg.gather /bin/true\n\
+             PARENT job-sg CHILD job-sg.workers\n\
+             PARENT job-sg.workers CHILD job-sg.gather\n\
+             PARENT job-a CHILD job-sg\n\
+             PARENT job-sg.gather CHILD job-c\n"
+        );
+    }
+
+    #[test]
+    fn test_submit_description_resources() {
+        let directives = SbatchDirectives {
+            cpus_per_task: Some(2),
+            mem: Some("4G".into()),
+            time: Some("01:30:00".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            generate_submit_description(
+                &JobId("job-a".into()),
+                "/store/submissions/lab/aaa.sh",
+                "/store/outputs/job-a/repx/condor.out",
+                &directives,
+            )
+            .unwrap(),
+            "universe = vanilla\n\
+             executable = /store/submissions/lab/aaa.sh\n\
+             output = /store/outputs/job-a/repx/condor.out\n\
+             error = /store/outputs/job-a/repx/condor.out\n\
+             should_transfer_files = NO\n\
+             request_cpus = 2\n\
+             request_memory = 4GB\n\
+             allowed_job_duration = 5400\n\
+             queue\n"
+        );
+        assert_eq!(
+            parse_dag_cluster_id("Submitting job(s).\n1 job(s) submitted to cluster 4711.\n"),
+            Some(4711)
+        );
+    }
+
+    #[test]
+    fn test_resolve_dag_job_states() {
+        let job_a = JobId("job-a".into());
+        let job_sg = JobId("job-sg".into());
+        let job_c = JobId("job-c".into());
+        let live = vec![
+            node(900, None, "", 2),
+            node(901, Some(900), "job-a", 1),
+            node(902, Some(900), "job-sg.workers", 2),
+        ];
+        assert_eq!(
+            resolve_dag_job(&job_a, 900, &live, None),
+            Some(CondorNodeState::Active(engine::JobStatus::Queued))
+        );
+        assert_eq!(
+            resolve_dag_job(&job_sg, 900, &live, None),
+            Some(CondorNodeState::Active(engine::JobStatus::Running))
+        );
+        assert_eq!(
+            resolve_dag_job(&job_c, 900, &live, None),
+            Some(CondorNodeState::Active(engine::JobStatus::Queued))
+        );
+
+        let mut failed = node(903, Some(900), "job-sg.gather", 4);
+        failed.exit_code = Some(1);
+        let history = vec![node(901, Some(900), "job-a", 4), failed];
+        assert_eq!(resolve_dag_job(&job_a, 900, &[], None), None);
+        assert_eq!(
+            resolve_dag_job(&job_sg, 900, &[], Some(&history)),
+            Some(CondorNodeState::Failed("exited with code 1".into()))
+        );
+        assert_eq!(
+            resolve_dag_job(&job_a, 900, &[], Some(&history)),
+            Some(CondorNodeState::Failed(
+                "completed without outcome markers".into()
+            ))
+        );
+        assert_eq!(
+            resolve_dag_job(&job_c, 900, &[], Some(&history)),
+            Some(CondorNodeState::Failed(
+                "DAG finished without running the job".into()
+            ))
+        );
+    }
+}
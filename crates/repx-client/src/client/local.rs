@@ -1,27 +1,78 @@
+use super::scheduler::{job_repx_dir, Scheduler, JOB_STDOUT_LOG_FILE};
 use super::{Client, ClientEvent, SubmitOptions};
 use crate::error::{ClientError, Result};
+use crate::resources;
 use crate::targets::Target;
 use num_cpus;
 use repx_core::{
+    config::{self, SchedulerConfig},
     engine,
     error::AppError,
     model::{Job, JobId},
+    store::progress::WorkerProgress,
 };
 use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+const PROGRESS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+pub(crate) struct ExecutionHost {
+    pub target: Arc<dyn Target>,
+    pub repx_binary_path: PathBuf,
+    pub concurrency: usize,
+    pub cpus: usize,
+}
 
 pub fn submit_local_batch_run(
     client: &Client,
     jobs_in_batch: HashMap<JobId, &Job>,
     target: Arc<dyn Target>,
-    _target_name: &str,
+    target_name: &str,
     repx_binary_path: &Path,
     options: &SubmitOptions,
     send: impl Fn(ClientEvent),
 ) -> Result<String> {
+    let host = ExecutionHost {
+        target: target.clone(),
+        repx_binary_path: repx_binary_path.to_path_buf(),
+        concurrency: options.num_jobs.unwrap_or_else(num_cpus::get),
+        cpus: num_cpus::get(),
+    };
+    let submitted_count = run_on_hosts(
+        client,
+        jobs_in_batch,
+        target.as_ref(),
+        target_name,
+        "local",
+        target.config().local.as_ref(),
+        &[host],
+        options,
+        &send,
+        &|_, _, _| Ok(()),
+    )?;
+
+    Ok(format!(
+        "Successfully executed {} jobs locally.",
+        submitted_count
+    ))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn run_on_hosts(
+    client: &Client,
+    jobs_in_batch: HashMap<JobId, &Job>,
+    target: &dyn Target,
+    target_name: &str,
+    scheduler_name: &str,
+    scheduler_config: Option<&SchedulerConfig>,
+    hosts: &[ExecutionHost],
+    options: &SubmitOptions,
+    send: &dyn Fn(ClientEvent),
+    before_spawn: &dyn Fn(&JobId, &Job, &ExecutionHost) -> Result<()>,
+) -> Result<usize> {
     send(ClientEvent::SubmittingJobs {
         total: jobs_in_batch.len(),
     });
@@ -35,13 +86,17 @@ pub fn submit_local_batch_run(
         })
         .cloned()
         .collect();
-    let raw_statuses = client.get_statuses_for_active_target(target.name(), Some("local"))?;
+    let raw_statuses =
+        client.get_statuses_for_active_target(target.name(), Some(scheduler_name))?;
     let all_job_statuses = engine::determine_job_statuses(&client.lab, &raw_statuses);
     let mut completed_jobs: HashSet<JobId> = all_job_statuses
         .into_iter()
         .filter(|(id, status)| {
-            matches!(status, repx_core::engine::JobStatus::Succeeded { .. })
-                && (all_deps.contains(id) || jobs_in_batch.contains_key(id))
+            matches!(
+                status,
+                repx_core::engine::JobStatus::Succeeded { .. }
+                    | repx_core::engine::JobStatus::PartiallySucceeded { .. }
+            ) && (all_deps.contains(id) || jobs_in_batch.contains_key(id))
         })
         .map(|(id, _)| id)
         .collect();
@@ -51,20 +106,24 @@ pub fn submit_local_batch_run(
     let mut submitted_count = 0;
     let mut active_handles: Vec<(
         JobId,
+        usize,
+        usize,
         std::thread::JoinHandle<std::io::Result<std::process::Output>>,
     )> = vec![];
-    let concurrency = options.num_jobs.unwrap_or_else(num_cpus::get);
+    let mut reported_progress: HashMap<JobId, WorkerProgress> = HashMap::new();
+    let mut last_progress_poll = Instant::now();
 
     loop {
         let mut finished_indices = Vec::new();
-        for (i, (_id, handle)) in active_handles.iter().enumerate() {
+        for (i, (_id, _host, _slots, handle)) in active_handles.iter().enumerate() {
             if handle.is_finished() {
                 finished_indices.push(i);
             }
         }
 
         for i in finished_indices.into_iter().rev() {
-            let (job_id, handle) = active_handles.remove(i);
+            let (job_id, host_index, _slots, handle) = active_handles.remove(i);
+            let host_target = hosts[host_index].target.as_ref();
             let join_res = handle.join();
             match join_res {
                 Ok(output_res) => {
@@ -72,10 +131,19 @@ pub fn submit_local_batch_run(
 
                     if !output.status.success() {
                         let stderr = String::from_utf8_lossy(&output.stderr);
+                        let message = if hosts.len() == 1 {
+                            format!("Local execution of job '{}' failed.", job_id)
+                        } else {
+                            format!(
+                                "Execution of job '{}' on '{}' failed.",
+                                job_id,
+                                host_target.name()
+                            )
+                        };
                         return Err(ClientError::Core(AppError::ExecutionFailed {
-                            message: format!("Local execution of job '{}' failed.", job_id),
+                            message,
                             log_path: Some(
-                                target
+                                host_target
                                     .base_path()
                                     .join("outputs")
                                     .join(job_id.0)
@@ -87,6 +155,18 @@ pub fn submit_local_batch_run(
                             ),
                         }));
                     }
+                    if reported_progress.contains_key(&job_id) {
+                        if let Ok(mut progress_map) =
+                            target.read_worker_progress(std::slice::from_ref(&job_id))
+                        {
+                            if let Some(progress) = progress_map.remove(&job_id) {
+                                send(ClientEvent::WorkerProgress {
+                                    job_id: job_id.clone(),
+                                    progress,
+                                });
+                            }
+                        }
+                    }
                     completed_jobs.insert(job_id.clone());
                 }
                 Err(e) => {
@@ -103,9 +183,20 @@ pub fn submit_local_batch_run(
             break;
         }
 
-        let slots_available = concurrency.saturating_sub(active_handles.len());
-
-        if slots_available > 0 && !jobs_left.is_empty() {
+        let mut host_slots_left: Vec<usize> = hosts
+            .iter()
+            .enumerate()
+            .map(|(index, host)| {
+                let in_use: usize = active_handles
+                    .iter()
+                    .filter(|(_, host_index, _, _)| *host_index == index)
+                    .map(|(_, _, slots, _)| slots)
+                    .sum();
+                host.concurrency.saturating_sub(in_use)
+            })
+            .collect();
+
+        if host_slots_left.iter().any(|&slots| slots > 0) && !jobs_left.is_empty() {
             let mut ready_candidates: Vec<JobId> = jobs_left
                 .iter()
                 .filter(|job_id| {
@@ -131,6 +222,10 @@ pub fn submit_local_batch_run(
                 .collect();
 
             ready_candidates.sort();
+            let mut ready_scatter_jobs = ready_candidates
+                .iter()
+                .filter(|job_id| jobs_in_batch[*job_id].stage_type == "scatter-gather")
+                .count();
 
             if ready_candidates.is_empty() && active_handles.is_empty() {
                 return Err(ClientError::Core(AppError::ConfigurationError(
@@ -138,10 +233,37 @@ pub fn submit_local_batch_run(
                 )));
             }
 
-            for job_id in ready_candidates.into_iter().take(slots_available) {
+            for job_id in ready_candidates {
+                let Some((host_index, slots_left)) = host_slots_left
+                    .iter()
+                    .copied()
+                    .enumerate()
+                    .filter(|(_, slots)| *slots > 0)
+                    .max_by_key(|(index, slots)| (*slots, std::cmp::Reverse(*index)))
+                else {
+                    break;
+                };
+                let host = &hosts[host_index];
+                let target = host.target.as_ref();
                 jobs_left.remove(&job_id);
                 let job = jobs_in_batch.get(&job_id).unwrap();
 
+                let job_slots = if job.stage_type == "scatter-gather" {
+                    let slots = worker_slots(
+                        &job_id,
+                        target_name,
+                        options,
+                        slots_left,
+                        ready_scatter_jobs,
+                        host.cpus,
+                    );
+                    ready_scatter_jobs -= 1;
+                    slots
+                } else {
+                    1
+                };
+                host_slots_left[host_index] -= job_slots;
+
                 let image_path_opt = client
                     .lab
                     .runs
@@ -157,7 +279,7 @@ pub fn submit_local_batch_run(
                     "native"
                 } else {
                     options.execution_type.as_deref().unwrap_or_else(|| {
-                        let scheduler_config = target.config().local.as_ref().unwrap();
+                        let scheduler_config = scheduler_config.unwrap();
                         target
                             .config()
                             .default_execution_type
@@ -251,6 +373,18 @@ pub fn submit_local_batch_run(
 
                     args.push("--worker-sbatch-opts".to_string());
                     args.push("".to_string());
+
+                    args.push("--worker-concurrency".to_string());
+                    args.push(job_slots.to_string());
+
+                    if let Some(threshold) = resources::resolve_min_worker_success(
+                        &job_id,
+                        target_name,
+                        &options.resources,
+                    ) {
+                        args.push("--min-worker-success".to_string());
+                        args.push(threshold.to_string());
+                    }
                 } else {
                     let main_exe = job.executables.get("main").unwrap();
                     let executable_path_on_target =
@@ -259,7 +393,8 @@ pub fn submit_local_batch_run(
                     args.push(executable_path_on_target.to_string_lossy().to_string());
                 }
 
-                let child = target.spawn_repx_job(repx_binary_path, &args)?;
+                before_spawn(&job_id, job, host)?;
+                let child = target.spawn_repx_job(&host.repx_binary_path, &args)?;
                 submitted_count += 1;
                 let pid = child.id();
 
@@ -271,7 +406,25 @@ pub fn submit_local_batch_run(
                 });
 
                 let handle = thread::spawn(move || child.wait_with_output());
-                active_handles.push((job_id, handle));
+                active_handles.push((job_id, host_index, job_slots, handle));
+            }
+        }
+
+        if last_progress_poll.elapsed() >= PROGRESS_POLL_INTERVAL {
+            last_progress_poll = Instant::now();
+            let scatter_gather_jobs: Vec<JobId> = active_handles
+                .iter()
+                .map(|(job_id, _, _, _)| job_id)
+                .filter(|job_id| jobs_in_batch[*job_id].stage_type == "scatter-gather")
+                .cloned()
+                .collect();
+            if let Ok(progress_map) = target.read_worker_progress(&scatter_gather_jobs) {
+                for (job_id, progress) in progress_map {
+                    if reported_progress.get(&job_id) != Some(&progress) {
+                        reported_progress.insert(job_id.clone(), progress);
+                        send(ClientEvent::WorkerProgress { job_id, progress });
+                    }
+                }
             }
         }
 
@@ -280,8 +433,110 @@ pub fn submit_local_batch_run(
         }
     }
 
-    Ok(format!(
-        "Successfully executed {} jobs locally.",
-        submitted_count
-    ))
+    Ok(submitted_count)
+}
+
+fn worker_slots(
+    job_id: &JobId,
+    target_name: &str,
+    options: &SubmitOptions,
+    slots_available: usize,
+    ready_scatter_jobs: usize,
+    cpus: usize,
+) -> usize {
+    let worker_directives =
+        resources::resolve_worker_resources(job_id, target_name, &options.resources);
+    fair_worker_slots(
+        slots_available,
+        ready_scatter_jobs,
+        worker_directives.cpus_per_task,
+        cpus,
+    )
+}
+
+/// Splits the free slots evenly between the ready scatter-gather jobs, so the first one
+/// does not take every slot and leave the others queued behind it.
+fn fair_worker_slots(
+    slots_available: usize,
+    ready_scatter_jobs: usize,
+    cpus_per_worker: Option<u32>,
+    cpus: usize,
+) -> usize {
+    let fair_share = slots_available.div_ceil(ready_scatter_jobs.max(1));
+    let cpu_limit = cpus_per_worker
+        .map(|per_task| (cpus / per_task.max(1) as usize).max(1))
+        .unwrap_or(fair_share);
+    fair_share.min(cpu_limit).max(1)
+}
+
+pub struct LocalScheduler;
+
+impl Scheduler for LocalScheduler {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    fn config<'a>(&self, target_config: &'a config::Target) -> Option<&'a SchedulerConfig> {
+        target_config.local.as_ref()
+    }
+
+    fn tracks_jobs(&self) -> bool {
+        false
+    }
+
+    fn submit(
+        &self,
+        client: &Client,
+        jobs: HashMap<JobId, &Job>,
+        target: Arc<dyn Target>,
+        repx_binary_path: &Path,
+        options: &SubmitOptions,
+        send: &dyn Fn(ClientEvent),
+    ) -> Result<String> {
+        let target_name = target.name().to_string();
+        submit_local_batch_run(
+            client,
+            jobs,
+            target,
+            &target_name,
+            repx_binary_path,
+            options,
+            send,
+        )
+    }
+
+    fn query_states(
+        &self,
+        _client: &Client,
+        _target: &dyn Target,
+        _job_statuses: &mut HashMap<JobId, engine::JobStatus>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn cancel(&self, _target: &dyn Target, _job_id: &JobId, _scheduler_id: u32) -> Result<()> {
+        Ok(())
+    }
+
+    fn log_path(&self, target: &dyn Target, job_id: &JobId, _scheduler_id: u32) -> PathBuf {
+        job_repx_dir(target, job_id).join(JOB_STDOUT_LOG_FILE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_worker_slots_are_shared_between_ready_scatter_jobs() {
+        assert_eq!(fair_worker_slots(8, 1, None, 8), 8);
+        assert_eq!(fair_worker_slots(8, 2, None, 8), 4);
+        assert_eq!(fair_worker_slots(8, 3, None, 8), 3);
+        assert_eq!(fair_worker_slots(5, 3, None, 8), 2);
+        assert_eq!(fair_worker_slots(1, 4, None, 8), 1);
+
+        assert_eq!(fair_worker_slots(8, 1, Some(4), 8), 2);
+        assert_eq!(fair_worker_slots(8, 4, Some(2), 8), 2);
+        assert_eq!(fair_worker_slots(2, 1, Some(16), 8), 1);
+    }
 }
@@ -0,0 +1,547 @@
+use super::batch::{self, record_submitted_jobs, BatchContext, WorkerDispatch};
+use super::scheduler::{job_repx_dir, Scheduler};
+use super::{Client, ClientEvent, SubmitOptions};
+use crate::error::{ClientError, Result};
+use crate::orchestration::{OrchestrationPlan, SUBMISSION_LOG_FILE};
+use crate::resources::{self, SbatchDirectives};
+use crate::targets::{parse_pbs_job_id, Target};
+use repx_core::{
+    config::{self, SchedulerConfig, SubmitFailurePolicy},
+    engine,
+    error::AppError,
+    log_debug, log_warn,
+    model::{DependencyType, Job, JobId},
+};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const PBS_LOG_FILE: &str = "pbs.out";
+const SUBMIT_SCRIPT_FILE: &str = "submit-pbs.sh";
+
+fn pbs_memory(mem: &str) -> Option<String> {
+    let mem = mem.trim();
+    let split = mem.find(|c: char| !c.is_ascii_digit()).unwrap_or(mem.len());
+    let (amount, unit) = mem.split_at(split);
+    let amount: u64 = amount.parse().ok()?;
+    let unit = match unit.to_ascii_uppercase().as_str() {
+        "" | "M" | "MB" => "mb",
+        "K" | "KB" => "kb",
+        "G" | "GB" => "gb",
+        "T" | "TB" => "tb",
+        _ => return None,
+    };
+    Some(format!("{}{}", amount, unit))
+}
+
+fn pbs_walltime(time: &str) -> Option<String> {
+    let seconds = resources::parse_walltime(time)?;
+    Some(format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        (seconds % 3600) / 60,
+        seconds % 60
+    ))
+}
+
+fn pbs_dependency_type(dependency_type: DependencyType) -> &'static str {
+    if dependency_type.requires_success() {
+        "afterok"
+    } else {
+        "afterany"
+    }
+}
+
+fn generate_pbs_script(
+    job_id: &JobId,
+    job_root_on_target: &Path,
+    directives: &SbatchDirectives,
+    repx_command_to_wrap: &str,
+) -> Result<String> {
+    let log_path = job_root_on_target.join("repx").join(PBS_LOG_FILE);
+    let mut s = String::from("#!/usr/bin/env bash\n");
+    s.push_str(&format!("#PBS -N repx-{}\n", job_id.0));
+    s.push_str(&format!("#PBS -o {}\n", log_path.display()));
+    s.push_str("#PBS -j oe\n");
+
+    if let Some(p) = &directives.partition {
+        s.push_str(&format!("#PBS -q {}\n", p));
+    }
+    let mut select = String::from("select=1");
+    if let Some(c) = directives.cpus_per_task {
+        select.push_str(&format!(":ncpus={}", c));
+    }
+    if let Some(m) = &directives.mem {
+        let mem = pbs_memory(m).ok_or_else(|| {
+            AppError::ConfigurationError(format!(
+                "Cannot translate mem = '{}' of job '{}' into a PBS memory request.",
+                m, job_id
+            ))
+        })?;
+        select.push_str(&format!(":mem={}", mem));
+    }
+    s.push_str(&format!("#PBS -l {}\n", select));
+    if let Some(t) = &directives.time {
+        let walltime = pbs_walltime(t).ok_or_else(|| {
+            AppError::ConfigurationError(format!(
+                "Cannot translate time = '{}' of job '{}' into a PBS walltime.",
+                t, job_id
+            ))
+        })?;
+        s.push_str(&format!("#PBS -l walltime={}\n", walltime));
+    }
+
+    s.push_str("\nset -e\n\n");
+    s.push_str(&format!("cd {}\n", job_root_on_target.display()));
+    s.push_str(repx_command_to_wrap);
+    s.push('\n');
+    Ok(s)
+}
+
+fn generate_submit_script(
+    plan: &OrchestrationPlan,
+    on_submit_failure: SubmitFailurePolicy,
+) -> Result<String> {
+    let order = plan.submission_order()?;
+    let var_names: HashMap<&JobId, String> = order
+        .iter()
+        .enumerate()
+        .map(|(i, job_id)| (job_id, format!("j{}", i)))
+        .collect();
+
+    let mut s = String::from("#!/bin/sh\n");
+    s.push_str(&format!(
+        "cd '{}' || exit 1\n",
+        plan.submissions_dir.display()
+    ));
+    s.push_str(&format!(": > {}\n", SUBMISSION_LOG_FILE));
+    s.push_str("submitted=''\n");
+    s.push_str("abort() {\n");
+    s.push_str("  echo \"qsub failed for job $1\" >&2\n");
+    if on_submit_failure == SubmitFailurePolicy::Cancel {
+        s.push_str(&format!(
+            "  if [ -n \"$submitted\" ]; then qdel $submitted && : > {}; fi\n",
+            SUBMISSION_LOG_FILE
+        ));
+    }
+    s.push_str("  exit 1\n}\n");
+
+    for job_id in &order {
+        let job_plan = &plan.jobs[job_id];
+        let var = &var_names[job_id];
+        let mut qsub = String::from("qsub");
+        if !job_plan.dependencies.is_empty() {
+            let mut by_type: Vec<(&str, Vec<String>)> = Vec::new();
+            for dep in &job_plan.dependencies {
+                let kind = pbs_dependency_type(job_plan.dependency_type(dep));
+                let dep_ref = format!("${}", var_names[dep]);
+                match by_type.iter_mut().find(|(k, _)| *k == kind) {
+                    Some((_, refs)) => refs.push(dep_ref),
+                    None => by_type.push((kind, vec![dep_ref])),
+                }
+            }
+            let spec: Vec<String> = by_type
+                .into_iter()
+                .map(|(kind, refs)| format!("{}:{}", kind, refs.join(":")))
+                .collect();
+            qsub.push_str(&format!(" -W depend={}", spec.join(",")));
+        }
+        s.push_str(&format!(
+            "{}=$({} {}.pbs) || abort '{}'\n",
+            var, qsub, job_plan.script_hash, job_id
+        ));
+        s.push_str(&format!("submitted=\"$submitted ${}\"\n", var));
+        s.push_str(&format!(
+            "echo \"{} ${} pbs\" >> {}\n",
+            job_id, var, SUBMISSION_LOG_FILE
+        ));
+        s.push_str(&format!("echo \"{} ${}\"\n", job_id, var));
+    }
+    Ok(s)
+}
+
+pub fn submit_pbs_batch_run(
+    client: &Client,
+    jobs_to_submit: HashMap<JobId, &Job>,
+    target: Arc<dyn Target>,
+    target_name: &str,
+    remote_repx_binary_path: &Path,
+    options: &SubmitOptions,
+    send: impl Fn(ClientEvent),
+) -> Result<String> {
+    let remote_repx_command = remote_repx_binary_path.to_string_lossy();
+    send(ClientEvent::GeneratingSlurmScripts {
+        num_jobs: jobs_to_submit.len(),
+    });
+    let xdg_dirs = xdg::BaseDirectories::with_prefix("repx");
+    let cache_home = xdg_dirs.get_cache_home().ok_or_else(|| {
+        ClientError::Core(AppError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "Could not find cache home directory",
+        )))
+    })?;
+    let local_batch_dir = cache_home
+        .join("submissions")
+        .join(&client.lab.content_hash);
+    fs_err::create_dir_all(&local_batch_dir).map_err(AppError::from)?;
+
+    let pbs_config = target.config().pbs.clone().unwrap_or_default();
+    let batch_ctx = BatchContext {
+        client,
+        target: target.as_ref(),
+        target_name,
+        scheduler_config: &pbs_config,
+        remote_repx_command: &remote_repx_command,
+        options,
+    };
+    let mut plan = OrchestrationPlan::new(target.base_path(), &client.lab.content_hash);
+    let job_ids_in_batch: HashSet<JobId> = jobs_to_submit.keys().cloned().collect();
+    let mut ignored_opts = false;
+
+    for (job_id, job) in &jobs_to_submit {
+        let job_root_on_target = target.base_path().join("outputs").join(&job_id.0);
+        let (repx_command_to_wrap, directives) =
+            batch::job_command(&batch_ctx, job_id, job, WorkerDispatch::InAllocation)?;
+        ignored_opts |= !directives.sbatch_opts.is_empty();
+
+        let script_content = generate_pbs_script(
+            job_id,
+            &job_root_on_target,
+            &directives,
+            &repx_command_to_wrap,
+        )?;
+        let script_hash = format!("{:x}", Sha256::digest(script_content.as_bytes()));
+        fs_err::write(
+            local_batch_dir.join(format!("{}.pbs", script_hash)),
+            script_content,
+        )
+        .map_err(AppError::from)?;
+
+        plan.add_job(
+            job_id.clone(),
+            job,
+            script_hash,
+            &directives,
+            &job_ids_in_batch,
+        )?;
+    }
+    if ignored_opts {
+        log_warn!(
+            "Ignoring sbatch_opts for PBS target '{}': they are SLURM-specific.",
+            target_name
+        );
+    }
+
+    let on_submit_failure = pbs_config.on_submit_failure.unwrap_or_default();
+    let plan_content = serde_json::to_string_pretty(&plan).map_err(AppError::from)?;
+    fs_err::write(local_batch_dir.join("plan.json"), plan_content).map_err(AppError::from)?;
+    fs_err::write(
+        local_batch_dir.join(SUBMIT_SCRIPT_FILE),
+        generate_submit_script(&plan, on_submit_failure)?,
+    )
+    .map_err(AppError::from)?;
+
+    send(ClientEvent::ExecutingOrchestrator);
+    send(ClientEvent::SubmittingJobs {
+        total: jobs_to_submit.len(),
+    });
+
+    let submission_dir_on_target = target
+        .base_path()
+        .join("submissions")
+        .join(&client.lab.content_hash);
+    target.sync_directory(&local_batch_dir, &submission_dir_on_target)?;
+
+    let submit_command = format!(
+        "sh {}",
+        submission_dir_on_target.join(SUBMIT_SCRIPT_FILE).display()
+    );
+    let total_to_submit = jobs_to_submit.len();
+    let submit_output = match target.run_command_once("sh", &["-c", &submit_command]) {
+        Ok(output) => output,
+        Err(e) => {
+            let submission_log = submission_dir_on_target.join(SUBMISSION_LOG_FILE);
+            let recorded = target
+                .run_command("cat", &[&submission_log.to_string_lossy()])
+                .unwrap_or_default();
+            let submitted_count = record_submitted_jobs(
+                client,
+                target_name,
+                "pbs",
+                &recorded,
+                parse_pbs_job_id,
+                total_to_submit,
+                &send,
+            );
+            client.save_slurm_map()?;
+            log_warn!(
+                "PBS submission failed on target '{}' after submitting {} of {} jobs (on_submit_failure = {}).",
+                target.name(),
+                submitted_count,
+                total_to_submit,
+                on_submit_failure
+            );
+            return Err(e);
+        }
+    };
+
+    log_debug!(
+        "qsub output on target '{}':\n---\n{}\n---",
+        target.name(),
+        submit_output
+    );
+
+    let submitted_count = record_submitted_jobs(
+        client,
+        target_name,
+        "pbs",
+        &submit_output,
+        parse_pbs_job_id,
+        total_to_submit,
+        &send,
+    );
+
+    client.save_slurm_map()?;
+    Ok(format!(
+        "Successfully submitted {} jobs via PBS.",
+        submitted_count
+    ))
+}
+
+pub struct PbsScheduler;
+
+impl Scheduler for PbsScheduler {
+    fn name(&self) -> &str {
+        "pbs"
+    }
+
+    fn config<'a>(&self, target_config: &'a config::Target) -> Option<&'a SchedulerConfig> {
+        target_config.pbs.as_ref()
+    }
+
+    fn parse_job_id(&self, id: &str) -> Option<u32> {
+        parse_pbs_job_id(id)
+    }
+
+    fn submit(
+        &self,
+        client: &Client,
+        jobs: HashMap<JobId, &Job>,
+        target: Arc<dyn Target>,
+        remote_repx_binary_path: &Path,
+        options: &SubmitOptions,
+        send: &dyn Fn(ClientEvent),
+    ) -> Result<String> {
+        let target_name = target.name().to_string();
+        submit_pbs_batch_run(
+            client,
+            jobs,
+            target,
+            &target_name,
+            remote_repx_binary_path,
+            options,
+            send,
+        )
+    }
+
+    fn query_states(
+        &self,
+        client: &Client,
+        target: &dyn Target,
+        job_statuses: &mut HashMap<JobId, engine::JobStatus>,
+    ) -> Result<()> {
+        apply_qstat_statuses(client, target, job_statuses);
+        Ok(())
+    }
+
+    fn cancel(&self, target: &dyn Target, _job_id: &JobId, pbs_id: u32) -> Result<()> {
+        target.qdel(pbs_id)
+    }
+
+    fn log_path(&self, target: &dyn Target, job_id: &JobId, _pbs_id: u32) -> PathBuf {
+        job_repx_dir(target, job_id).join(PBS_LOG_FILE)
+    }
+}
+
+fn apply_qstat_statuses(
+    client: &Client,
+    target: &dyn Target,
+    job_statuses: &mut HashMap<JobId, engine::JobStatus>,
+) {
+    let unresolved: Vec<(JobId, u32)> = client
+        .tracked_jobs(target.name(), "pbs")
+        .into_iter()
+        .filter(|(job_id, _)| {
+            matches!(
+                job_statuses.get(job_id),
+                None | Some(engine::JobStatus::Requeued { .. })
+            )
+        })
+        .collect();
+    if unresolved.is_empty() {
+        return;
+    }
+
+    let mut pbs_ids: Vec<u32> = unresolved.iter().map(|(_, id)| *id).collect();
+    pbs_ids.sort_unstable();
+    pbs_ids.dedup();
+    let Ok(pbs_jobs) = target.qstat(&pbs_ids) else {
+        return;
+    };
+
+    for (job_id, pbs_id) in unresolved {
+        let Some(info) = pbs_jobs.get(&pbs_id) else {
+            continue;
+        };
+        if let Some(status) = info.queue_status() {
+            job_statuses.entry(job_id).or_insert(status);
+        } else if let Some(reason) = info.failure_reason() {
+            job_statuses.insert(
+                job_id,
+                engine::JobStatus::SchedulerFailed {
+                    location: target.name().to_string(),
+                    reason,
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestration::JobPlan;
+    use std::collections::BTreeMap;
+
+    fn job_plan(script_hash: &str, dependencies: &[&str]) -> JobPlan {
+        JobPlan {
+            script_hash: script_hash.to_string(),
+            dependencies: dependencies.iter().map(|d| JobId(d.to_string())).collect(),
+            job_type: "simple".to_string(),
+            bundle: None,
+            dependency_types: BTreeMap::new(),
+            directives: SbatchDirectives::default(),
+            on_timeout: None,
+        }
+    }
+
+    #[test]
+    fn test_pbs_script_directives() {
+        let directives = SbatchDirectives {
+            partition: Some("workq".into()),
+            cpus_per_task: Some(4),
+            mem: Some("16G".into()),
+            time: Some("1-02:00:00".into()),
+            sbatch_opts: vec![],
+        };
+        let script = generate_pbs_script(
+            &JobId("abc-stage-one".into()),
+            Path::new("/store/outputs/abc-stage-one"),
+            &directives,
+            "repx internal-execute --job-id abc-stage-one",
+        )
+        .unwrap();
+        assert_eq!(
+            script,
+            "#!/usr/bin/env bash\n\
+             #PBS -N repx-abc-stage-one\n\
+             #PBS -o /store/outputs/abc-stage-one/repx/pbs.out\n\
+             #PBS -j oe\n\
+             #PBS -q workq\n\
+             #PBS -l select=1:ncpus=4:mem=16gb\n\
+             #PBS -l walltime=26:00:00\n\
+             \n\
+             set -e\n\
+             \n\
+             cd /store/outputs/abc-stage-one\n\
+             repx internal-execute --job-id abc-stage-one\n"
+        );
+        assert_eq!(pbs_memory("2000").as_deref(), Some("2000mb"));
+        assert_eq!(pbs_memory("lots"), None);
+    }
+
+    #[test]
+    fn test_submit_script_against_stub_qsub() {
+        let dir = tempfile::tempdir().unwrap();
+        let bin_dir = dir.path().join("bin");
+        let submissions_dir = dir.path().join("submissions").join("lab");
+        fs_err::create_dir_all(&bin_dir).unwrap();
+        fs_err::create_dir_all(&submissions_dir).unwrap();
+        let calls = dir.path().join("calls");
+        fs_err::write(
+            bin_dir.join("qsub"),
+            format!(
+                "#!/bin/sh\necho \"qsub $*\" >> '{calls}'\ncase \"$*\" in *fail.pbs) exit 1;; esac\nn=$(wc -l < '{calls}')\necho \"$((n + 100)).pbs-head\"\n",
+                calls = calls.display()
+            ),
+        )
+        .unwrap();
+        fs_err::write(
+            bin_dir.join("qdel"),
+            format!("#!/bin/sh\necho \"qdel $*\" >> '{}'\n", calls.display()),
+        )
+        .unwrap();
+        for stub in ["qsub", "qdel"] {
+            std::process::Command::new("chmod")
+                .arg("+x")
+                .arg(bin_dir.join(stub))
+                .status()
+                .unwrap();
+        }
+
+        let mut plan = OrchestrationPlan::new(dir.path(), "lab");
+        plan.jobs.insert(JobId("job-a".into()), job_plan("a", &[]));
+        plan.jobs
+            .insert(JobId("job-b".into()), job_plan("b", &["job-a"]));
+        let mut job_c = job_plan("c", &["job-a", "job-b"]);
+        job_c
+            .dependency_types
+            .insert(JobId("job-a".into()), DependencyType::AfterAny);
+        plan.jobs.insert(JobId("job-c".into()), job_c);
+
+        let run = |plan: &OrchestrationPlan| {
+            let script = generate_submit_script(plan, SubmitFailurePolicy::Cancel).unwrap();
+            let path = submissions_dir.join(SUBMIT_SCRIPT_FILE);
+            fs_err::write(&path, script).unwrap();
+            std::process::Command::new("sh")
+                .arg(&path)
+                .env(
+                    "PATH",
+                    format!(
+                        "{}:{}",
+                        bin_dir.display(),
+                        std::env::var("PATH").unwrap_or_default()
+                    ),
+                )
+                .output()
+                .unwrap()
+        };
+
+        let output = run(&plan);
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "job-a 101.pbs-head\njob-b 102.pbs-head\njob-c 103.pbs-head\n"
+        );
+        assert_eq!(
+            fs_err::read_to_string(&calls).unwrap(),
+            "qsub a.pbs\n\
+             qsub -W depend=afterok:101.pbs-head b.pbs\n\
+             qsub -W depend=afterany:101.pbs-head,afterok:102.pbs-head c.pbs\n"
+        );
+
+        fs_err::remove_file(&calls).unwrap();
+        plan.jobs
+            .insert(JobId("job-d".into()), job_plan("fail", &["job-c"]));
+        let output = run(&plan);
+        assert!(!output.status.success());
+        assert!(fs_err::read_to_string(&calls)
+            .unwrap()
+            .ends_with("qdel 101.pbs-head 102.pbs-head 103.pbs-head\n"));
+        assert_eq!(
+            fs_err::read_to_string(submissions_dir.join(SUBMISSION_LOG_FILE)).unwrap(),
+            ""
+        );
+    }
+}
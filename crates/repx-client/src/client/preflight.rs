@@ -0,0 +1,278 @@
+use super::sync::PendingArtifacts;
+use crate::error::{ClientError, Result};
+use crate::targets::{DiskSpace, Target};
+use indicatif::HumanBytes;
+use repx_core::{
+    config::DiskCheckPolicy,
+    engine::JobStatus,
+    log_debug, log_info, log_warn,
+    model::{Job, JobId, Lab},
+};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+const OUTPUT_SAMPLE_SIZE: usize = 20;
+
+struct FilesystemDemand {
+    paths: Vec<PathBuf>,
+    required_bytes: u64,
+    space: DiskSpace,
+}
+
+pub(crate) fn check_disk_space(
+    target: &dyn Target,
+    lab: &Lab,
+    pending: &PendingArtifacts,
+    job_statuses: &HashMap<JobId, JobStatus>,
+    jobs_to_run: &HashMap<JobId, &Job>,
+) -> Result<()> {
+    let policy = target.config().disk_check.unwrap_or_default();
+    if policy == DiskCheckPolicy::Off {
+        return Ok(());
+    }
+
+    let output_bytes = estimate_output_bytes(target, lab, job_statuses, jobs_to_run);
+    let mut requirements = vec![(
+        target.base_path().to_path_buf(),
+        pending.missing_bytes() + output_bytes,
+    )];
+    if let Some(node_local_path) = &target.config().node_local_path {
+        requirements.push((node_local_path.clone(), pending.image_bytes()));
+    }
+
+    let mut measured = Vec::new();
+    for (path, required_bytes) in requirements {
+        if required_bytes == 0 {
+            continue;
+        }
+        let space = match target.disk_space(&path) {
+            Ok(Some(space)) => space,
+            Ok(None) => {
+                log_debug!(
+                    "Could not determine the free space of '{}' on target '{}'",
+                    path.display(),
+                    target.name()
+                );
+                continue;
+            }
+            Err(e) => {
+                log_warn!(
+                    "Could not check the free space of '{}' on target '{}': {}",
+                    path.display(),
+                    target.name(),
+                    e
+                );
+                continue;
+            }
+        };
+        measured.push((path, required_bytes, space));
+    }
+
+    let shortages = find_shortages(measured);
+    if shortages.is_empty() {
+        log_info!(
+            "Disk space check passed on target '{}' ({} of artifacts, about {} of outputs)",
+            target.name(),
+            HumanBytes(pending.missing_bytes()),
+            HumanBytes(output_bytes)
+        );
+        return Ok(());
+    }
+    apply_policy(policy, target.name(), &shortages)
+}
+
+/// Paths on the same filesystem share its free space, so their demands are added up.
+fn find_shortages(measured: Vec<(PathBuf, u64, DiskSpace)>) -> Vec<String> {
+    let mut demands: BTreeMap<String, FilesystemDemand> = BTreeMap::new();
+    for (path, required_bytes, space) in measured {
+        let demand = demands
+            .entry(space.filesystem.clone())
+            .or_insert_with(|| FilesystemDemand {
+                paths: Vec::new(),
+                required_bytes: 0,
+                space,
+            });
+        demand.paths.push(path);
+        demand.required_bytes += required_bytes;
+    }
+
+    demands
+        .values()
+        .filter(|demand| demand.required_bytes > demand.space.usable_bytes())
+        .map(|demand| {
+            let paths: Vec<String> = demand
+                .paths
+                .iter()
+                .map(|path| format!("'{}'", path.display()))
+                .collect();
+            let limited_by = if demand.space.usable_bytes() < demand.space.available_bytes {
+                " within your quota"
+            } else {
+                ""
+            };
+            format!(
+                "{} needs about {} but only {} is free{}",
+                paths.join(" and "),
+                HumanBytes(demand.required_bytes),
+                HumanBytes(demand.space.usable_bytes()),
+                limited_by
+            )
+        })
+        .collect()
+}
+
+fn apply_policy(policy: DiskCheckPolicy, target_name: &str, shortages: &[String]) -> Result<()> {
+    if shortages.is_empty() {
+        return Ok(());
+    }
+    let message = shortages.join("; ");
+    match policy {
+        DiskCheckPolicy::Abort => Err(ClientError::InsufficientDiskSpace {
+            target: target_name.to_string(),
+            message: format!(
+                "{}. Free up space, or set disk_check = \"warn\" on the target to submit anyway.",
+                message
+            ),
+        }),
+        DiskCheckPolicy::Warn | DiskCheckPolicy::Off => {
+            log_warn!(
+                "Not enough disk space on target '{}': {}",
+                target_name,
+                message
+            );
+            Ok(())
+        }
+    }
+}
+
+fn estimate_output_bytes(
+    target: &dyn Target,
+    lab: &Lab,
+    job_statuses: &HashMap<JobId, JobStatus>,
+    jobs_to_run: &HashMap<JobId, &Job>,
+) -> u64 {
+    let mut submitted: BTreeMap<&str, u64> = BTreeMap::new();
+    for (job_id, job) in jobs_to_run {
+        *submitted.entry(stage_name(job_id, job)).or_default() += 1;
+    }
+
+    let mut finished: BTreeMap<&str, Vec<&JobId>> = BTreeMap::new();
+    for (job_id, status) in job_statuses {
+        let on_target = matches!(
+            status,
+            JobStatus::Succeeded { location } | JobStatus::PartiallySucceeded { location }
+                if location == target.name()
+        );
+        if let (true, Some(job)) = (on_target, lab.jobs.get(job_id)) {
+            finished
+                .entry(stage_name(job_id, job))
+                .or_default()
+                .push(job_id);
+        }
+    }
+
+    let outputs_path = target.base_path().join("outputs");
+    let mut total_bytes = 0;
+    for (stage, job_count) in submitted {
+        let Some(samples) = finished.get_mut(stage) else {
+            log_debug!(
+                "No finished '{}' jobs on target '{}' to estimate its output size from",
+                stage,
+                target.name()
+            );
+            continue;
+        };
+        samples.sort();
+        samples.truncate(OUTPUT_SAMPLE_SIZE);
+        let paths: Vec<PathBuf> = samples
+            .iter()
+            .map(|job_id| outputs_path.join(&job_id.0))
+            .collect();
+        match target.directory_size(&paths) {
+            Ok(sample_bytes) => total_bytes += sample_bytes / paths.len() as u64 * job_count,
+            Err(e) => log_debug!(
+                "Could not measure finished '{}' outputs on target '{}': {}",
+                stage,
+                target.name(),
+                e
+            ),
+        }
+    }
+    total_bytes
+}
+
+fn stage_name<'a>(job_id: &'a JobId, job: &'a Job) -> &'a str {
+    job.name.as_deref().unwrap_or_else(|| {
+        job_id
+            .0
+            .split_once('-')
+            .map_or(job_id.0.as_str(), |(_, name)| name)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn space(filesystem: &str, available_bytes: u64, quota: Option<u64>) -> DiskSpace {
+        DiskSpace {
+            filesystem: filesystem.to_string(),
+            available_bytes,
+            quota_available_bytes: quota,
+        }
+    }
+
+    #[test]
+    fn paths_on_one_filesystem_share_its_free_space() {
+        let shortages = find_shortages(vec![
+            (
+                PathBuf::from("/scratch/repx"),
+                600,
+                space("/dev/sda1", 1000, None),
+            ),
+            (
+                PathBuf::from("/scratch/local"),
+                600,
+                space("/dev/sda1", 1000, None),
+            ),
+        ]);
+        assert_eq!(shortages.len(), 1);
+        assert!(shortages[0].starts_with("'/scratch/repx' and '/scratch/local' needs about"));
+    }
+
+    #[test]
+    fn separate_filesystems_are_checked_on_their_own() {
+        let shortages = find_shortages(vec![
+            (
+                PathBuf::from("/home/repx"),
+                600,
+                space("/dev/sda1", 1000, None),
+            ),
+            (PathBuf::from("/tmp/local"), 600, space("tmpfs", 500, None)),
+        ]);
+        assert_eq!(shortages.len(), 1);
+        assert!(shortages[0].starts_with("'/tmp/local' needs about"));
+    }
+
+    #[test]
+    fn quota_limits_the_usable_space() {
+        let shortages = find_shortages(vec![(
+            PathBuf::from("/home/repx"),
+            600,
+            space("/dev/sda1", 1000, Some(500)),
+        )]);
+        assert_eq!(shortages.len(), 1);
+        assert!(shortages[0].ends_with("within your quota"));
+    }
+
+    #[test]
+    fn abort_policy_fails_and_warn_policy_continues() {
+        let shortages = vec!["/home/repx needs about 1 GiB".to_string()];
+        assert!(matches!(
+            apply_policy(DiskCheckPolicy::Abort, "cluster", &shortages),
+            Err(ClientError::InsufficientDiskSpace { .. })
+        ));
+        assert!(apply_policy(DiskCheckPolicy::Warn, "cluster", &shortages).is_ok());
+        assert!(apply_policy(DiskCheckPolicy::Abort, "cluster", &[]).is_ok());
+    }
+}
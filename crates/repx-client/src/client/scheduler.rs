@@ -0,0 +1,201 @@
+use super::{condor, local, pbs, slurm, ssh_pool, Client, ClientEvent, SubmitOptions};
+use crate::error::Result;
+use crate::targets::Target;
+use repx_core::{
+    config::{self, SchedulerConfig},
+    engine,
+    model::{Job, JobId},
+};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+pub(crate) const JOB_STDOUT_LOG_FILE: &str = "stdout.log";
+
+pub trait Scheduler: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn config<'a>(&self, target_config: &'a config::Target) -> Option<&'a SchedulerConfig>;
+
+    fn tracks_jobs(&self) -> bool {
+        true
+    }
+
+    fn parse_job_id(&self, id: &str) -> Option<u32> {
+        id.trim().parse().ok()
+    }
+
+    fn submit(
+        &self,
+        client: &Client,
+        jobs: HashMap<JobId, &Job>,
+        target: Arc<dyn Target>,
+        remote_repx_binary_path: &Path,
+        options: &SubmitOptions,
+        send: &dyn Fn(ClientEvent),
+    ) -> Result<String>;
+
+    fn query_states(
+        &self,
+        client: &Client,
+        target: &dyn Target,
+        job_statuses: &mut HashMap<JobId, engine::JobStatus>,
+    ) -> Result<()>;
+
+    fn cancel(&self, target: &dyn Target, job_id: &JobId, scheduler_id: u32) -> Result<()>;
+
+    fn log_path(&self, target: &dyn Target, job_id: &JobId, scheduler_id: u32) -> PathBuf;
+}
+
+pub fn default_schedulers() -> Vec<Arc<dyn Scheduler>> {
+    vec![
+        Arc::new(slurm::SlurmScheduler),
+        Arc::new(pbs::PbsScheduler),
+        Arc::new(condor::CondorScheduler),
+        Arc::new(ssh_pool::SshPoolScheduler),
+        Arc::new(local::LocalScheduler),
+    ]
+}
+
+pub(crate) fn tracking_scheduler<'a>(
+    schedulers: &'a [Arc<dyn Scheduler>],
+    target_config: &config::Target,
+) -> Option<&'a Arc<dyn Scheduler>> {
+    schedulers
+        .iter()
+        .find(|s| s.tracks_jobs() && s.config(target_config).is_some())
+}
+
+pub(crate) fn schedulers_by_name(
+    schedulers: &[Arc<dyn Scheduler>],
+    names: &BTreeSet<String>,
+) -> Vec<Arc<dyn Scheduler>> {
+    names
+        .iter()
+        .filter_map(|name| schedulers.iter().find(|s| s.name() == name))
+        .filter(|s| s.tracks_jobs())
+        .cloned()
+        .collect()
+}
+
+pub(crate) fn job_repx_dir(target: &dyn Target, job_id: &JobId) -> PathBuf {
+    target
+        .base_path()
+        .join("outputs")
+        .join(&job_id.0)
+        .join("repx")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeScheduler;
+
+    impl Scheduler for FakeScheduler {
+        fn name(&self) -> &str {
+            "fake"
+        }
+
+        fn config<'a>(&self, target_config: &'a config::Target) -> Option<&'a SchedulerConfig> {
+            target_config.local.as_ref()
+        }
+
+        fn submit(
+            &self,
+            _client: &Client,
+            jobs: HashMap<JobId, &Job>,
+            _target: Arc<dyn Target>,
+            _remote_repx_binary_path: &Path,
+            _options: &SubmitOptions,
+            _send: &dyn Fn(ClientEvent),
+        ) -> Result<String> {
+            Ok(format!("{} jobs", jobs.len()))
+        }
+
+        fn query_states(
+            &self,
+            _client: &Client,
+            _target: &dyn Target,
+            job_statuses: &mut HashMap<JobId, engine::JobStatus>,
+        ) -> Result<()> {
+            job_statuses.insert(JobId("job-a".into()), engine::JobStatus::Queued);
+            Ok(())
+        }
+
+        fn cancel(&self, _target: &dyn Target, _job_id: &JobId, _scheduler_id: u32) -> Result<()> {
+            Ok(())
+        }
+
+        fn log_path(&self, _target: &dyn Target, job_id: &JobId, scheduler_id: u32) -> PathBuf {
+            PathBuf::from(format!("{}-{}.log", job_id, scheduler_id))
+        }
+    }
+
+    fn target_config(toml_src: &str) -> config::Target {
+        toml::from_str(&format!("base_path = \"/tmp/repx\"\n{}", toml_src)).unwrap()
+    }
+
+    fn tracked_name(schedulers: &[Arc<dyn Scheduler>], target: &config::Target) -> Option<String> {
+        tracking_scheduler(schedulers, target).map(|s| s.name().to_string())
+    }
+
+    #[test]
+    fn test_unrecorded_jobs_fall_back_to_the_configured_scheduler() {
+        let schedulers = default_schedulers();
+        let slurm_and_pbs = target_config(
+            "[slurm]\nexecution_types = [\"native\"]\n[pbs]\nexecution_types = [\"native\"]",
+        );
+        let condor = target_config("[condor]\nexecution_types = [\"native\"]");
+        let local = target_config("[local]\nexecution_types = [\"native\"]");
+
+        assert_eq!(
+            tracked_name(&schedulers, &slurm_and_pbs).as_deref(),
+            Some("slurm")
+        );
+        assert_eq!(
+            tracked_name(&schedulers, &condor).as_deref(),
+            Some("condor")
+        );
+        assert_eq!(tracked_name(&schedulers, &local), None);
+    }
+
+    #[test]
+    fn test_recorded_scheduler_names_select_schedulers() {
+        let schedulers = default_schedulers();
+        let names = |names: &[&str]| -> Vec<String> {
+            let names: BTreeSet<String> = names.iter().map(|n| n.to_string()).collect();
+            schedulers_by_name(&schedulers, &names)
+                .iter()
+                .map(|s| s.name().to_string())
+                .collect()
+        };
+
+        assert_eq!(names(&["pbs"]), ["pbs"]);
+        assert_eq!(names(&["slurm", "pbs"]), ["pbs", "slurm"]);
+        assert_eq!(names(&["local", "condor", "unknown"]), ["condor"]);
+    }
+
+    #[test]
+    fn test_registered_scheduler_is_used_for_tracking() {
+        let mut schedulers = default_schedulers();
+        schedulers.retain(|s| s.name() != "local");
+        schedulers.push(Arc::new(FakeScheduler));
+        let local = target_config("[local]\nexecution_types = [\"native\"]");
+
+        let scheduler = tracking_scheduler(&schedulers, &local).unwrap();
+        assert_eq!(scheduler.name(), "fake");
+        assert_eq!(
+            scheduler.log_path(
+                &crate::targets::local::LocalTarget {
+                    name: "local".into(),
+                    config: local.clone(),
+                    local_tools_path: PathBuf::new(),
+                },
+                &JobId("job-a".into()),
+                7
+            ),
+            PathBuf::from("job-a-7.log")
+        );
+    }
+}
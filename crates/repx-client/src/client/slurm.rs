@@ -1,54 +1,110 @@
+use super::batch::{self, record_submitted_jobs, BatchContext, WorkerDispatch};
+use super::scheduler::{job_repx_dir, Scheduler};
 use super::{Client, ClientEvent, SubmitOptions};
 use crate::error::{ClientError, Result};
-use crate::orchestration::OrchestrationPlan;
+use crate::orchestration::{
+    OrchestrationPlan, BUNDLE_JOB_PREFIX, BUNDLE_SKIP_MARKER, ORCHESTRATOR_LOG_FILE,
+    SUBMISSION_LOG_FILE,
+};
 use crate::resources::{self, SbatchDirectives};
-use crate::targets::Target;
+use crate::targets::{self, orchestrator_command, SlurmJobInfo, SlurmState, Target};
 use fs_err;
 use repx_core::{
+    config::{self, SchedulerConfig, SubmitFailurePolicy},
+    engine,
     error::AppError,
-    log_debug,
+    log_debug, log_info, log_warn,
     model::{Job, JobId},
 };
 use sha2::{Digest, Sha256};
 use std::collections::{HashMap, HashSet};
 use std::io::Write;
-use std::path::Path;
-use std::str::FromStr;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use xdg;
 
+const REPX_COMMAND_PLACEHOLDER: &str = "{{repx_command}}";
+const DIRECTIVES_PLACEHOLDER: &str = "{{directives}}";
+
+fn load_sbatch_template(path: &Path) -> Result<String> {
+    let template = fs_err::read_to_string(path).map_err(AppError::from)?;
+    let invalid = |reason: String| {
+        ClientError::Core(AppError::ConfigurationError(format!(
+            "sbatch template '{}' {}.",
+            path.display(),
+            reason
+        )))
+    };
+    for placeholder in [DIRECTIVES_PLACEHOLDER, REPX_COMMAND_PLACEHOLDER] {
+        if !template.contains(placeholder) {
+            return Err(invalid(format!(
+                "does not contain the required {} placeholder",
+                placeholder
+            )));
+        }
+    }
+    // sbatch stops reading #SBATCH lines at the first command, so the generated
+    // directives must come before any of the template's own commands.
+    for (number, line) in template.lines().enumerate() {
+        let line = line.trim();
+        if line == DIRECTIVES_PLACEHOLDER {
+            break;
+        }
+        if !line.is_empty() && !line.starts_with('#') {
+            return Err(invalid(format!(
+                "has a command on line {} before {}, so sbatch would ignore the generated directives",
+                number + 1,
+                DIRECTIVES_PLACEHOLDER
+            )));
+        }
+    }
+    Ok(template)
+}
+
 fn generate_repx_invoker_script(
     job_id: &JobId,
     job_root_on_target: &Path,
     directives: &SbatchDirectives,
     repx_command_to_wrap: String,
+    template: Option<&str>,
 ) -> Result<String> {
-    let mut s = String::from("#!/usr/bin/env bash\n");
-    s.push_str(&format!("#SBATCH --job-name={}\n", job_id.0));
-    s.push_str(&format!(
+    let log_path = job_root_on_target.join("repx").join("slurm-%j.out");
+    let mut directive_lines = String::new();
+    directive_lines.push_str(&format!("#SBATCH --job-name={}\n", job_id.0));
+    directive_lines.push_str(&format!(
         "#SBATCH --chdir={}\n",
         job_root_on_target.display()
     ));
-    let log_path = job_root_on_target.join("repx").join("slurm-%j.out");
-    s.push_str(&format!("#SBATCH --output={}\n", log_path.display()));
-    s.push_str(&format!("#SBATCH --error={}\n", log_path.display()));
+    directive_lines.push_str(&format!("#SBATCH --output={}\n", log_path.display()));
+    directive_lines.push_str(&format!("#SBATCH --error={}\n", log_path.display()));
 
     if let Some(p) = &directives.partition {
-        s.push_str(&format!("#SBATCH --partition={}\n", p));
+        directive_lines.push_str(&format!("#SBATCH --partition={}\n", p));
     }
     if let Some(c) = directives.cpus_per_task {
-        s.push_str(&format!("#SBATCH --cpus-per-task={}\n", c));
+        directive_lines.push_str(&format!("#SBATCH --cpus-per-task={}\n", c));
     }
     if let Some(m) = &directives.mem {
-        s.push_str(&format!("#SBATCH --mem={}\n", m));
+        directive_lines.push_str(&format!("#SBATCH --mem={}\n", m));
     }
     if let Some(t) = &directives.time {
-        s.push_str(&format!("#SBATCH --time={}\n", t));
+        directive_lines.push_str(&format!("#SBATCH --time={}\n", t));
     }
     for opt in &directives.sbatch_opts {
-        s.push_str(&format!("#SBATCH {}\n", opt));
+        directive_lines.push_str(&format!("#SBATCH {}\n", opt));
+    }
+
+    if let Some(template) = template {
+        return Ok(template
+            .replace("{{job_id}}", &job_id.0)
+            .replace(DIRECTIVES_PLACEHOLDER, directive_lines.trim_end())
+            .replace("{{chdir}}", &job_root_on_target.to_string_lossy())
+            .replace("{{log_path}}", &log_path.to_string_lossy())
+            .replace(REPX_COMMAND_PLACEHOLDER, &repx_command_to_wrap));
     }
 
+    let mut s = String::from("#!/usr/bin/env bash\n");
+    s.push_str(&directive_lines);
     s.push_str("\nset -e\n\n");
     s.push_str("# This script invokes the repx binary to handle execution.\n");
     s.push_str(&repx_command_to_wrap);
@@ -84,137 +140,53 @@ pub fn submit_slurm_batch_run(
 
     let mut plan = OrchestrationPlan::new(target.base_path(), &client.lab.content_hash);
     let job_ids_in_batch: HashSet<JobId> = jobs_to_submit.keys().cloned().collect();
+    let max_jobs_per_bundle = target
+        .config()
+        .slurm
+        .as_ref()
+        .and_then(|c| c.max_jobs_per_bundle)
+        .filter(|&n| n > 1);
+
+    let target_template = target
+        .config()
+        .slurm
+        .as_ref()
+        .and_then(|c| c.sbatch_template.clone());
+    let mut templates: HashMap<PathBuf, String> = HashMap::new();
+    let slurm_config = target.config().slurm.clone().unwrap_or_default();
+    let batch_ctx = BatchContext {
+        client,
+        target: target.as_ref(),
+        target_name,
+        scheduler_config: &slurm_config,
+        remote_repx_command: &remote_repx_command,
+        options,
+    };
 
     for (job_id, job) in &jobs_to_submit {
         let job_root_on_target = target.base_path().join("outputs").join(&job_id.0);
-        let image_path_opt = client
-            .lab
-            .runs
-            .values()
-            .find(|r| r.jobs.contains(job_id))
-            .and_then(|r| r.image.as_deref());
-        let image_tag = image_path_opt
-            .and_then(|p| p.file_stem())
-            .and_then(|s| s.to_str());
-
-        let execution_type = if options.execution_type.is_none() && image_tag.is_none() {
-            "native"
-        } else {
-            options.execution_type.as_deref().unwrap_or_else(|| {
-                let scheduler_config = target.config().slurm.as_ref().unwrap();
-                target
-                    .config()
-                    .default_execution_type
-                    .as_deref()
-                    .filter(|&et| scheduler_config.execution_types.contains(&et.to_string()))
-                    .or_else(|| scheduler_config.execution_types.first().map(|s| s.as_str()))
-                    .unwrap_or("native")
-            })
-        };
-        let mut repx_args = format!(
-            "--job-id {} --runtime {} {} --base-path {} --host-tools-dir {}",
-            job_id,
-            execution_type,
-            image_tag
-                .map(|t| format!("--image-tag {}", t))
-                .unwrap_or_default(),
-            target.base_path().display(),
-            client.lab.host_tools_dir_name
-        );
-        if let Some(local_path) = &target.config().node_local_path {
-            repx_args.push_str(&format!(" --node-local-path {}", local_path.display()));
-        }
-        if target.config().mount_host_paths {
-            if !target.config().mount_paths.is_empty() {
-                return Err(ClientError::Core(AppError::ConfigurationError(
-                    "Cannot specify both 'mount_host_paths = true' and 'mount_paths'.".into(),
-                )));
-            }
-            repx_args.push_str(" --mount-host-paths");
-        } else {
-            for path in &target.config().mount_paths {
-                repx_args.push_str(&format!(" --mount-paths {}", path));
-            }
-        }
-        let (repx_command_to_wrap, directives) = if job.stage_type == "scatter-gather" {
-            let scatter_exe = job.executables.get("scatter").ok_or_else(|| {
-                AppError::ConfigurationError(
-                    "Scatter-gather job missing 'scatter' executable".into(),
-                )
-            })?;
-            let worker_exe = job.executables.get("worker").ok_or_else(|| {
-                AppError::ConfigurationError(
-                    "Scatter-gather job missing 'worker' executable".into(),
-                )
-            })?;
-            let gather_exe = job.executables.get("gather").ok_or_else(|| {
-                AppError::ConfigurationError(
-                    "Scatter-gather job missing 'gather' executable".into(),
-                )
-            })?;
-
-            let artifacts_base = target.artifacts_base_path();
-            let scatter_exe_path = artifacts_base.join(&scatter_exe.path);
-            let worker_exe_path = artifacts_base.join(&worker_exe.path);
-            let gather_exe_path = artifacts_base.join(&gather_exe.path);
-
-            let worker_outputs_json =
-                serde_json::to_string(&worker_exe.outputs).map_err(AppError::from)?;
-
-            let scatter_gather_args = format!(
-                "--job-package-path {} --scatter-exe-path {} --worker-exe-path {} --gather-exe-path {} --worker-outputs-json '{}' {}",
-                target.artifacts_base_path().join(format!("jobs/{}", job_id)).display(),
-                scatter_exe_path.display(),
-                worker_exe_path.display(),
-                gather_exe_path.display(),
-                worker_outputs_json,
-                if target.config().mount_host_paths {
-                    "--mount-host-paths".to_string()
-                } else {
-                    target
-                        .config()
-                        .mount_paths
-                        .iter()
-                        .map(|p| format!("--mount-paths {}", p))
-                        .collect::<Vec<_>>()
-                        .join(" ")
+        let (repx_command_to_wrap, directives) =
+            batch::job_command(&batch_ctx, job_id, job, WorkerDispatch::Slurm)?;
+
+        let template_path =
+            resources::resolve_sbatch_template(job_id, target_name, &options.resources)
+                .or_else(|| target_template.clone());
+        let template = match template_path {
+            Some(path) => {
+                if !templates.contains_key(&path) {
+                    let template = load_sbatch_template(&path)?;
+                    templates.insert(path.clone(), template);
                 }
-            );
-            let main_directives =
-                resources::resolve_for_job(job_id, target_name, &options.resources);
-            let worker_directives =
-                resources::resolve_worker_resources(job_id, target_name, &options.resources);
-            let worker_opts_str = worker_directives.to_shell_string();
-
-            let command = format!(
-                "{} internal-scatter-gather {} {} --worker-sbatch-opts='{}' --scheduler slurm --anchor-id $REPX_ANCHOR_ID",
-                remote_repx_command, repx_args, scatter_gather_args, worker_opts_str
-            );
-            (command, main_directives)
-        } else {
-            let main_exe = job.executables.get("main").ok_or_else(|| {
-                AppError::ConfigurationError(format!(
-                    "Simple job '{}' missing 'main' executable",
-                    job_id
-                ))
-            })?;
-            let executable_path_on_target = target.artifacts_base_path().join(&main_exe.path);
-
-            let repx_args = format!(
-                "{} --executable-path {}",
-                repx_args,
-                executable_path_on_target.display()
-            );
-            let directives = resources::resolve_for_job(job_id, target_name, &options.resources);
-            let command = format!("{} internal-execute {}", remote_repx_command, repx_args);
-            (command, directives)
+                templates.get(&path).map(|t| t.as_str())
+            }
+            None => None,
         };
-
         let script_content = generate_repx_invoker_script(
             job_id,
             &job_root_on_target,
             &directives,
             repx_command_to_wrap,
+            template,
         )?;
 
         let mut hasher = Sha256::new();
@@ -227,7 +199,29 @@ pub fn submit_slurm_batch_run(
         file.write_all(script_content.as_bytes())
             .map_err(AppError::from)?;
 
-        plan.add_job(job_id.clone(), job, script_hash, &job_ids_in_batch);
+        plan.add_job(
+            job_id.clone(),
+            job,
+            script_hash,
+            &directives,
+            &job_ids_in_batch,
+        )?;
+        let on_timeout = resources::resolve_on_timeout(job_id, target_name, &options.resources)
+            .filter(|_| job.stage_type != "scatter-gather");
+        if let Some(policy) = on_timeout {
+            if directives.time.is_some() {
+                plan.assign_timeout_policy(job_id, policy);
+            } else {
+                log_warn!(
+                    "Ignoring on_timeout for job '{}': it has no `time` to escalate.",
+                    job_id
+                );
+            }
+        } else if let Some(max_jobs) = max_jobs_per_bundle {
+            if job.stage_type != "scatter-gather" {
+                plan.assign_bundle(job_id, &directives, max_jobs);
+            }
+        }
     }
     let plan_filename = "plan.json";
     let plan_content = serde_json::to_string_pretty(&plan).map_err(AppError::from)?;
@@ -244,13 +238,81 @@ pub fn submit_slurm_batch_run(
         .join(&client.lab.content_hash);
     target.sync_directory(&local_batch_dir, &submission_dir_on_target)?;
 
-    let orchestrator_command = format!(
-        "{} internal-orchestrate {}",
-        remote_repx_command,
-        submission_dir_on_target.join(plan_filename).display()
-    );
+    let on_submit_failure = slurm_config.on_submit_failure.unwrap_or_default();
+    let mut orchestrator_args = vec!["--on-failure".to_string(), on_submit_failure.to_string()];
+    if let Some(max_in_flight) = slurm_config.max_in_flight_jobs {
+        orchestrator_args.extend(["--max-in-flight".to_string(), max_in_flight.to_string()]);
+    }
+    if let Some(rate) = slurm_config.max_submissions_per_minute {
+        orchestrator_args.extend(["--max-submissions-per-minute".to_string(), rate.to_string()]);
+    }
+    let plan_file_on_target = submission_dir_on_target.join(plan_filename);
+
+    let total_to_submit = jobs_to_submit.len();
+    if is_throttled(&slurm_config) || plan.needs_supervision() {
+        if on_submit_failure == SubmitFailurePolicy::Cancel {
+            log_warn!(
+                "on_submit_failure = \"cancel\" does not apply to the background orchestrator on target '{}'; jobs it has already submitted stay queued if a later submission fails.",
+                target.name()
+            );
+        }
+        let orchestrator_log = submission_dir_on_target.join(ORCHESTRATOR_LOG_FILE);
+        let background_command = format!(
+            "nohup {} > {} 2>&1 < /dev/null & echo $!",
+            orchestrator_command(
+                remote_repx_binary_path,
+                &orchestrator_args,
+                &plan_file_on_target
+            ),
+            orchestrator_log.display()
+        );
+        let pid_output = target.run_command_once("sh", &["-c", &background_command])?;
+        log_info!(
+            "Background orchestrator for {} jobs started on target '{}' (pid {}), logging to {}",
+            total_to_submit,
+            target.name(),
+            pid_output.trim(),
+            orchestrator_log.display()
+        );
+        return Ok(format!(
+            "The orchestrator keeps submitting and supervising {} jobs in the background on '{}' (log: {}).",
+            total_to_submit,
+            target.name(),
+            orchestrator_log.display()
+        ));
+    }
 
-    let orchestrator_output = target.run_command("sh", &["-c", &orchestrator_command])?;
+    let orchestrator_output = match target.run_orchestrator(
+        remote_repx_binary_path,
+        &orchestrator_args,
+        &plan_file_on_target,
+    ) {
+        Ok(output) => output,
+        Err(e) => {
+            let submission_log = submission_dir_on_target.join(SUBMISSION_LOG_FILE);
+            let recorded = target
+                .run_command("cat", &[&submission_log.to_string_lossy()])
+                .unwrap_or_default();
+            let submitted_count = record_submitted_jobs(
+                client,
+                target_name,
+                "slurm",
+                &recorded,
+                |id| id.parse().ok(),
+                total_to_submit,
+                &send,
+            );
+            client.save_slurm_map()?;
+            log_warn!(
+                "Orchestrator failed on target '{}' after submitting {} of {} jobs (on_submit_failure = {}).",
+                target.name(),
+                submitted_count,
+                total_to_submit,
+                on_submit_failure
+            );
+            return Err(e);
+        }
+    };
 
     log_debug!(
         "Orchestrator raw output on target '{}':\n---\n{}\n---",
@@ -258,29 +320,15 @@ pub fn submit_slurm_batch_run(
         orchestrator_output
     );
 
-    let mut submitted_count = 0;
-    let total_to_submit = jobs_to_submit.len();
-    for line in orchestrator_output.lines() {
-        let parts: Vec<_> = line.split_whitespace().collect();
-        if parts.len() == 2 {
-            if let (Ok(repx_id), Ok(slurm_id)) =
-                (JobId::from_str(parts[0]), parts[1].parse::<u32>())
-            {
-                client
-                    .slurm_map
-                    .lock()
-                    .unwrap()
-                    .insert(repx_id.clone(), (target_name.to_string(), slurm_id));
-                submitted_count += 1;
-                send(ClientEvent::JobSubmitted {
-                    job_id: repx_id,
-                    slurm_id,
-                    total: total_to_submit,
-                    current: submitted_count,
-                });
-            }
-        }
-    }
+    let submitted_count = record_submitted_jobs(
+        client,
+        target_name,
+        "slurm",
+        &orchestrator_output,
+        |id| id.parse().ok(),
+        total_to_submit,
+        &send,
+    );
 
     client.save_slurm_map()?;
     Ok(format!(
@@ -288,3 +336,257 @@ pub fn submit_slurm_batch_run(
         submitted_count
     ))
 }
+
+fn is_throttled(slurm_config: &SchedulerConfig) -> bool {
+    slurm_config.max_in_flight_jobs.is_some() || slurm_config.max_submissions_per_minute.is_some()
+}
+
+pub struct SlurmScheduler;
+
+impl Scheduler for SlurmScheduler {
+    fn name(&self) -> &str {
+        "slurm"
+    }
+
+    fn config<'a>(&self, target_config: &'a config::Target) -> Option<&'a SchedulerConfig> {
+        target_config.slurm.as_ref()
+    }
+
+    fn submit(
+        &self,
+        client: &Client,
+        jobs: HashMap<JobId, &Job>,
+        target: Arc<dyn Target>,
+        remote_repx_binary_path: &Path,
+        options: &SubmitOptions,
+        send: &dyn Fn(ClientEvent),
+    ) -> Result<String> {
+        let target_name = target.name().to_string();
+        submit_slurm_batch_run(
+            client,
+            jobs,
+            target,
+            &target_name,
+            remote_repx_binary_path,
+            options,
+            send,
+        )
+    }
+
+    fn query_states(
+        &self,
+        client: &Client,
+        target: &dyn Target,
+        job_statuses: &mut HashMap<JobId, engine::JobStatus>,
+    ) -> Result<()> {
+        let queued_jobs = target.squeue()?;
+        apply_squeue_statuses(client, target.name(), job_statuses, queued_jobs);
+        reconcile_with_sacct(client, target, job_statuses);
+        Ok(())
+    }
+
+    fn cancel(&self, target: &dyn Target, _job_id: &JobId, slurm_id: u32) -> Result<()> {
+        target.scancel(slurm_id)
+    }
+
+    fn log_path(&self, target: &dyn Target, job_id: &JobId, slurm_id: u32) -> PathBuf {
+        job_repx_dir(target, job_id).join(format!("slurm-{}.out", slurm_id))
+    }
+}
+
+/// Marks `job_id` to be skipped by the bundle it shares with `members`. Returns true once
+/// every member is marked, at which point the whole allocation can be cancelled.
+pub(crate) fn cancel_bundle_member(
+    target: &dyn Target,
+    job_id: &JobId,
+    members: &[JobId],
+) -> Result<bool> {
+    target.write_remote_file(&job_repx_dir(target, job_id).join(BUNDLE_SKIP_MARKER), "")?;
+    let checks: Vec<String> = members
+        .iter()
+        .map(|member| {
+            let marker = job_repx_dir(target, member).join(BUNDLE_SKIP_MARKER);
+            format!(
+                "test -e {}",
+                targets::shell_quote(&marker.to_string_lossy())
+            )
+        })
+        .collect();
+    Ok(target
+        .run_command("sh", &["-c", &checks.join(" && ")])
+        .is_ok())
+}
+
+fn apply_squeue_statuses(
+    client: &Client,
+    target_name: &str,
+    job_statuses: &mut HashMap<JobId, engine::JobStatus>,
+    queued_jobs: HashMap<JobId, SlurmJobInfo>,
+) {
+    let squeue_status = |info: &SlurmJobInfo| {
+        if info.state == SlurmState::Running {
+            engine::JobStatus::Running
+        } else {
+            engine::JobStatus::Queued
+        }
+    };
+
+    let mut bundles = HashMap::new();
+    for (job_id, squeue_info) in queued_jobs {
+        if job_id.0.starts_with(BUNDLE_JOB_PREFIX) {
+            bundles.insert(squeue_info.slurm_id, squeue_status(&squeue_info));
+        } else {
+            job_statuses
+                .entry(job_id)
+                .or_insert_with(|| squeue_status(&squeue_info));
+        }
+    }
+
+    if bundles.is_empty() {
+        return;
+    }
+    for (job_id, slurm_id) in client.tracked_jobs(target_name, "slurm") {
+        if let Some(status) = bundles.get(&slurm_id) {
+            job_statuses.entry(job_id).or_insert_with(|| status.clone());
+        }
+    }
+}
+
+fn reconcile_with_sacct(
+    client: &Client,
+    target: &dyn Target,
+    job_statuses: &mut HashMap<JobId, engine::JobStatus>,
+) {
+    let unresolved: Vec<(JobId, u32)> = client
+        .tracked_jobs(target.name(), "slurm")
+        .into_iter()
+        .filter(|(job_id, _)| {
+            matches!(
+                job_statuses.get(job_id),
+                None | Some(engine::JobStatus::Requeued { .. })
+            )
+        })
+        .collect();
+    if unresolved.is_empty() {
+        return;
+    }
+
+    let mut slurm_ids: Vec<u32> = unresolved.iter().map(|(_, id)| *id).collect();
+    slurm_ids.sort_unstable();
+    slurm_ids.dedup();
+    let Ok(accounting) = target.sacct(&slurm_ids) else {
+        return;
+    };
+
+    let completed: Vec<JobId> = unresolved
+        .iter()
+        .filter(|(_, slurm_id)| {
+            accounting
+                .get(slurm_id)
+                .is_some_and(|info| info.state == "COMPLETED")
+        })
+        .map(|(job_id, _)| job_id.clone())
+        .collect();
+    let mut finished_since_snapshot = target.read_outcome_markers(&completed).unwrap_or_default();
+
+    for (job_id, slurm_id) in unresolved {
+        if let Some(status) = finished_since_snapshot.remove(&job_id) {
+            job_statuses.insert(job_id, status);
+        } else if let Some(reason) = accounting
+            .get(&slurm_id)
+            .and_then(|info| info.failure_reason())
+        {
+            job_statuses.insert(
+                job_id,
+                engine::JobStatus::SchedulerFailed {
+                    location: target.name().to_string(),
+                    reason,
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sbatch_template_rendering() {
+        let job_id = JobId("abc-stage-one".into());
+        let job_root = Path::new("/store/outputs/abc-stage-one");
+        let directives = SbatchDirectives {
+            partition: Some("compute".into()),
+            ..Default::default()
+        };
+        let template = "#!/bin/bash\n{{directives}}\n#SBATCH --account=lab\nmodule load podman\ncd {{chdir}}\n{{repx_command}}\nrm -rf /scratch/{{job_id}}\n";
+        let script = generate_repx_invoker_script(
+            &job_id,
+            job_root,
+            &directives,
+            "repx internal-execute --job-id abc-stage-one".into(),
+            Some(template),
+        )
+        .unwrap();
+        assert_eq!(
+            script,
+            "#!/bin/bash\n\
+             #SBATCH --job-name=abc-stage-one\n\
+             #SBATCH --chdir=/store/outputs/abc-stage-one\n\
+             #SBATCH --output=/store/outputs/abc-stage-one/repx/slurm-%j.out\n\
+             #SBATCH --error=/store/outputs/abc-stage-one/repx/slurm-%j.out\n\
+             #SBATCH --partition=compute\n\
+             #SBATCH --account=lab\n\
+             module load podman\n\
+             cd /store/outputs/abc-stage-one\n\
+             repx internal-execute --job-id abc-stage-one\n\
+             rm -rf /scratch/abc-stage-one\n"
+        );
+    }
+
+    #[test]
+    fn test_sbatch_template_requires_repx_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("template.sh");
+        fs_err::write(&path, "#!/bin/bash\n{{directives}}\n").unwrap();
+        assert!(load_sbatch_template(&path).is_err());
+
+        fs_err::write(&path, "#!/bin/bash\n{{repx_command}}\n").unwrap();
+        assert!(load_sbatch_template(&path).is_err());
+
+        fs_err::write(
+            &path,
+            "#!/bin/bash\nmodule load podman\n{{directives}}\n{{repx_command}}\n",
+        )
+        .unwrap();
+        assert!(load_sbatch_template(&path).is_err());
+
+        fs_err::write(
+            &path,
+            "#!/bin/bash\n\n#SBATCH --account=lab\n{{directives}}\n{{repx_command}}\n",
+        )
+        .unwrap();
+        assert!(load_sbatch_template(&path).is_ok());
+
+        fs_err::write(&path, "#!/bin/bash\n{{directives}}\n{{repx_command}}\n").unwrap();
+        assert!(load_sbatch_template(&path).is_ok());
+    }
+
+    #[test]
+    fn test_cancelling_a_bundle_member_skips_it_until_all_are_cancelled() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = crate::targets::local::LocalTarget {
+            name: "local".into(),
+            config: toml::from_str(&format!("base_path = \"{}\"", dir.path().display())).unwrap(),
+            local_tools_path: PathBuf::new(),
+        };
+        let members = vec![JobId("job-a".into()), JobId("job-b".into())];
+        let marker = |job: &JobId| job_repx_dir(&target, job).join(BUNDLE_SKIP_MARKER);
+
+        assert!(!cancel_bundle_member(&target, &members[0], &members).unwrap());
+        assert!(marker(&members[0]).exists());
+        assert!(!marker(&members[1]).exists());
+
+        assert!(cancel_bundle_member(&target, &members[1], &members).unwrap());
+    }
+}
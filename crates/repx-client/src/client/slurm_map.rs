@@ -0,0 +1,394 @@
+use crate::error::Result;
+use repx_core::{error::AppError, log_warn, model::JobId};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs::OpenOptions,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+pub(crate) const SLURM_MAP_FILE: &str = "slurm_map.json";
+const IMPORTED_LEGACY_MAP_FILE: &str = "slurm_map.json.imported";
+
+pub(crate) type TrackedSlurmJobs = HashMap<JobId, TrackedJob>;
+pub(crate) type TargetSlurmJobs = BTreeMap<JobId, SchedulerJob>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct SchedulerJob {
+    pub scheduler: String,
+    pub id: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TrackedJob {
+    pub target: String,
+    pub scheduler: String,
+    pub id: u32,
+}
+
+impl TrackedJob {
+    pub(crate) fn new(target: &str, scheduler: &str, id: u32) -> Self {
+        Self {
+            target: target.to_string(),
+            scheduler: scheduler.to_string(),
+            id,
+        }
+    }
+
+    fn scheduler_job(&self) -> SchedulerJob {
+        SchedulerJob {
+            scheduler: self.scheduler.clone(),
+            id: self.id,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredJob {
+    Tracked(SchedulerJob),
+    BareId(u32),
+}
+
+pub(crate) struct SlurmMapStore {
+    state_home: PathBuf,
+    lab_dir: PathBuf,
+    synced: Mutex<TrackedSlurmJobs>,
+    bare_id_schedulers: HashMap<String, String>,
+}
+
+impl SlurmMapStore {
+    /// `bare_id_schedulers` names, per target, the scheduler of entries written before the
+    /// map recorded one.
+    pub(crate) fn new(
+        state_home: &Path,
+        lab_hash: &str,
+        bare_id_schedulers: HashMap<String, String>,
+    ) -> Self {
+        Self {
+            state_home: state_home.to_path_buf(),
+            lab_dir: state_home.join("slurm").join(lab_hash),
+            synced: Mutex::new(HashMap::new()),
+            bare_id_schedulers,
+        }
+    }
+
+    fn map_path(&self, target_name: &str) -> PathBuf {
+        self.lab_dir.join(format!("{}.json", target_name))
+    }
+
+    fn lock_path(&self, target_name: &str) -> PathBuf {
+        self.lab_dir.join(format!("{}.lock", target_name))
+    }
+
+    /// On the first load for a lab, entries for its jobs are imported from the global map
+    /// older versions kept in the state directory.
+    pub(crate) fn load<'a>(
+        &self,
+        target_names: impl IntoIterator<Item = &'a String>,
+        is_lab_job: impl Fn(&JobId) -> bool,
+    ) -> TrackedSlurmJobs {
+        let target_names: Vec<&String> = target_names.into_iter().collect();
+        if !self.lab_dir.exists() {
+            if let Err(e) = self.import_legacy_map(&target_names, is_lab_job) {
+                log_warn!("Could not import the legacy SLURM job map: {}", e);
+            }
+        }
+
+        let mut tracked = HashMap::new();
+        for target_name in target_names {
+            let jobs = match self.read_target_map(target_name) {
+                Ok(jobs) => jobs,
+                Err(e) => {
+                    log_warn!(
+                        "Could not read the SLURM job map for target '{}': {}",
+                        target_name,
+                        e
+                    );
+                    BTreeMap::new()
+                }
+            };
+            for (job_id, job) in jobs {
+                tracked.insert(job_id, TrackedJob::new(target_name, &job.scheduler, job.id));
+            }
+        }
+        *self.synced.lock().unwrap() = tracked.clone();
+        tracked
+    }
+
+    pub(crate) fn save(
+        &self,
+        tracked: &mut TrackedSlurmJobs,
+    ) -> Result<BTreeMap<String, TargetSlurmJobs>> {
+        let mut synced = self.synced.lock().unwrap();
+        let target_names: BTreeSet<String> = tracked
+            .values()
+            .chain(synced.values())
+            .map(|job| job.target.clone())
+            .collect();
+        fs_err::create_dir_all(&self.lab_dir).map_err(AppError::from)?;
+
+        let mut merged = HashMap::new();
+        let mut changed_targets = BTreeMap::new();
+        for target_name in target_names {
+            let lock_file = OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(self.lock_path(&target_name))
+                .map_err(AppError::from)?;
+            lock_file.lock().map_err(AppError::from)?;
+
+            let map_path = self.map_path(&target_name);
+            let on_disk = self.read_target_map(&target_name)?;
+            let jobs = merge_target_jobs(&target_name, on_disk.clone(), tracked, &synced);
+            if jobs != on_disk {
+                let tmp_path = map_path.with_extension("json.tmp");
+                let json_string = serde_json::to_string_pretty(&jobs).map_err(AppError::from)?;
+                fs_err::write(&tmp_path, json_string).map_err(AppError::from)?;
+                fs_err::rename(&tmp_path, &map_path).map_err(AppError::from)?;
+                changed_targets.insert(target_name.clone(), jobs.clone());
+            }
+            drop(lock_file);
+
+            for (job_id, job) in jobs {
+                merged.insert(
+                    job_id,
+                    TrackedJob::new(&target_name, &job.scheduler, job.id),
+                );
+            }
+        }
+
+        *tracked = merged.clone();
+        *synced = merged;
+        Ok(changed_targets)
+    }
+
+    /// The legacy map is renamed rather than deleted once imported, so labs loaded later
+    /// can still import their own entries from it.
+    fn import_legacy_map(
+        &self,
+        target_names: &[&String],
+        is_lab_job: impl Fn(&JobId) -> bool,
+    ) -> Result<()> {
+        let legacy_path = self.state_home.join(SLURM_MAP_FILE);
+        let imported_path = self.state_home.join(IMPORTED_LEGACY_MAP_FILE);
+        let source = if legacy_path.exists() {
+            &legacy_path
+        } else if imported_path.exists() {
+            &imported_path
+        } else {
+            return Ok(());
+        };
+
+        let content = fs_err::read_to_string(source).map_err(AppError::from)?;
+        let legacy: HashMap<JobId, (String, u32)> =
+            serde_json::from_str(&content).map_err(AppError::from)?;
+        let mut imported: TrackedSlurmJobs = legacy
+            .into_iter()
+            .filter(|(job_id, (target_name, _))| {
+                is_lab_job(job_id) && target_names.contains(&target_name)
+            })
+            .map(|(job_id, (target_name, id))| {
+                let scheduler = self
+                    .bare_id_schedulers
+                    .get(&target_name)
+                    .map_or("slurm", String::as_str);
+                let job = TrackedJob::new(&target_name, scheduler, id);
+                (job_id, job)
+            })
+            .collect();
+        fs_err::create_dir_all(&self.lab_dir).map_err(AppError::from)?;
+        self.save(&mut imported)?;
+        *self.synced.lock().unwrap() = HashMap::new();
+
+        if source == &legacy_path {
+            fs_err::rename(&legacy_path, &imported_path).map_err(AppError::from)?;
+        }
+        Ok(())
+    }
+
+    /// A map that does not parse is moved aside, so the next save does not overwrite it.
+    fn read_target_map(&self, target_name: &str) -> Result<TargetSlurmJobs> {
+        let path = self.map_path(target_name);
+        if !path.exists() {
+            return Ok(BTreeMap::new());
+        }
+        let content = fs_err::read_to_string(&path).map_err(AppError::from)?;
+        let stored: BTreeMap<JobId, StoredJob> = match serde_json::from_str(&content) {
+            Ok(stored) => stored,
+            Err(e) => {
+                let corrupt_path = path.with_extension("json.corrupt");
+                fs_err::rename(&path, &corrupt_path).map_err(AppError::from)?;
+                log_warn!(
+                    "The SLURM job map '{}' is corrupt ({}). It was moved to '{}' and jobs it tracked will not be shown.",
+                    path.display(),
+                    e,
+                    corrupt_path.display()
+                );
+                return Ok(BTreeMap::new());
+            }
+        };
+        let bare_id_scheduler = self
+            .bare_id_schedulers
+            .get(target_name)
+            .map_or("slurm", String::as_str);
+        Ok(stored
+            .into_iter()
+            .map(|(job_id, job)| {
+                let job = match job {
+                    StoredJob::Tracked(job) => job,
+                    StoredJob::BareId(id) => SchedulerJob {
+                        scheduler: bare_id_scheduler.to_string(),
+                        id,
+                    },
+                };
+                (job_id, job)
+            })
+            .collect())
+    }
+}
+
+fn merge_target_jobs(
+    target_name: &str,
+    mut on_disk: TargetSlurmJobs,
+    tracked: &TrackedSlurmJobs,
+    synced: &TrackedSlurmJobs,
+) -> TargetSlurmJobs {
+    for (job_id, job) in synced {
+        if job.target == target_name
+            && !tracked.contains_key(job_id)
+            && on_disk.get(job_id) == Some(&job.scheduler_job())
+        {
+            on_disk.remove(job_id);
+        }
+    }
+    for (job_id, job) in tracked {
+        if job.target == target_name && synced.get(job_id) != Some(job) {
+            on_disk.insert(job_id.clone(), job.scheduler_job());
+        }
+    }
+    on_disk
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(target_name: &str, slurm_id: u32) -> TrackedJob {
+        TrackedJob::new(target_name, "slurm", slurm_id)
+    }
+
+    fn store(state_home: &Path, lab_hash: &str) -> SlurmMapStore {
+        SlurmMapStore::new(state_home, lab_hash, HashMap::new())
+    }
+
+    #[test]
+    fn test_save_merges_concurrent_writers() {
+        let state_home = tempfile::tempdir().unwrap();
+        let targets = vec!["cluster".to_string()];
+        let first = store(state_home.path(), "lab-hash");
+        let second = store(state_home.path(), "lab-hash");
+
+        let mut first_jobs = first.load(&targets, |_| true);
+        first_jobs.insert(JobId("job-a".into()), entry("cluster", 100));
+        first_jobs.insert(JobId("job-b".into()), entry("cluster", 101));
+        first.save(&mut first_jobs).unwrap();
+
+        let mut second_jobs = second.load(&targets, |_| true);
+        first_jobs.remove(&JobId("job-a".into()));
+        first.save(&mut first_jobs).unwrap();
+
+        second_jobs.insert(JobId("job-c".into()), entry("cluster", 102));
+        second.save(&mut second_jobs).unwrap();
+
+        let mut expected = HashMap::new();
+        expected.insert(JobId("job-b".into()), entry("cluster", 101));
+        expected.insert(JobId("job-c".into()), entry("cluster", 102));
+        assert_eq!(second_jobs, expected);
+        assert_eq!(
+            store(state_home.path(), "lab-hash").load(&targets, |_| true),
+            expected
+        );
+        assert!(store(state_home.path(), "other-lab")
+            .load(&targets, |_| true)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_entries_keep_the_scheduler_that_submitted_them() {
+        let state_home = tempfile::tempdir().unwrap();
+        let targets = vec!["cluster".to_string()];
+        let lab_dir = state_home.path().join("slurm").join("lab-hash");
+        fs_err::create_dir_all(&lab_dir).unwrap();
+        fs_err::write(lab_dir.join("cluster.json"), r#"{"job-old": 7}"#).unwrap();
+
+        let bare_id_schedulers = HashMap::from([("cluster".to_string(), "pbs".to_string())]);
+        let first = SlurmMapStore::new(state_home.path(), "lab-hash", bare_id_schedulers);
+        let mut jobs = first.load(&targets, |_| true);
+        assert_eq!(
+            jobs[&JobId("job-old".into())],
+            TrackedJob::new("cluster", "pbs", 7)
+        );
+        jobs.insert(
+            JobId("job-new".into()),
+            TrackedJob::new("cluster", "condor", 8),
+        );
+        first.save(&mut jobs).unwrap();
+
+        let reloaded = store(state_home.path(), "lab-hash").load(&targets, |_| true);
+        assert_eq!(reloaded[&JobId("job-old".into())].scheduler, "pbs");
+        assert_eq!(reloaded[&JobId("job-new".into())].scheduler, "condor");
+    }
+
+    #[test]
+    fn test_corrupt_map_is_moved_aside_before_saving() {
+        let state_home = tempfile::tempdir().unwrap();
+        let targets = vec!["cluster".to_string()];
+        let lab_dir = state_home.path().join("slurm").join("lab-hash");
+        fs_err::create_dir_all(&lab_dir).unwrap();
+        fs_err::write(lab_dir.join("cluster.json"), r#"{"job-a": {"sched"#).unwrap();
+
+        let first = store(state_home.path(), "lab-hash");
+        let mut jobs = first.load(&targets, |_| true);
+        assert!(jobs.is_empty());
+        jobs.insert(JobId("job-b".into()), entry("cluster", 101));
+        first.save(&mut jobs).unwrap();
+
+        assert_eq!(
+            fs_err::read_to_string(lab_dir.join("cluster.json.corrupt")).unwrap(),
+            r#"{"job-a": {"sched"#
+        );
+        let reloaded = store(state_home.path(), "lab-hash").load(&targets, |_| true);
+        assert_eq!(reloaded[&JobId("job-b".into())], entry("cluster", 101));
+    }
+
+    #[test]
+    fn test_first_load_imports_the_legacy_global_map() {
+        let state_home = tempfile::tempdir().unwrap();
+        let targets = vec!["cluster".to_string()];
+        fs_err::write(
+            state_home.path().join(SLURM_MAP_FILE),
+            r#"{"job-a": ["cluster", 100], "job-b": ["cluster", 101], "other-job": ["cluster", 200]}"#,
+        )
+        .unwrap();
+        let is_lab_job = |job_id: &JobId| job_id.0.starts_with("job-");
+
+        let jobs = store(state_home.path(), "lab-hash").load(&targets, is_lab_job);
+        let mut expected = HashMap::new();
+        expected.insert(JobId("job-a".into()), entry("cluster", 100));
+        expected.insert(JobId("job-b".into()), entry("cluster", 101));
+        assert_eq!(jobs, expected);
+        assert!(!state_home.path().join(SLURM_MAP_FILE).exists());
+        assert!(state_home.path().join(IMPORTED_LEGACY_MAP_FILE).exists());
+        assert_eq!(
+            store(state_home.path(), "lab-hash").load(&targets, |_| true),
+            expected
+        );
+
+        let other_lab =
+            store(state_home.path(), "other-lab").load(&targets, |job_id| job_id.0 == "other-job");
+        assert_eq!(other_lab[&JobId("other-job".into())], entry("cluster", 200));
+    }
+}
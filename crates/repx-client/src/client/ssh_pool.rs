@@ -0,0 +1,280 @@
+use super::local::{run_on_hosts, ExecutionHost};
+use super::scheduler::{job_repx_dir, Scheduler, JOB_STDOUT_LOG_FILE};
+use super::{Client, ClientEvent, SubmitOptions};
+use crate::error::{ClientError, Result};
+use crate::inputs;
+use crate::targets::Target;
+use repx_core::{
+    config::{self, SchedulerConfig},
+    engine,
+    error::AppError,
+    log_debug, log_info,
+    model::{Job, JobId},
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const HOST_FILE: &str = "ssh-host";
+
+pub fn submit_ssh_pool_batch_run(
+    client: &Client,
+    jobs_in_batch: HashMap<JobId, &Job>,
+    target: Arc<dyn Target>,
+    target_name: &str,
+    remote_repx_binary_path: &Path,
+    options: &SubmitOptions,
+    send: impl Fn(ClientEvent),
+) -> Result<String> {
+    let pool_config = target.config().ssh_pool.as_ref().ok_or_else(|| {
+        ClientError::Core(AppError::ConfigurationError(format!(
+            "Target '{}' has no [targets.{}.ssh-pool] section.",
+            target_name, target_name
+        )))
+    })?;
+    let hosts = execution_hosts(
+        client,
+        target.as_ref(),
+        pool_config,
+        remote_repx_binary_path,
+    )?;
+
+    let before_spawn = |job_id: &JobId, job: &Job, host: &ExecutionHost| -> Result<()> {
+        if host.target.base_path() != target.base_path() {
+            let executable_name = if job.stage_type == "scatter-gather" {
+                "scatter"
+            } else {
+                "main"
+            };
+            inputs::generate_and_write_inputs_json(
+                &client.lab,
+                &client.lab_path,
+                job,
+                job_id,
+                host.target.clone(),
+                executable_name,
+            )?;
+        }
+        target.write_remote_file(
+            &job_repx_dir(target.as_ref(), job_id).join(HOST_FILE),
+            host.target.name(),
+        )
+    };
+
+    let submitted_count = run_on_hosts(
+        client,
+        jobs_in_batch,
+        target.as_ref(),
+        target_name,
+        "ssh-pool",
+        Some(pool_config),
+        &hosts,
+        options,
+        &send,
+        &before_spawn,
+    )?;
+
+    Ok(format!(
+        "Successfully executed {} jobs on {} hosts.",
+        submitted_count,
+        hosts.len()
+    ))
+}
+
+fn execution_hosts(
+    client: &Client,
+    pool_target: &dyn Target,
+    pool_config: &SchedulerConfig,
+    remote_repx_binary_path: &Path,
+) -> Result<Vec<ExecutionHost>> {
+    if pool_config.hosts.is_empty() {
+        return Err(ClientError::Core(AppError::ConfigurationError(format!(
+            "The ssh-pool scheduler of target '{}' has no hosts.",
+            pool_target.name()
+        ))));
+    }
+
+    let mut hosts = Vec::new();
+    for (host_name, &concurrency) in &pool_config.hosts {
+        let host_target = client
+            .get_target(host_name)
+            .ok_or_else(|| ClientError::TargetNotFound(host_name.clone()))?;
+        let repx_binary_path = if host_name == pool_target.name() {
+            remote_repx_binary_path.to_path_buf()
+        } else {
+            host_target.deploy_repx_binary()?
+        };
+        log_info!(
+            "ssh-pool host '{}' runs up to {} jobs with {}",
+            host_name,
+            concurrency,
+            repx_binary_path.display()
+        );
+        hosts.push(ExecutionHost {
+            target: host_target,
+            repx_binary_path,
+            concurrency: concurrency.max(1),
+            cpus: concurrency.max(1),
+        });
+    }
+    Ok(hosts)
+}
+
+fn read_job_hosts(target: &dyn Target) -> Result<HashMap<JobId, String>> {
+    let outputs_path = target.base_path().join("outputs");
+    let read_cmd = format!(
+        "cd '{}' 2>/dev/null || exit 0; for f in */repx/{}; do [ -f \"$f\" ] && printf '%s %s\\n' \"${{f%%/*}}\" \"$(cat \"$f\")\"; done; true",
+        outputs_path.display(),
+        HOST_FILE
+    );
+    let output = target.run_command("sh", &["-c", &read_cmd])?;
+    Ok(parse_job_hosts(&output))
+}
+
+fn parse_job_hosts(output: &str) -> HashMap<JobId, String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (job_id, host) = line.split_once(' ')?;
+            let host = host.trim();
+            (!host.is_empty()).then(|| (JobId(job_id.to_string()), host.to_string()))
+        })
+        .collect()
+}
+
+fn apply_job_hosts(
+    target_name: &str,
+    job_statuses: &mut HashMap<JobId, engine::JobStatus>,
+    job_hosts: &HashMap<JobId, String>,
+) {
+    for (job_id, status) in job_statuses.iter_mut() {
+        let Some(host) = job_hosts.get(job_id) else {
+            continue;
+        };
+        let location = match status {
+            engine::JobStatus::Succeeded { location }
+            | engine::JobStatus::PartiallySucceeded { location }
+            | engine::JobStatus::Failed { location }
+            | engine::JobStatus::SchedulerFailed { location, .. }
+            | engine::JobStatus::Requeued { location } => location,
+            _ => continue,
+        };
+        if location == target_name {
+            *location = host.clone();
+        }
+    }
+}
+
+pub struct SshPoolScheduler;
+
+impl Scheduler for SshPoolScheduler {
+    fn name(&self) -> &str {
+        "ssh-pool"
+    }
+
+    fn config<'a>(&self, target_config: &'a config::Target) -> Option<&'a SchedulerConfig> {
+        target_config.ssh_pool.as_ref()
+    }
+
+    fn submit(
+        &self,
+        client: &Client,
+        jobs: HashMap<JobId, &Job>,
+        target: Arc<dyn Target>,
+        remote_repx_binary_path: &Path,
+        options: &SubmitOptions,
+        send: &dyn Fn(ClientEvent),
+    ) -> Result<String> {
+        let target_name = target.name().to_string();
+        submit_ssh_pool_batch_run(
+            client,
+            jobs,
+            target,
+            &target_name,
+            remote_repx_binary_path,
+            options,
+            send,
+        )
+    }
+
+    fn query_states(
+        &self,
+        _client: &Client,
+        target: &dyn Target,
+        job_statuses: &mut HashMap<JobId, engine::JobStatus>,
+    ) -> Result<()> {
+        match read_job_hosts(target) {
+            Ok(job_hosts) => apply_job_hosts(target.name(), job_statuses, &job_hosts),
+            Err(e) => log_debug!(
+                "Could not read ssh-pool job hosts on target '{}': {}",
+                target.name(),
+                e
+            ),
+        }
+        Ok(())
+    }
+
+    fn cancel(&self, target: &dyn Target, job_id: &JobId, _scheduler_id: u32) -> Result<()> {
+        Err(ClientError::Core(AppError::ConfigurationError(format!(
+            "Cannot cancel job '{}' on target '{}': cancel is not supported for ssh-pool.",
+            job_id,
+            target.name()
+        ))))
+    }
+
+    fn log_path(&self, target: &dyn Target, job_id: &JobId, _scheduler_id: u32) -> PathBuf {
+        job_repx_dir(target, job_id).join(JOB_STDOUT_LOG_FILE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_hosts_become_status_locations() {
+        let job_hosts = parse_job_hosts("job-a ws1\njob-b ws2\njob-c \nmalformed\n");
+        assert_eq!(job_hosts.len(), 2);
+
+        let mut job_statuses = HashMap::from([
+            (
+                JobId("job-a".into()),
+                engine::JobStatus::Succeeded {
+                    location: "workstations".into(),
+                },
+            ),
+            (
+                JobId("job-b".into()),
+                engine::JobStatus::Failed {
+                    location: "workstations".into(),
+                },
+            ),
+            (
+                JobId("job-c".into()),
+                engine::JobStatus::Succeeded {
+                    location: "workstations".into(),
+                },
+            ),
+        ]);
+        apply_job_hosts("workstations", &mut job_statuses, &job_hosts);
+
+        assert_eq!(
+            job_statuses[&JobId("job-a".into())],
+            engine::JobStatus::Succeeded {
+                location: "ws1".into()
+            }
+        );
+        assert_eq!(
+            job_statuses[&JobId("job-b".into())],
+            engine::JobStatus::Failed {
+                location: "ws2".into()
+            }
+        );
+        assert_eq!(
+            job_statuses[&JobId("job-c".into())],
+            engine::JobStatus::Succeeded {
+                location: "workstations".into()
+            }
+        );
+    }
+}
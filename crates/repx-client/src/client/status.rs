@@ -1,11 +1,135 @@
-use super::Client;
+use super::{
+    scheduler::{self, Scheduler},
+    slurm_map::TrackedJob,
+    Client,
+};
 use crate::error::{ClientError, Result};
-use crate::targets::SlurmState;
+use crate::orchestration::SUBMISSION_LOG_FILE;
+use crate::targets::{self, Target};
 use repx_core::{
-    engine,
+    engine, log_debug,
     model::{JobId, RunId},
+    store::{
+        progress::WorkerProgress,
+        status::{self as status_report, StatusReport, SubmittedJob},
+    },
 };
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+
+const STATUS_JOBS_FILE: &str = "status-jobs.txt";
+
+#[derive(Default)]
+pub(crate) struct RemoteStatusState {
+    runner_hash: OnceLock<Option<String>>,
+    job_lists_written: Mutex<HashSet<String>>,
+    reported_progress: Mutex<HashMap<String, HashMap<JobId, WorkerProgress>>>,
+    scheduler_failures: Mutex<HashMap<JobId, (TrackedJob, engine::JobStatus)>>,
+}
+
+struct TargetSnapshot {
+    outcomes: HashMap<JobId, engine::JobStatus>,
+    submissions: Option<Vec<SubmittedJob>>,
+}
+
+fn submissions_dir(client: &Client, target: &dyn Target) -> PathBuf {
+    target
+        .base_path()
+        .join("submissions")
+        .join(&client.lab.content_hash)
+}
+
+fn fetch_status_report(client: &Client, target: &dyn Target) -> Result<Option<StatusReport>> {
+    let state = &client.remote_status;
+    let runner_hash = state.runner_hash.get_or_init(|| {
+        targets::find_local_runner_binary()
+            .and_then(|path| targets::compute_file_hash(&path))
+            .ok()
+    });
+    let Some(runner_hash) = runner_hash else {
+        return Ok(None);
+    };
+    let repx_binary_path = target
+        .base_path()
+        .join("bin")
+        .join(runner_hash)
+        .join("repx-runner");
+
+    let submissions_dir = submissions_dir(client, target);
+    let jobs_file = submissions_dir.join(STATUS_JOBS_FILE);
+    if !state
+        .job_lists_written
+        .lock()
+        .unwrap()
+        .contains(target.name())
+    {
+        let mut job_ids: Vec<&str> = client.lab.jobs.keys().map(|id| id.0.as_str()).collect();
+        job_ids.sort();
+        target.write_remote_file(&jobs_file, &(job_ids.join("\n") + "\n"))?;
+        state
+            .job_lists_written
+            .lock()
+            .unwrap()
+            .insert(target.name().to_string());
+    }
+
+    target.status_report(
+        &repx_binary_path,
+        &jobs_file,
+        &submissions_dir.join(SUBMISSION_LOG_FILE),
+    )
+}
+
+fn snapshot_target(client: &Client, target: &dyn Target) -> Result<TargetSnapshot> {
+    let report = fetch_status_report(client, target).unwrap_or_else(|e| {
+        log_debug!(
+            "internal-status failed on target '{}', falling back to shell queries: {}",
+            target.name(),
+            e
+        );
+        client
+            .remote_status
+            .job_lists_written
+            .lock()
+            .unwrap()
+            .remove(target.name());
+        None
+    });
+    let Some(report) = report else {
+        client
+            .remote_status
+            .reported_progress
+            .lock()
+            .unwrap()
+            .remove(target.name());
+        return Ok(TargetSnapshot {
+            outcomes: target.check_outcome_markers()?,
+            submissions: None,
+        });
+    };
+
+    let mut outcomes = HashMap::new();
+    let mut progress = HashMap::new();
+    for (job_id, job) in report.jobs {
+        if let Some(marker) = job.outcome {
+            outcomes.insert(job_id.clone(), marker.job_status(target.name().to_string()));
+        }
+        if let Some(job_progress) = job.progress {
+            progress.insert(job_id, job_progress);
+        }
+    }
+    client
+        .remote_status
+        .reported_progress
+        .lock()
+        .unwrap()
+        .insert(target.name().to_string(), progress);
+    Ok(TargetSnapshot {
+        outcomes,
+        submissions: Some(report.submissions),
+    })
+}
 
 pub fn get_statuses(
     client: &Client,
@@ -14,26 +138,20 @@ pub fn get_statuses(
     HashMap<JobId, engine::JobStatus>,
 )> {
     let mut all_outcomes = HashMap::new();
+    let mut all_submissions = Vec::new();
     for target in client.targets.values() {
-        let outcomes = target.check_outcome_markers()?;
-        all_outcomes.extend(outcomes);
+        let snapshot = snapshot_target(client, target.as_ref())?;
+        all_outcomes.extend(snapshot.outcomes);
+        all_submissions.push((target.clone(), snapshot.submissions));
     }
 
-    let mut slurm_map_guard = client.slurm_map.lock().unwrap();
     let mut map_was_changed = false;
-    slurm_map_guard.retain(|job_id, _| {
-        let is_done = matches!(
-            all_outcomes.get(job_id),
-            Some(engine::JobStatus::Succeeded { .. }) | Some(engine::JobStatus::Failed { .. })
-        );
-        if is_done {
-            map_was_changed = true;
-        }
-        !is_done
-    });
-
-    drop(slurm_map_guard);
+    for (target, submissions) in all_submissions {
+        map_was_changed |=
+            ingest_submission_log(client, target.as_ref(), submissions, &all_outcomes);
+    }
 
+    map_was_changed |= forget_finished_jobs(client, &all_outcomes, None);
     if map_was_changed {
         client.save_slurm_map()?;
     }
@@ -41,19 +159,16 @@ pub fn get_statuses(
     let mut job_statuses = all_outcomes;
 
     for target in client.targets.values() {
-        if target.config().slurm.is_some() {
-            let queued_jobs = target.squeue()?;
-            for (job_id, squeue_info) in queued_jobs {
-                job_statuses
-                    .entry(job_id)
-                    .or_insert(if squeue_info.state == SlurmState::Running {
-                        engine::JobStatus::Running
-                    } else {
-                        engine::JobStatus::Queued
-                    });
-            }
+        let configured = client.tracking_scheduler(target.config());
+        let configured_name = configured.as_ref().map(|scheduler| scheduler.name());
+        for scheduler in schedulers_to_query(client, target.name(), configured_name) {
+            scheduler.query_states(client, target.as_ref(), &mut job_statuses)?;
         }
     }
+    if forget_finished_jobs(client, &job_statuses, None) {
+        client.save_slurm_map()?;
+    }
+    add_scheduler_failures(client, &mut job_statuses, None);
 
     let final_statuses = engine::determine_job_statuses(&client.lab, &job_statuses);
     let run_statuses = engine::determine_run_aggregate_statuses(&client.lab, &final_statuses);
@@ -61,6 +176,58 @@ pub fn get_statuses(
     Ok((run_statuses, final_statuses))
 }
 
+fn ingest_submission_log(
+    client: &Client,
+    target: &dyn Target,
+    submissions: Option<Vec<SubmittedJob>>,
+    outcomes: &HashMap<JobId, engine::JobStatus>,
+) -> bool {
+    let configured = client.tracking_scheduler(target.config());
+
+    let submissions = match submissions {
+        Some(submissions) => submissions,
+        None => {
+            let submission_log = submissions_dir(client, target).join(SUBMISSION_LOG_FILE);
+            let cat_cmd = format!("cat '{}' 2>/dev/null || true", submission_log.display());
+            let Ok(output) = target.run_command("sh", &["-c", &cat_cmd]) else {
+                return false;
+            };
+            status_report::parse_submission_log(&output)
+        }
+    };
+
+    let scheduler_failures = client.remote_status.scheduler_failures.lock().unwrap();
+    let mut slurm_map_guard = client.slurm_map.lock().unwrap();
+    let mut map_was_changed = false;
+    for submitted in submissions {
+        let scheduler = match &submitted.scheduler {
+            Some(name) => client.scheduler(name),
+            None => configured.clone(),
+        };
+        let Some(scheduler) = scheduler else {
+            continue;
+        };
+        let Some(scheduler_id) = scheduler.parse_job_id(&submitted.scheduler_id) else {
+            continue;
+        };
+        if outcomes.contains_key(&submitted.job_id) {
+            continue;
+        }
+        let entry = TrackedJob::new(target.name(), scheduler.name(), scheduler_id);
+        if scheduler_failures
+            .get(&submitted.job_id)
+            .is_some_and(|(failed, _)| *failed == entry)
+        {
+            continue;
+        }
+        if slurm_map_guard.get(&submitted.job_id) != Some(&entry) {
+            slurm_map_guard.insert(submitted.job_id, entry);
+            map_was_changed = true;
+        }
+    }
+    map_was_changed
+}
+
 pub fn get_statuses_for_active_target(
     client: &Client,
     active_target_name: &str,
@@ -72,54 +239,131 @@ pub fn get_statuses_for_active_target(
         .get(active_target_name)
         .ok_or_else(|| ClientError::TargetNotFound(active_target_name.to_string()))?;
 
-    let outcomes = target.check_outcome_markers()?;
+    let snapshot = snapshot_target(client, target.as_ref())?;
+    let outcomes = snapshot.outcomes;
     job_statuses.extend(outcomes.clone());
 
+    let mut map_was_changed =
+        ingest_submission_log(client, target.as_ref(), snapshot.submissions, &outcomes);
+
+    map_was_changed |= forget_finished_jobs(client, &outcomes, Some(active_target_name));
+    if map_was_changed {
+        client.save_slurm_map()?;
+    }
+
+    for scheduler in schedulers_to_query(client, active_target_name, active_scheduler) {
+        scheduler.query_states(client, target.as_ref(), &mut job_statuses)?;
+    }
+    if forget_finished_jobs(client, &job_statuses, Some(active_target_name)) {
+        client.save_slurm_map()?;
+    }
+    add_scheduler_failures(client, &mut job_statuses, Some(active_target_name));
+
+    Ok(job_statuses)
+}
+
+/// Drops finished jobs from the job map. Scheduler failures leave no marker behind, so they
+/// are kept for the session and their submission log entries are not tracked again.
+fn forget_finished_jobs(
+    client: &Client,
+    statuses: &HashMap<JobId, engine::JobStatus>,
+    target_name: Option<&str>,
+) -> bool {
+    let mut scheduler_failures = client.remote_status.scheduler_failures.lock().unwrap();
     let mut slurm_map_guard = client.slurm_map.lock().unwrap();
-    let mut map_was_changed = false;
-    slurm_map_guard.retain(|job_id, (target_name, _slurm_id)| {
-        if target_name != active_target_name {
+    let tracked_before = slurm_map_guard.len();
+    slurm_map_guard.retain(|job_id, tracked| {
+        if target_name.is_some_and(|name| tracked.target != name) {
             return true;
         }
-        let is_done = matches!(
-            outcomes.get(job_id),
-            Some(engine::JobStatus::Succeeded { .. }) | Some(engine::JobStatus::Failed { .. })
-        );
-        if is_done {
-            map_was_changed = true;
+        match statuses.get(job_id) {
+            Some(engine::JobStatus::Succeeded { .. })
+            | Some(engine::JobStatus::PartiallySucceeded { .. })
+            | Some(engine::JobStatus::Failed { .. }) => false,
+            Some(status @ engine::JobStatus::SchedulerFailed { .. }) => {
+                scheduler_failures.insert(job_id.clone(), (tracked.clone(), status.clone()));
+                false
+            }
+            _ => true,
         }
-        !is_done
     });
+    slurm_map_guard.len() != tracked_before
+}
 
-    let has_tracked_slurm_jobs = slurm_map_guard
-        .values()
-        .any(|(t, _)| t == active_target_name);
+fn add_scheduler_failures(
+    client: &Client,
+    job_statuses: &mut HashMap<JobId, engine::JobStatus>,
+    target_name: Option<&str>,
+) {
+    let scheduler_failures = client.remote_status.scheduler_failures.lock().unwrap();
+    let slurm_map_guard = client.slurm_map.lock().unwrap();
+    for (job_id, (tracked, status)) in scheduler_failures.iter() {
+        if target_name.is_some_and(|name| tracked.target != name)
+            || slurm_map_guard.contains_key(job_id)
+        {
+            continue;
+        }
+        job_statuses
+            .entry(job_id.clone())
+            .or_insert_with(|| status.clone());
+    }
+}
 
-    drop(slurm_map_guard);
+fn schedulers_to_query(
+    client: &Client,
+    target_name: &str,
+    also_query: Option<&str>,
+) -> Vec<Arc<dyn Scheduler>> {
+    let mut names: BTreeSet<String> = client
+        .slurm_map
+        .lock()
+        .unwrap()
+        .values()
+        .filter(|tracked| tracked.target == target_name)
+        .map(|tracked| tracked.scheduler.clone())
+        .collect();
+    names.extend(also_query.map(str::to_string));
+    scheduler::schedulers_by_name(&client.schedulers, &names)
+}
 
-    if map_was_changed {
-        client.save_slurm_map()?;
-    }
+pub fn get_worker_progress(
+    client: &Client,
+    target_name: &str,
+    job_statuses: &HashMap<JobId, engine::JobStatus>,
+) -> Result<HashMap<JobId, WorkerProgress>> {
+    let target = client
+        .targets
+        .get(target_name)
+        .ok_or_else(|| ClientError::TargetNotFound(target_name.to_string()))?;
 
-    let should_query_slurm = target.config().slurm.is_some()
-        && match active_scheduler {
-            Some("slurm") => true,
-            Some(_) => false,
-            None => has_tracked_slurm_jobs,
-        };
+    let mut unfinished_jobs: Vec<JobId> = client
+        .lab
+        .jobs
+        .iter()
+        .filter(|(job_id, job)| {
+            job.stage_type == "scatter-gather"
+                && !matches!(
+                    job_statuses.get(*job_id),
+                    Some(engine::JobStatus::Succeeded { .. })
+                        | Some(engine::JobStatus::PartiallySucceeded { .. })
+                        | Some(engine::JobStatus::Failed { .. })
+                )
+        })
+        .map(|(job_id, _)| job_id.clone())
+        .collect();
+    unfinished_jobs.sort();
 
-    if should_query_slurm {
-        let queued_jobs = target.squeue()?;
-        for (job_id, squeue_info) in queued_jobs {
-            job_statuses
-                .entry(job_id)
-                .or_insert(if squeue_info.state == SlurmState::Running {
-                    engine::JobStatus::Running
-                } else {
-                    engine::JobStatus::Queued
-                });
-        }
+    let reported = client
+        .remote_status
+        .reported_progress
+        .lock()
+        .unwrap()
+        .remove(target_name);
+    match reported {
+        Some(mut reported) => Ok(unfinished_jobs
+            .into_iter()
+            .filter_map(|job_id| reported.remove_entry(&job_id))
+            .collect()),
+        None => target.read_worker_progress(&unfinished_jobs),
     }
-
-    Ok(job_statuses)
 }
@@ -0,0 +1,581 @@
+use super::{Client, ClientEvent, VerifyReport};
+use crate::error::{ClientError, Result};
+use crate::targets::{self, Target};
+use repx_core::{
+    error::AppError,
+    log_info, log_warn,
+    model::{JobId, Lab},
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::time::{Duration, Instant};
+use walkdir::WalkDir;
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+const MANIFESTS_DIR: &str = "manifests";
+
+pub(crate) fn required_artifacts<'a>(
+    lab: &Lab,
+    lab_path: &Path,
+    job_ids: impl IntoIterator<Item = &'a JobId>,
+) -> Result<BTreeMap<PathBuf, u64>> {
+    let job_ids: HashSet<&JobId> = job_ids.into_iter().collect();
+    let job_packages: HashSet<&Path> = lab
+        .jobs
+        .values()
+        .map(|job| job.path_in_lab.as_path())
+        .collect();
+    let images: HashSet<&Path> = lab
+        .runs
+        .values()
+        .filter_map(|run| run.image.as_deref())
+        .collect();
+    let needed_images: HashSet<&Path> = lab
+        .runs
+        .values()
+        .filter(|run| run.jobs.iter().any(|job_id| job_ids.contains(job_id)))
+        .filter_map(|run| run.image.as_deref())
+        .collect();
+    let needed_packages: HashSet<&Path> = job_ids
+        .iter()
+        .filter_map(|job_id| lab.jobs.get(*job_id))
+        .map(|job| job.path_in_lab.as_path())
+        .collect();
+
+    let mut files = BTreeMap::new();
+    for referenced in &lab.referenced_files {
+        let referenced = referenced.as_path();
+        if job_packages.contains(referenced) && !needed_packages.contains(referenced) {
+            continue;
+        }
+        if images.contains(referenced) && !needed_images.contains(referenced) {
+            continue;
+        }
+        for entry in WalkDir::new(lab_path.join(referenced)).follow_links(true) {
+            let entry = entry?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let relative = entry.path().strip_prefix(lab_path).map_err(|_| {
+                ClientError::Core(AppError::ConfigurationError(format!(
+                    "Lab artifact '{}' is outside of the lab directory.",
+                    entry.path().display()
+                )))
+            })?;
+            let size = entry.metadata()?.len();
+            files.insert(relative.to_path_buf(), size);
+        }
+    }
+    Ok(files)
+}
+
+pub(crate) struct PendingArtifacts {
+    required: BTreeMap<PathBuf, u64>,
+    missing: HashSet<PathBuf>,
+    reopened_dirs: Vec<(PathBuf, String)>,
+    image_bytes: u64,
+}
+
+impl PendingArtifacts {
+    pub(crate) fn missing_bytes(&self) -> u64 {
+        self.missing
+            .iter()
+            .filter_map(|path| self.required.get(path))
+            .sum()
+    }
+
+    pub(crate) fn image_bytes(&self) -> u64 {
+        self.image_bytes
+    }
+}
+
+pub(crate) fn pending_artifacts<'a>(
+    client: &Client,
+    target: &dyn Target,
+    job_ids: impl IntoIterator<Item = &'a JobId>,
+) -> Result<PendingArtifacts> {
+    let job_ids: HashSet<&JobId> = job_ids.into_iter().collect();
+    let required = required_artifacts(&client.lab, &client.lab_path, job_ids.iter().copied())?;
+    let images: HashSet<&Path> = client
+        .lab
+        .runs
+        .values()
+        .filter(|run| run.jobs.iter().any(|job_id| job_ids.contains(job_id)))
+        .filter_map(|run| run.image.as_deref())
+        .collect();
+    let image_bytes = required
+        .iter()
+        .filter(|(path, _)| images.iter().any(|image| path.starts_with(image)))
+        .map(|(_, size)| size)
+        .sum();
+
+    let required_paths: HashSet<PathBuf> = required.keys().cloned().collect();
+    let mut missing = target.get_missing_artifacts(&required_paths)?;
+    let mut reopened_dirs = Vec::new();
+    log_info!(
+        "{} of {} lab artifacts are missing on target '{}'",
+        missing.len(),
+        required.len(),
+        target.name()
+    );
+    if target.config().verify_artifacts {
+        let mut present: Vec<PathBuf> = required_paths.difference(&missing).cloned().collect();
+        present.sort();
+        let mismatched = mismatched_artifacts(
+            &client.lab_path,
+            &manifest_path(&client.lab)?,
+            target,
+            &present,
+        )?;
+        if !mismatched.is_empty() {
+            log_warn!(
+                "{} lab artifacts on target '{}' do not match their checksum and will be synced again",
+                mismatched.len(),
+                target.name()
+            );
+            reopened_dirs = target.remove_artifacts(&mismatched)?;
+            missing.extend(mismatched);
+        }
+    }
+    Ok(PendingArtifacts {
+        required,
+        missing,
+        reopened_dirs,
+        image_bytes,
+    })
+}
+
+pub(crate) fn sync_lab_artifacts(
+    client: &Client,
+    target: &dyn Target,
+    pending: PendingArtifacts,
+    event_sender: Option<&Sender<ClientEvent>>,
+) -> Result<usize> {
+    let total_bytes = pending.missing_bytes();
+    let missing = &pending.missing;
+    let send = |event: ClientEvent| {
+        if let Some(sender) = event_sender {
+            let _ = sender.send(event);
+        }
+    };
+    send(ClientEvent::SyncingArtifacts {
+        files: missing.len() as u64,
+        total_bytes,
+    });
+    if missing.is_empty() {
+        return Ok(0);
+    }
+
+    let mut reporter = ProgressReporter::new(total_bytes);
+    let synced =
+        target.sync_artifacts_batch(&client.lab_path, missing, &mut |path, bytes_transferred| {
+            if let Some(event) = reporter.update(path, bytes_transferred) {
+                send(event);
+            }
+        });
+    target.restore_artifact_dirs(&pending.reopened_dirs)?;
+    synced?;
+    send(reporter.finish());
+    verify_artifacts(
+        &client.lab_path,
+        &manifest_path(&client.lab)?,
+        target,
+        missing,
+    )?;
+    Ok(missing.len())
+}
+
+pub(crate) fn verify_lab_artifacts<'a>(
+    client: &Client,
+    target: &dyn Target,
+    job_ids: impl IntoIterator<Item = &'a JobId>,
+    resync: bool,
+) -> Result<VerifyReport> {
+    verify_artifacts_against_manifest(
+        &client.lab,
+        &client.lab_path,
+        &manifest_path(&client.lab)?,
+        target,
+        job_ids,
+        resync,
+    )
+}
+
+fn verify_artifacts_against_manifest<'a>(
+    lab: &Lab,
+    lab_path: &Path,
+    manifest_path: &Path,
+    target: &dyn Target,
+    job_ids: impl IntoIterator<Item = &'a JobId>,
+    resync: bool,
+) -> Result<VerifyReport> {
+    let required = required_artifacts(lab, lab_path, job_ids)?;
+    let required_paths: HashSet<PathBuf> = required.into_keys().collect();
+    let missing = target.get_missing_artifacts(&required_paths)?;
+    let mut present: Vec<PathBuf> = required_paths.difference(&missing).cloned().collect();
+    present.sort();
+    let mismatched = mismatched_artifacts(lab_path, manifest_path, target, &present)?;
+
+    let mut report = VerifyReport {
+        checked: present.len(),
+        missing: missing.into_iter().collect(),
+        mismatched,
+        resynced: Vec::new(),
+    };
+    report.missing.sort();
+    if resync && !report.mismatched.is_empty() {
+        let reopened_dirs = target.remove_artifacts(&report.mismatched)?;
+        let resync_paths: HashSet<PathBuf> = report.mismatched.iter().cloned().collect();
+        let synced = target.sync_artifacts_batch(lab_path, &resync_paths, &mut |_, _| {});
+        target.restore_artifact_dirs(&reopened_dirs)?;
+        synced?;
+        verify_artifacts(lab_path, manifest_path, target, &resync_paths)?;
+        report.resynced = report.mismatched.clone();
+    }
+    Ok(report)
+}
+
+fn manifest_path(lab: &Lab) -> Result<PathBuf> {
+    let xdg_dirs = xdg::BaseDirectories::with_prefix("repx");
+    let cache_home = xdg_dirs.get_cache_home().ok_or_else(|| {
+        ClientError::Core(AppError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "Could not find cache home directory",
+        )))
+    })?;
+    Ok(cache_home
+        .join(MANIFESTS_DIR)
+        .join(format!("{}.sha256", lab.content_hash)))
+}
+
+fn local_checksums(
+    manifest_path: &Path,
+    lab_path: &Path,
+    paths: &[PathBuf],
+) -> Result<HashMap<PathBuf, String>> {
+    let mut manifest = fs_err::read_to_string(manifest_path)
+        .map(|content| targets::parse_sha256sum(&content))
+        .unwrap_or_default();
+    let mut changed = false;
+    for path in paths {
+        if !manifest.contains_key(path) {
+            let hash = targets::compute_file_hash(&lab_path.join(path))?;
+            manifest.insert(path.clone(), hash);
+            changed = true;
+        }
+    }
+    if changed {
+        let entries: BTreeMap<&PathBuf, &String> = manifest.iter().collect();
+        let content: String = entries
+            .into_iter()
+            .map(|(path, hash)| format!("{}  {}\n", hash, path.display()))
+            .collect();
+        if let Some(parent) = manifest_path.parent() {
+            fs_err::create_dir_all(parent).map_err(AppError::from)?;
+        }
+        fs_err::write(manifest_path, content).map_err(AppError::from)?;
+    }
+    Ok(manifest)
+}
+
+fn mismatched_artifacts(
+    lab_path: &Path,
+    manifest_path: &Path,
+    target: &dyn Target,
+    paths: &[PathBuf],
+) -> Result<Vec<PathBuf>> {
+    if paths.is_empty() {
+        return Ok(Vec::new());
+    }
+    let expected = local_checksums(manifest_path, lab_path, paths)?;
+    let remote_hashes = target.artifact_hashes(paths)?;
+    Ok(paths
+        .iter()
+        .filter(|path| remote_hashes.get(*path) != expected.get(*path))
+        .cloned()
+        .collect())
+}
+
+struct ProgressReporter {
+    started: Instant,
+    last_sent: Option<Instant>,
+    path: PathBuf,
+    bytes_transferred: u64,
+    total_bytes: u64,
+}
+
+impl ProgressReporter {
+    fn new(total_bytes: u64) -> Self {
+        Self {
+            started: Instant::now(),
+            last_sent: None,
+            path: PathBuf::new(),
+            bytes_transferred: 0,
+            total_bytes,
+        }
+    }
+
+    fn update(&mut self, path: &Path, bytes_transferred: u64) -> Option<ClientEvent> {
+        let path_changed = self.path != path;
+        if path_changed {
+            self.path = path.to_path_buf();
+        }
+        self.bytes_transferred = self
+            .bytes_transferred
+            .max(bytes_transferred.min(self.total_bytes));
+        let due = self
+            .last_sent
+            .is_none_or(|sent| sent.elapsed() >= PROGRESS_INTERVAL);
+        if !path_changed && !due {
+            return None;
+        }
+        self.last_sent = Some(Instant::now());
+        Some(self.event())
+    }
+
+    fn finish(mut self) -> ClientEvent {
+        self.bytes_transferred = self.total_bytes;
+        self.event()
+    }
+
+    fn event(&self) -> ClientEvent {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let bytes_per_second = if elapsed > 0.0 {
+            (self.bytes_transferred as f64 / elapsed) as u64
+        } else {
+            0
+        };
+        ClientEvent::SyncingArtifactProgress {
+            path: self.path.clone(),
+            bytes_transferred: self.bytes_transferred,
+            total_bytes: self.total_bytes,
+            bytes_per_second,
+        }
+    }
+}
+
+fn verify_artifacts(
+    lab_path: &Path,
+    manifest_path: &Path,
+    target: &dyn Target,
+    synced: &HashSet<PathBuf>,
+) -> Result<()> {
+    let mut paths: Vec<PathBuf> = synced.iter().cloned().collect();
+    paths.sort();
+    let mismatched: Vec<String> = mismatched_artifacts(lab_path, manifest_path, target, &paths)?
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect();
+    if mismatched.is_empty() {
+        return Ok(());
+    }
+    Err(ClientError::TargetCommandFailed {
+        target: target.name().to_string(),
+        source: AppError::ExecutionFailed {
+            message: format!(
+                "{} synced artifacts do not match their local checksum",
+                mismatched.len()
+            ),
+            log_path: None,
+            log_summary: mismatched.join("\n"),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use repx_core::model::{Job, Run, RunId};
+    use std::collections::HashMap;
+    use std::fs;
+
+    fn job(path: &str) -> Job {
+        Job {
+            name: None,
+            params: serde_json::Value::Null,
+            path_in_lab: PathBuf::from(path),
+            stage_type: "simple".to_string(),
+            executables: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_progress_is_throttled_and_monotonic() {
+        let mut reporter = ProgressReporter::new(100);
+        let bytes = |event: Option<ClientEvent>| match event {
+            Some(ClientEvent::SyncingArtifactProgress {
+                bytes_transferred, ..
+            }) => Some(bytes_transferred),
+            _ => None,
+        };
+        assert_eq!(bytes(reporter.update(Path::new("a"), 10)), Some(10));
+        assert_eq!(bytes(reporter.update(Path::new("a"), 20)), None);
+        assert_eq!(bytes(reporter.update(Path::new("b"), 5)), Some(20));
+        assert_eq!(bytes(reporter.update(Path::new("c"), 500)), Some(100));
+        assert_eq!(bytes(Some(reporter.finish())), Some(100));
+    }
+
+    #[test]
+    fn test_local_checksums_are_cached_in_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let lab_path = dir.path().join("lab");
+        fs::create_dir_all(lab_path.join("jobs")).unwrap();
+        fs::write(lab_path.join("jobs/a"), "a").unwrap();
+        fs::write(lab_path.join("jobs/b"), "b").unwrap();
+        let manifest_path = dir.path().join("manifests/hash.sha256");
+        fs::create_dir_all(manifest_path.parent().unwrap()).unwrap();
+        fs::write(&manifest_path, "cached  jobs/a\n").unwrap();
+
+        let paths = [PathBuf::from("jobs/a"), PathBuf::from("jobs/b")];
+        let checksums = local_checksums(&manifest_path, &lab_path, &paths).unwrap();
+        let hash_b = targets::compute_file_hash(&lab_path.join("jobs/b")).unwrap();
+        assert_eq!(checksums[&paths[0]], "cached");
+        assert_eq!(checksums[&paths[1]], hash_b);
+        assert_eq!(
+            fs::read_to_string(&manifest_path).unwrap(),
+            format!("cached  jobs/a\n{}  jobs/b\n", hash_b)
+        );
+    }
+
+    #[test]
+    fn test_only_artifacts_of_selected_jobs_are_required() {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, content) in [
+            ("lab/lab-metadata.json", "{}"),
+            ("jobs/job-a/bin/main", "a"),
+            ("jobs/job-b/bin/main", "bb"),
+            ("images/run-a.tar", "image-a"),
+            ("images/run-b.tar", "image-b"),
+            ("host-tools/tools/bin/rsync", "tool"),
+        ] {
+            let file = dir.path().join(path);
+            fs::create_dir_all(file.parent().unwrap()).unwrap();
+            fs::write(file, content).unwrap();
+        }
+        let job_a = JobId("job-a".to_string());
+        let job_b = JobId("job-b".to_string());
+        let lab = Lab {
+            schema_version: "1".to_string(),
+            git_hash: String::new(),
+            content_hash: "hash".to_string(),
+            runs: HashMap::from([
+                (
+                    RunId("run-a".to_string()),
+                    Run {
+                        image: Some(PathBuf::from("images/run-a.tar")),
+                        jobs: vec![job_a.clone()],
+                        dependencies: HashMap::new(),
+                    },
+                ),
+                (
+                    RunId("run-b".to_string()),
+                    Run {
+                        image: Some(PathBuf::from("images/run-b.tar")),
+                        jobs: vec![job_b.clone()],
+                        dependencies: HashMap::new(),
+                    },
+                ),
+            ]),
+            jobs: HashMap::from([
+                (job_a.clone(), job("jobs/job-a")),
+                (job_b.clone(), job("jobs/job-b")),
+            ]),
+            host_tools_path: dir.path().join("host-tools/tools/bin"),
+            host_tools_dir_name: "tools".to_string(),
+            referenced_files: [
+                "lab/lab-metadata.json",
+                "host-tools/tools",
+                "images/run-a.tar",
+                "images/run-b.tar",
+                "jobs/job-a",
+                "jobs/job-b",
+            ]
+            .iter()
+            .map(PathBuf::from)
+            .collect(),
+        };
+
+        let required = required_artifacts(&lab, dir.path(), [&job_a]).unwrap();
+        assert_eq!(
+            required,
+            BTreeMap::from([
+                (PathBuf::from("host-tools/tools/bin/rsync"), 4),
+                (PathBuf::from("images/run-a.tar"), 7),
+                (PathBuf::from("jobs/job-a/bin/main"), 1),
+                (PathBuf::from("lab/lab-metadata.json"), 2),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_mismatched_artifacts_are_resynced_with_their_permissions_restored() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let lab_path = dir.path().join("lab");
+        let artifacts = dir.path().join("store/artifacts");
+        for (root, content) in [(&lab_path, "good"), (&artifacts, "corrupt")] {
+            fs::create_dir_all(root.join("jobs/job-a/bin")).unwrap();
+            fs::write(root.join("jobs/job-a/bin/main"), content).unwrap();
+        }
+        let bin_dir = artifacts.join("jobs/job-a/bin");
+        fs::set_permissions(bin_dir.join("main"), fs::Permissions::from_mode(0o444)).unwrap();
+        fs::set_permissions(&bin_dir, fs::Permissions::from_mode(0o555)).unwrap();
+
+        let job_a = JobId("job-a".to_string());
+        let lab = Lab {
+            schema_version: "1".to_string(),
+            git_hash: String::new(),
+            content_hash: "hash".to_string(),
+            runs: HashMap::new(),
+            jobs: HashMap::from([(job_a.clone(), job("jobs/job-a"))]),
+            host_tools_path: dir.path().join("host-tools"),
+            host_tools_dir_name: "tools".to_string(),
+            referenced_files: vec![PathBuf::from("jobs/job-a")],
+        };
+        let target = crate::targets::local::LocalTarget {
+            name: "local".into(),
+            config: toml::from_str(&format!(
+                "base_path = \"{}\"",
+                dir.path().join("store").display()
+            ))
+            .unwrap(),
+            local_tools_path: PathBuf::new(),
+        };
+        let manifest_path = dir.path().join("manifest.sha256");
+
+        let report = verify_artifacts_against_manifest(
+            &lab,
+            &lab_path,
+            &manifest_path,
+            &target,
+            [&job_a],
+            true,
+        )
+        .unwrap();
+        let main = PathBuf::from("jobs/job-a/bin/main");
+        assert_eq!(report.mismatched, vec![main.clone()]);
+        assert_eq!(report.resynced, vec![main]);
+        assert_eq!(fs::read_to_string(bin_dir.join("main")).unwrap(), "good");
+        assert_eq!(
+            fs::metadata(&bin_dir).unwrap().permissions().mode() & 0o777,
+            0o555
+        );
+        assert_eq!(
+            fs::read_dir(dir.path().join("store/tmp")).unwrap().count(),
+            0
+        );
+
+        let report = verify_artifacts_against_manifest(
+            &lab,
+            &lab_path,
+            &manifest_path,
+            &target,
+            [&job_a],
+            false,
+        )
+        .unwrap();
+        assert!(report.mismatched.is_empty());
+    }
+}
@@ -14,6 +14,9 @@ pub enum ClientError {
         source: repx_core::error::AppError,
     },
 
+    #[error("Not enough disk space on target '{target}': {message}")]
+    InsufficientDiskSpace { target: String, message: String },
+
     #[error("Could not find target '{0}' in configuration.")]
     TargetNotFound(String),
 
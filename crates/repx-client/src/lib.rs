@@ -1,7 +1,8 @@
+pub mod agent;
 pub mod client;
 pub mod error;
 pub mod inputs;
 pub mod orchestration;
 pub mod resources;
 pub mod targets;
-pub use client::{Client, ClientEvent, SubmitOptions};
+pub use client::{Client, ClientEvent, SubmitOptions, VerifyReport};
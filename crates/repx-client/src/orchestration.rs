@@ -1,26 +1,76 @@
-use repx_core::model::{Job, JobId};
+use crate::resources::SbatchDirectives;
+use repx_core::{
+    config::TimeoutPolicy,
+    error::AppError,
+    log_warn,
+    model::{DependencyType, Job, JobId},
+};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
+pub const BUNDLE_JOB_PREFIX: &str = "repx-bundle-";
+pub const SUBMISSION_LOG_FILE: &str = "submitted.log";
+pub const ORCHESTRATOR_LOG_FILE: &str = "orchestrator.log";
+pub const SUBMISSION_INFO_FILE: &str = "submission.json";
+pub const CONDOR_WORKER_DAG_FILE: &str = "workers.dag";
+/// Written to a bundled job's `repx` directory to make the bundle skip it.
+pub const BUNDLE_SKIP_MARKER: &str = "CANCELLED";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JobPlan {
     pub script_hash: String,
     pub dependencies: Vec<JobId>,
     #[serde(default)]
     pub job_type: String,
+    #[serde(default)]
+    pub bundle: Option<String>,
+    #[serde(default)]
+    pub dependency_types: BTreeMap<JobId, DependencyType>,
+    #[serde(default)]
+    pub directives: SbatchDirectives,
+    #[serde(default)]
+    pub on_timeout: Option<TimeoutPolicy>,
+}
+
+impl JobPlan {
+    pub fn dependency_type(&self, dep_id: &JobId) -> DependencyType {
+        self.dependency_types
+            .get(dep_id)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmissionInfo {
+    pub slurm_id: u32,
+    pub attempt: u32,
+    pub directives: SbatchDirectives,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundlePlan {
+    pub directives: SbatchDirectives,
+    pub max_jobs: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrchestrationPlan {
     pub submissions_dir: PathBuf,
+    pub outputs_dir: PathBuf,
     pub jobs: HashMap<JobId, JobPlan>,
+    #[serde(default)]
+    pub bundles: HashMap<String, BundlePlan>,
 }
 
 impl OrchestrationPlan {
     pub fn new(base_path: &Path, lab_content_hash: &str) -> Self {
         Self {
             submissions_dir: base_path.join("submissions").join(lab_content_hash),
+            outputs_dir: base_path.join("outputs"),
             jobs: HashMap::new(),
+            bundles: HashMap::new(),
         }
     }
 
@@ -29,20 +79,55 @@ impl OrchestrationPlan {
         job_id: JobId,
         job_def: &Job,
         script_hash: String,
+        directives: &SbatchDirectives,
         batch_jobs: &HashSet<JobId>,
-    ) {
+    ) -> Result<(), AppError> {
         let entrypoint_exe = job_def
             .executables
             .get("main")
             .or_else(|| job_def.executables.get("scatter"))
             .unwrap();
 
-        let dependencies = entrypoint_exe
-            .inputs
-            .iter()
-            .filter_map(|m| m.job_id.clone())
-            .filter(|dep_id| batch_jobs.contains(dep_id))
-            .collect();
+        let mut dependencies: Vec<JobId> = Vec::new();
+        let mut dependency_types: BTreeMap<JobId, DependencyType> = BTreeMap::new();
+        for mapping in &entrypoint_exe.inputs {
+            let Some(dep_id) = mapping
+                .job_id
+                .as_ref()
+                .filter(|id| batch_jobs.contains(*id))
+            else {
+                continue;
+            };
+            let dependency_type = match mapping.dependency_type.as_deref() {
+                Some(s) => s.parse::<DependencyType>().map_err(|e| {
+                    AppError::ConfigurationError(format!(
+                        "Input '{}' of job '{}': {}",
+                        mapping.target_input, job_id, e
+                    ))
+                })?,
+                None => DependencyType::AfterOk,
+            };
+            let dependency_type = if dependency_type == DependencyType::AfterCorr {
+                log_warn!(
+                    "Input '{}' of job '{}': 'aftercorr' only applies to job arrays, which repx does not submit; using 'afterok'.",
+                    mapping.target_input,
+                    job_id
+                );
+                DependencyType::AfterOk
+            } else {
+                dependency_type
+            };
+            match dependency_types.get(dep_id) {
+                Some(existing) if *existing <= dependency_type => {}
+                _ => {
+                    dependency_types.insert(dep_id.clone(), dependency_type);
+                }
+            }
+            if !dependencies.contains(dep_id) {
+                dependencies.push(dep_id.clone());
+            }
+        }
+        dependency_types.retain(|_, t| *t != DependencyType::AfterOk);
 
         self.jobs.insert(
             job_id,
@@ -50,7 +135,119 @@ impl OrchestrationPlan {
                 script_hash,
                 dependencies,
                 job_type: job_def.stage_type.clone(),
+                bundle: None,
+                dependency_types,
+                directives: directives.clone(),
+                on_timeout: None,
             },
         );
+        Ok(())
+    }
+
+    pub fn submission_order(&self) -> Result<Vec<JobId>, AppError> {
+        let mut remaining: BTreeMap<&JobId, &JobPlan> = self.jobs.iter().collect();
+        let mut order: Vec<JobId> = Vec::with_capacity(remaining.len());
+        while !remaining.is_empty() {
+            let ready: Vec<JobId> = remaining
+                .iter()
+                .filter(|(_, job_plan)| {
+                    job_plan
+                        .dependencies
+                        .iter()
+                        .all(|dep| !remaining.contains_key(dep))
+                })
+                .map(|(job_id, _)| (*job_id).clone())
+                .collect();
+            if ready.is_empty() {
+                return Err(AppError::ConfigurationError(format!(
+                    "Dependency cycle between jobs: {}",
+                    remaining
+                        .keys()
+                        .map(|id| id.0.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )));
+            }
+            for job_id in &ready {
+                remaining.remove(job_id);
+            }
+            order.extend(ready);
+        }
+        Ok(order)
+    }
+
+    pub fn needs_supervision(&self) -> bool {
+        self.jobs.values().any(|job_plan| {
+            job_plan.on_timeout.is_some()
+                || job_plan
+                    .dependencies
+                    .iter()
+                    .any(|dep_id| self.is_bundled(dep_id))
+        })
+    }
+
+    pub fn is_bundled(&self, job_id: &JobId) -> bool {
+        self.jobs
+            .get(job_id)
+            .and_then(|job_plan| job_plan.bundle.as_ref())
+            .is_some_and(|bundle_key| self.bundles.contains_key(bundle_key))
+    }
+
+    pub fn assign_timeout_policy(&mut self, job_id: &JobId, policy: TimeoutPolicy) {
+        if let Some(job_plan) = self.jobs.get_mut(job_id) {
+            job_plan.on_timeout = Some(policy);
+        }
+    }
+
+    pub fn assign_bundle(
+        &mut self,
+        job_id: &JobId,
+        directives: &SbatchDirectives,
+        max_jobs: usize,
+    ) {
+        let bundle_key = format!(
+            "{:x}",
+            Sha256::digest(directives.to_shell_string().as_bytes())
+        );
+        if let Some(job_plan) = self.jobs.get_mut(job_id) {
+            job_plan.bundle = Some(bundle_key.clone());
+        }
+        self.bundles
+            .entry(bundle_key)
+            .or_insert_with(|| BundlePlan {
+                directives: directives.clone(),
+                max_jobs,
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aftercorr_dependency_is_submitted_as_afterok() {
+        let job: Job = serde_json::from_value(serde_json::json!({
+            "params": null,
+            "executables": {"main": {"path": "bin/main", "inputs": [
+                {"job_id": "upstream", "target_input": "data", "dependency_type": "aftercorr"}
+            ]}}
+        }))
+        .unwrap();
+        let upstream = JobId("upstream".into());
+        let downstream = JobId("downstream".into());
+        let mut plan = OrchestrationPlan::new(Path::new("/store"), "lab-hash");
+        plan.add_job(
+            downstream.clone(),
+            &job,
+            "hash".into(),
+            &SbatchDirectives::default(),
+            &HashSet::from([upstream.clone()]),
+        )
+        .unwrap();
+
+        let job_plan = &plan.jobs[&downstream];
+        assert_eq!(job_plan.dependencies, vec![upstream.clone()]);
+        assert_eq!(job_plan.dependency_type(&upstream), DependencyType::AfterOk);
     }
 }
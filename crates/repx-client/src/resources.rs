@@ -1,11 +1,13 @@
 use repx_core::{
-    config::{ResourceRule, Resources},
+    config::{ResourceRule, Resources, TimeoutPolicy, WorkerSuccessThreshold},
     log_debug,
     model::JobId,
 };
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use wildmatch::WildMatch;
 
-#[derive(Debug, Default, Clone, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SbatchDirectives {
     pub partition: Option<String>,
     pub cpus_per_task: Option<u32>,
@@ -126,6 +128,128 @@ pub fn resolve_worker_resources(
     worker_directives
 }
 
+pub fn resolve_min_worker_success(
+    orchestrator_job_id: &JobId,
+    target_name: &str,
+    resources: &Option<Resources>,
+) -> Option<WorkerSuccessThreshold> {
+    let resources = resources.as_ref()?;
+
+    let mut threshold = resources.defaults.min_worker_success;
+    for rule in &resources.rules {
+        let target_matches = rule.target.as_deref().is_none_or(|t| t == target_name);
+        let glob_matches = rule
+            .job_id_glob
+            .as_ref()
+            .is_none_or(|glob| WildMatch::new(glob).matches(&orchestrator_job_id.0));
+        if target_matches && glob_matches && rule.min_worker_success.is_some() {
+            threshold = rule.min_worker_success;
+        }
+    }
+    threshold
+}
+
+pub fn resolve_sbatch_template(
+    job_id: &JobId,
+    target_name: &str,
+    resources: &Option<Resources>,
+) -> Option<PathBuf> {
+    let resources = resources.as_ref()?;
+
+    let mut template = resources.defaults.sbatch_template.clone();
+    for rule in &resources.rules {
+        let target_matches = rule.target.as_deref().is_none_or(|t| t == target_name);
+        let glob_matches = rule
+            .job_id_glob
+            .as_ref()
+            .is_none_or(|glob| WildMatch::new(glob).matches(&job_id.0));
+        if target_matches && glob_matches && rule.sbatch_template.is_some() {
+            template = rule.sbatch_template.clone();
+        }
+    }
+    template
+}
+
+pub fn resolve_on_timeout(
+    job_id: &JobId,
+    target_name: &str,
+    resources: &Option<Resources>,
+) -> Option<TimeoutPolicy> {
+    let resources = resources.as_ref()?;
+
+    let mut policy = resources.defaults.on_timeout.clone();
+    for rule in &resources.rules {
+        let target_matches = rule.target.as_deref().is_none_or(|t| t == target_name);
+        let glob_matches = rule
+            .job_id_glob
+            .as_ref()
+            .is_none_or(|glob| WildMatch::new(glob).matches(&job_id.0));
+        if target_matches && glob_matches && rule.on_timeout.is_some() {
+            policy = rule.on_timeout.clone();
+        }
+    }
+    policy.filter(|p| p.retries > 0)
+}
+
+pub fn escalate_walltime(time: &str, policy: &TimeoutPolicy) -> Option<String> {
+    let current = parse_walltime(time)?;
+    let mut escalated = (current as f64 * policy.factor).ceil() as u64;
+    if let Some(max) = policy.max.as_deref().and_then(parse_walltime) {
+        escalated = escalated.min(max);
+    }
+    (escalated > current).then(|| format_walltime(escalated))
+}
+
+pub fn parse_walltime(time: &str) -> Option<u64> {
+    let (days, rest) = match time.split_once('-') {
+        Some((d, rest)) => (d.parse::<u64>().ok()?, Some(rest)),
+        None => (0, None),
+    };
+    let parts: Vec<u64> = rest
+        .unwrap_or(time)
+        .split(':')
+        .map(|p| p.parse::<u64>().ok())
+        .collect::<Option<_>>()?;
+
+    let seconds = match (rest.is_some(), parts.as_slice()) {
+        (false, [m]) => m * 60,
+        (false, [m, s]) => m * 60 + s,
+        (false, [h, m, s]) => h * 3600 + m * 60 + s,
+        (true, [h]) => h * 3600,
+        (true, [h, m]) => h * 3600 + m * 60,
+        (true, [h, m, s]) => h * 3600 + m * 60 + s,
+        _ => return None,
+    };
+    Some(days * 86400 + seconds)
+}
+
+pub fn format_walltime(seconds: u64) -> String {
+    let days = seconds / 86400;
+    let hours = (seconds % 86400) / 3600;
+    let minutes = (seconds % 3600) / 60;
+    let secs = seconds % 60;
+    if days > 0 {
+        format!("{}-{:02}:{:02}:{:02}", days, hours, minutes, secs)
+    } else {
+        format!("{:02}:{:02}:{:02}", hours, minutes, secs)
+    }
+}
+
+pub fn multiply_walltime(time: &str, factor: f64) -> Option<String> {
+    let seconds = parse_walltime(time)?;
+    Some(format_walltime((seconds as f64 * factor).ceil() as u64))
+}
+
+pub fn bundle_size(job_time: Option<&str>, max_jobs: usize, time_limit: Option<u64>) -> usize {
+    let max_jobs = max_jobs.max(1);
+    match (job_time.and_then(parse_walltime), time_limit) {
+        (Some(job_seconds), Some(limit)) if job_seconds > 0 => {
+            ((limit / job_seconds) as usize).clamp(1, max_jobs)
+        }
+        _ => max_jobs,
+    }
+}
+
 fn merge_rule(current: &mut SbatchDirectives, rule: &ResourceRule) {
     if let Some(val) = &rule.partition {
         current.partition = Some(val.clone());
@@ -238,6 +362,153 @@ cpus-per-task = 4
         assert_eq!(directives.partition, Some("default".into()));
     }
 
+    #[test]
+    fn test_min_worker_success_resolution() {
+        let res: Resources = toml::from_str(
+            r#"
+[[rules]]
+job_id_glob = "*-monte-carlo"
+min_worker_success = 0.9
+
+[[rules]]
+job_id_glob = "*-exact-*"
+min_worker_success = 3
+"#,
+        )
+        .unwrap();
+        let res = Some(res);
+        assert_eq!(
+            resolve_min_worker_success(&JobId("abc-monte-carlo".into()), "any", &res),
+            Some(WorkerSuccessThreshold::Fraction(0.9))
+        );
+        assert_eq!(
+            resolve_min_worker_success(&JobId("abc-exact-job".into()), "any", &res),
+            Some(WorkerSuccessThreshold::Count(3))
+        );
+        assert_eq!(
+            resolve_min_worker_success(&JobId("abc-other".into()), "any", &res),
+            None
+        );
+        assert_eq!(WorkerSuccessThreshold::Fraction(0.9).required(10), 9);
+        assert_eq!(WorkerSuccessThreshold::Fraction(0.9).required(11), 10);
+        assert_eq!(WorkerSuccessThreshold::Count(3).required(10), 3);
+        assert_eq!(WorkerSuccessThreshold::Count(30).required(10), 10);
+
+        for invalid in ["1.5", "0.0", "-0.5"] {
+            let rule = format!("[defaults]\nmin_worker_success = {}\n", invalid);
+            assert!(toml::from_str::<Resources>(&rule).is_err(), "{}", invalid);
+        }
+        for threshold in [
+            WorkerSuccessThreshold::Fraction(1.0),
+            WorkerSuccessThreshold::Fraction(0.25),
+            WorkerSuccessThreshold::Count(4),
+        ] {
+            assert_eq!(threshold.to_string().parse(), Ok(threshold));
+        }
+        assert_eq!(WorkerSuccessThreshold::Fraction(1.0).to_string(), "1.0");
+    }
+
+    #[test]
+    fn test_sbatch_template_resolution() {
+        let res: Resources = toml::from_str(
+            r#"
+[defaults]
+sbatch_template = "/etc/repx/default.sh"
+
+[[rules]]
+job_id_glob = "*-gpu-*"
+target = "gpu-cluster"
+sbatch_template = "/etc/repx/gpu.sh"
+
+[[rules]]
+job_id_glob = "*-gpu-*"
+mem = "8G"
+"#,
+        )
+        .unwrap();
+        let res = Some(res);
+        let gpu_job = JobId("needs-a-gpu-job".into());
+        assert_eq!(
+            resolve_sbatch_template(&gpu_job, "gpu-cluster", &res),
+            Some(PathBuf::from("/etc/repx/gpu.sh"))
+        );
+        assert_eq!(
+            resolve_sbatch_template(&gpu_job, "other-cluster", &res),
+            Some(PathBuf::from("/etc/repx/default.sh"))
+        );
+        assert_eq!(resolve_sbatch_template(&gpu_job, "any", &None), None);
+    }
+
+    #[test]
+    fn test_walltime_formats() {
+        assert_eq!(parse_walltime("30"), Some(1800));
+        assert_eq!(parse_walltime("30:15"), Some(1815));
+        assert_eq!(parse_walltime("01:00:00"), Some(3600));
+        assert_eq!(parse_walltime("2-12"), Some(2 * 86400 + 12 * 3600));
+        assert_eq!(parse_walltime("1-00:30"), Some(86400 + 1800));
+        assert_eq!(parse_walltime("1-02:03:04"), Some(86400 + 7384));
+        assert_eq!(parse_walltime("infinite"), None);
+        assert_eq!(parse_walltime("1:2:3:4"), None);
+
+        assert_eq!(
+            multiply_walltime("00:10:00", 3.0).as_deref(),
+            Some("00:30:00")
+        );
+        assert_eq!(
+            multiply_walltime("20:00:00", 2.0).as_deref(),
+            Some("1-16:00:00")
+        );
+    }
+
+    #[test]
+    fn test_bundle_size_fits_time_limit() {
+        assert_eq!(bundle_size(Some("00:10:00"), 50, Some(3600)), 6);
+        assert_eq!(bundle_size(Some("00:10:00"), 4, Some(3600)), 4);
+        assert_eq!(bundle_size(Some("02:00:00"), 50, Some(3600)), 1);
+        assert_eq!(bundle_size(Some("00:10:00"), 50, None), 50);
+        assert_eq!(bundle_size(None, 50, Some(3600)), 50);
+        assert_eq!(bundle_size(Some("00:10:00"), 0, Some(3600)), 1);
+    }
+
+    #[test]
+    fn test_timeout_escalation() {
+        let res: Resources = toml::from_str(
+            r#"
+[[rules]]
+job_id_glob = "*-sweep-*"
+on_timeout = { retries = 2, max = "48:00:00" }
+
+[[rules]]
+job_id_glob = "*-sweep-fixed"
+on_timeout = { retries = 0 }
+"#,
+        )
+        .unwrap();
+        let res = Some(res);
+        let policy = resolve_on_timeout(&JobId("abc-sweep-1".into()), "any", &res).unwrap();
+        assert_eq!(policy.retries, 2);
+        assert_eq!(policy.factor, 2.0);
+        assert!(resolve_on_timeout(&JobId("abc-sweep-fixed".into()), "any", &res).is_none());
+
+        assert_eq!(
+            escalate_walltime("20:00:00", &policy).as_deref(),
+            Some("1-16:00:00")
+        );
+        assert_eq!(
+            escalate_walltime("1-16:00:00", &policy).as_deref(),
+            Some("2-00:00:00")
+        );
+        assert_eq!(escalate_walltime("2-00:00:00", &policy), None);
+
+        for invalid in ["1.0", "0.5", "-2.0"] {
+            let rule = format!(
+                "[defaults]\non_timeout = {{ retries = 1, factor = {} }}\n",
+                invalid
+            );
+            assert!(toml::from_str::<Resources>(&rule).is_err(), "{}", invalid);
+        }
+    }
+
     #[test]
     fn test_scatter_worker_inherits_parent_if_no_override() {
         let res = get_test_resources();
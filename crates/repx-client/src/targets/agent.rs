@@ -0,0 +1,293 @@
+use crate::agent::{AgentCall, AgentError, AgentRequest, AgentResponse, AGENT_PROTOCOL_VERSION};
+use repx_core::{log_debug, log_info, log_warn};
+use serde_json::Value;
+use std::{
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::{
+        mpsc::{self, Receiver},
+        Mutex, OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(60);
+
+struct AgentSession {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    next_id: u64,
+}
+
+impl AgentSession {
+    fn start(mut cmd: Command, timeout: Option<Duration>) -> Option<Self> {
+        let mut child = cmd
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let stdin = child.stdin.take()?;
+        let stdout = BufReader::new(child.stdout.take()?);
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in stdout.lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut session = Self {
+            child,
+            stdin,
+            lines,
+            next_id: 0,
+        };
+        let hello = session.request(AgentCall::Hello, timeout)?.ok()?;
+        let protocol = hello.get("protocol").and_then(Value::as_u64);
+        (protocol == Some(AGENT_PROTOCOL_VERSION as u64)).then_some(session)
+    }
+
+    /// Returns `None` when the agent exits or does not answer within `timeout`.
+    fn request(
+        &mut self,
+        call: AgentCall,
+        timeout: Option<Duration>,
+    ) -> Option<Result<Value, AgentError>> {
+        self.next_id += 1;
+        let id = self.next_id;
+        let line = serde_json::to_string(&AgentRequest::new(id, call)).ok()?;
+        writeln!(self.stdin, "{}", line).ok()?;
+        self.stdin.flush().ok()?;
+
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            let line = match deadline {
+                Some(deadline) => self
+                    .lines
+                    .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                    .ok()?,
+                None => self.lines.recv().ok()?,
+            };
+            let Ok(response) = serde_json::from_str::<AgentResponse>(&line) else {
+                continue;
+            };
+            if response.id != Some(id) {
+                continue;
+            }
+            return Some(match response.error {
+                Some(error) => Err(error),
+                None => Ok(response.result.unwrap_or(Value::Null)),
+            });
+        }
+    }
+}
+
+impl Drop for AgentSession {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+pub(crate) enum AgentOutcome {
+    Unavailable,
+    Lost,
+    Response(Result<Value, AgentError>),
+}
+
+enum AgentState {
+    Idle,
+    Connected(AgentSession),
+    Unavailable(Instant),
+}
+
+pub(crate) struct AgentConnection {
+    target_name: String,
+    timeout: Option<Duration>,
+    state: Mutex<AgentState>,
+    runner_hash: OnceLock<Option<String>>,
+}
+
+impl AgentConnection {
+    /// `timeout` bounds each request, like `command_timeout` bounds a shell command.
+    pub(crate) fn new(target_name: &str, timeout: Option<Duration>) -> Self {
+        Self {
+            target_name: target_name.to_string(),
+            timeout,
+            state: Mutex::new(AgentState::Idle),
+            runner_hash: OnceLock::new(),
+        }
+    }
+
+    fn runner_path(&self, base_path: &Path) -> Option<PathBuf> {
+        let runner_hash = self.runner_hash.get_or_init(|| {
+            super::find_local_runner_binary()
+                .and_then(|path| super::compute_file_hash(&path))
+                .ok()
+        });
+        runner_hash
+            .as_ref()
+            .map(|hash| base_path.join("bin").join(hash).join("repx-runner"))
+    }
+
+    pub(crate) fn call(
+        &self,
+        base_path: &Path,
+        call: AgentCall,
+        agent_command: impl FnOnce(&Path) -> Command,
+    ) -> AgentOutcome {
+        let mut state = self.state.lock().unwrap();
+        if let AgentState::Unavailable(since) = *state {
+            if since.elapsed() < RECONNECT_INTERVAL {
+                return AgentOutcome::Unavailable;
+            }
+        }
+        if !matches!(*state, AgentState::Connected(_)) {
+            let session = self.runner_path(base_path).and_then(|runner_path| {
+                AgentSession::start(agent_command(&runner_path), self.timeout)
+            });
+            match session {
+                Some(session) => {
+                    log_info!("Connected to the repx agent on '{}'", self.target_name);
+                    *state = AgentState::Connected(session);
+                }
+                None => {
+                    log_debug!(
+                        "No repx agent available on '{}'; using shell commands",
+                        self.target_name
+                    );
+                    *state = AgentState::Unavailable(Instant::now());
+                    return AgentOutcome::Unavailable;
+                }
+            }
+        }
+
+        let AgentState::Connected(session) = &mut *state else {
+            return AgentOutcome::Unavailable;
+        };
+        let started = Instant::now();
+        match session.request(call, self.timeout) {
+            Some(response) => AgentOutcome::Response(response),
+            None => {
+                match self.timeout.filter(|timeout| started.elapsed() >= *timeout) {
+                    Some(timeout) => log_warn!(
+                        "The repx agent on '{}' did not answer within {}s; using shell commands",
+                        self.target_name,
+                        timeout.as_secs()
+                    ),
+                    None => log_debug!(
+                        "Lost the repx agent connection to '{}'; using shell commands",
+                        self.target_name
+                    ),
+                }
+                *state = AgentState::Unavailable(Instant::now());
+                AgentOutcome::Lost
+            }
+        }
+    }
+
+    pub(crate) fn reset(&self) {
+        let mut state = self.state.lock().unwrap();
+        if matches!(*state, AgentState::Unavailable(_)) {
+            *state = AgentState::Idle;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scripted_agent(script: &str) -> Command {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(script);
+        cmd
+    }
+
+    #[test]
+    fn test_agent_session_matches_responses_by_id() {
+        let mut session = AgentSession::start(
+            scripted_agent(
+                r#"read l; echo '{"jsonrpc":"2.0","id":1,"result":{"protocol":1}}'
+               read l; echo 'noise'; echo '{"jsonrpc":"2.0","id":9,"result":null}'
+               echo '{"jsonrpc":"2.0","id":2,"error":{"code":-32000,"message":"boom"}}'"#,
+            ),
+            None,
+        )
+        .unwrap();
+        let response = session.request(AgentCall::Hello, None).unwrap();
+        assert_eq!(response.unwrap_err().message, "boom");
+        assert!(session.request(AgentCall::Hello, None).is_none());
+    }
+
+    #[test]
+    fn test_lost_connection_is_reported_separately_from_unavailable() {
+        let session = AgentSession::start(
+            scripted_agent(
+                r#"read l; echo '{"jsonrpc":"2.0","id":1,"result":{"protocol":1}}'; read l"#,
+            ),
+            None,
+        )
+        .unwrap();
+        let connection = AgentConnection::new("cluster", None);
+        *connection.state.lock().unwrap() = AgentState::Connected(session);
+        let base_path = Path::new("/scratch/repx");
+        let call = AgentCall::SubmitPlan {
+            plan_file: PathBuf::from("/scratch/repx/plan.json"),
+            args: Vec::new(),
+        };
+        assert!(!call.is_idempotent());
+        assert!(matches!(
+            connection.call(base_path, call, |_: &Path| -> Command { unreachable!() }),
+            AgentOutcome::Lost
+        ));
+        assert!(matches!(
+            connection.call(base_path, AgentCall::Hello, |_: &Path| -> Command {
+                unreachable!()
+            }),
+            AgentOutcome::Unavailable
+        ));
+    }
+
+    #[test]
+    fn test_incompatible_agent_is_rejected() {
+        assert!(AgentSession::start(
+            scripted_agent(r#"read l; echo '{"jsonrpc":"2.0","id":1,"result":{"protocol":999}}'"#),
+            None
+        )
+        .is_none());
+        assert!(AgentSession::start(scripted_agent("exit 127"), None).is_none());
+    }
+
+    #[test]
+    fn test_unanswered_request_times_out_and_kills_the_session() {
+        let session = AgentSession::start(
+            scripted_agent(
+                r#"read l; echo '{"jsonrpc":"2.0","id":1,"result":{"protocol":1}}'; read l; sleep 30"#,
+            ),
+            None,
+        )
+        .unwrap();
+        let connection = AgentConnection::new("cluster", Some(Duration::from_millis(200)));
+        *connection.state.lock().unwrap() = AgentState::Connected(session);
+
+        let started = Instant::now();
+        assert!(matches!(
+            connection.call(
+                Path::new("/scratch/repx"),
+                AgentCall::Hello,
+                |_: &Path| -> Command { unreachable!() }
+            ),
+            AgentOutcome::Lost
+        ));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(matches!(
+            *connection.state.lock().unwrap(),
+            AgentState::Unavailable(_)
+        ));
+    }
+}
@@ -3,7 +3,7 @@ use crate::error::{ClientError, Result};
 use fs_err;
 use repx_core::{error::AppError, model::JobId};
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     os::unix::fs::{MetadataExt, PermissionsExt},
     path::{Path, PathBuf},
     process::Command,
@@ -84,6 +84,19 @@ impl Target for LocalTarget {
         Ok(missing)
     }
 
+    fn artifact_hashes(&self, relative_paths: &[PathBuf]) -> Result<HashMap<PathBuf, String>> {
+        let artifacts_path = self.artifacts_base_path();
+        relative_paths
+            .iter()
+            .map(|path| {
+                Ok((
+                    path.clone(),
+                    super::compute_file_hash(&artifacts_path.join(path))?,
+                ))
+            })
+            .collect()
+    }
+
     fn sync_artifact(&self, local_path: &Path, relative_path: &Path) -> Result<()> {
         let dest_path = self.artifacts_base_path().join(relative_path);
         if let Some(parent) = dest_path.parent() {
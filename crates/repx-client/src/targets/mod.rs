@@ -1,15 +1,29 @@
 use crate::error::Result;
-use repx_core::{engine, model::JobId};
+use repx_core::{
+    engine,
+    error::AppError,
+    model::JobId,
+    store::{
+        progress::WorkerProgress,
+        status::{OutcomeMarker, StatusReport},
+    },
+};
 use sha2::{Digest, Sha256};
 use std::{
     collections::{HashMap, HashSet},
     io::Read,
     path::{Path, PathBuf},
-    sync::mpsc::Sender,
 };
 use whoami;
+pub(crate) mod agent;
 pub mod local;
+pub(crate) mod retry;
 pub mod ssh;
+pub(crate) mod ssh_master;
+
+pub(crate) fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
 
 pub(crate) fn compute_file_hash(path: &Path) -> Result<String> {
     let mut file = fs_err::File::open(path).map_err(repx_core::error::AppError::from)?;
@@ -71,11 +85,100 @@ pub struct SlurmJobInfo {
     pub state: SlurmState,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SacctJobInfo {
+    pub slurm_id: u32,
+    pub state: String,
+    pub exit_code: String,
+    pub reason: String,
+}
+
+impl SacctJobInfo {
+    pub fn failure_reason(&self) -> Option<String> {
+        match self.state.as_str() {
+            "PENDING" | "RUNNING" | "REQUEUED" | "RESIZING" | "SUSPENDED" => None,
+            "CANCELLED" if self.reason == "DependencyNeverSatisfied" => Some(self.reason.clone()),
+            "COMPLETED" => Some("COMPLETED without outcome markers".to_string()),
+            "FAILED" => Some(format!("FAILED (exit code {})", self.exit_code)),
+            state => Some(state.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PbsJobInfo {
+    pub pbs_id: u32,
+    pub name: String,
+    pub state: String,
+    pub exit_status: Option<i64>,
+}
+
+impl PbsJobInfo {
+    pub fn queue_status(&self) -> Option<engine::JobStatus> {
+        match self.state.as_str() {
+            "R" | "E" | "B" => Some(engine::JobStatus::Running),
+            "F" | "X" => None,
+            _ => Some(engine::JobStatus::Queued),
+        }
+    }
+
+    pub fn failure_reason(&self) -> Option<String> {
+        if !matches!(self.state.as_str(), "F" | "X") {
+            return None;
+        }
+        Some(match self.exit_status {
+            Some(0) => "finished without outcome markers".to_string(),
+            Some(code) => format!("finished (exit status {})", code),
+            None => "finished".to_string(),
+        })
+    }
+}
+
+pub fn parse_pbs_job_id(id: &str) -> Option<u32> {
+    id.trim().split(['.', '[']).next()?.parse().ok()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CondorJobInfo {
+    pub cluster_id: u32,
+    pub dagman_id: Option<u32>,
+    pub node_name: Option<String>,
+    pub job_status: u32,
+    pub exit_code: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskSpace {
+    pub filesystem: String,
+    pub available_bytes: u64,
+    pub quota_available_bytes: Option<u64>,
+}
+
+impl DiskSpace {
+    pub fn usable_bytes(&self) -> u64 {
+        self.quota_available_bytes
+            .map_or(self.available_bytes, |quota| {
+                quota.min(self.available_bytes)
+            })
+    }
+}
+
+fn condor_dag_constraint(dag_ids: &[u32]) -> String {
+    dag_ids
+        .iter()
+        .map(|id| format!("ClusterId == {id} || DAGManJobId == {id}"))
+        .collect::<Vec<_>>()
+        .join(" || ")
+}
+
 pub trait Target: Send + Sync {
     fn name(&self) -> &str;
     fn base_path(&self) -> &Path;
     fn config(&self) -> &repx_core::config::Target;
     fn run_command(&self, command: &str, args: &[&str]) -> Result<String>;
+    fn run_command_once(&self, command: &str, args: &[&str]) -> Result<String> {
+        self.run_command(command, args)
+    }
     fn scancel(&self, slurm_id: u32) -> Result<()>;
     fn get_missing_artifacts(&self, artifacts: &HashSet<PathBuf>) -> Result<HashSet<PathBuf>>;
     fn sync_artifact(&self, local_path: &Path, relative_path: &Path) -> Result<()>;
@@ -98,20 +201,108 @@ pub trait Target: Send + Sync {
         &self,
         local_lab_path: &Path,
         artifacts: &HashSet<PathBuf>,
-        event_sender: Option<&Sender<super::ClientEvent>>,
+        on_progress: &mut dyn FnMut(&Path, u64),
     ) -> Result<()> {
+        let mut bytes_transferred = 0;
         for relative_path in artifacts {
-            if let Some(sender) = event_sender {
-                let _ = sender.send(super::ClientEvent::SyncingArtifactProgress {
-                    path: relative_path.clone(),
-                });
-            }
+            on_progress(relative_path, bytes_transferred);
             let local_path = local_lab_path.join(relative_path);
             self.sync_artifact(&local_path, relative_path)?;
+            bytes_transferred += fs_err::metadata(&local_path)
+                .map(|metadata| metadata.len())
+                .unwrap_or(0);
+            on_progress(relative_path, bytes_transferred);
         }
         Ok(())
     }
 
+    fn artifact_hashes(&self, relative_paths: &[PathBuf]) -> Result<HashMap<PathBuf, String>> {
+        if relative_paths.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let list: String = relative_paths
+            .iter()
+            .map(|path| format!("{}\n", path.display()))
+            .collect();
+        let list_path = write_artifact_list(self, "artifact-hashes", &list)?;
+        let hash_cmd = format!(
+            "cd {} && xargs -d '\\n' sha256sum -- < {list}; status=$?; rm -f {list}; exit $status",
+            shell_quote(&self.artifacts_base_path().to_string_lossy()),
+            list = shell_quote(&list_path.to_string_lossy())
+        );
+        let output = self.run_command("sh", &["-c", &hash_cmd])?;
+        Ok(parse_sha256sum(&output))
+    }
+
+    /// Returns the original mode of every parent directory that had to be made writable,
+    /// to be passed to `restore_artifact_dirs` once the artifacts are synced again.
+    fn remove_artifacts(&self, relative_paths: &[PathBuf]) -> Result<Vec<(PathBuf, String)>> {
+        if relative_paths.is_empty() {
+            return Ok(Vec::new());
+        }
+        let list: String = relative_paths
+            .iter()
+            .map(|path| format!("{}\n", path.display()))
+            .collect();
+        let list_path = write_artifact_list(self, "remove-artifacts", &list)?;
+        let remove_cmd = format!(
+            "cd {} && while IFS= read -r path; do \
+             dir=$(dirname \"$path\"); stat -c '%a %n' \"$dir\" && chmod u+w \"$dir\" && rm -f \"$path\" || exit 1; \
+             done < {list}; status=$?; rm -f {list}; exit $status",
+            shell_quote(&self.artifacts_base_path().to_string_lossy()),
+            list = shell_quote(&list_path.to_string_lossy())
+        );
+        let output = self.run_command("sh", &["-c", &remove_cmd])?;
+        Ok(parse_dir_modes(&output))
+    }
+
+    fn restore_artifact_dirs(&self, dir_modes: &[(PathBuf, String)]) -> Result<()> {
+        if dir_modes.is_empty() {
+            return Ok(());
+        }
+        let list: String = dir_modes
+            .iter()
+            .map(|(dir, mode)| format!("{} {}\n", mode, dir.display()))
+            .collect();
+        let list_path = write_artifact_list(self, "artifact-dirs", &list)?;
+        let chmod_cmd = format!(
+            "cd {} && while read -r mode dir; do chmod \"$mode\" \"$dir\" || exit 1; done < {list}; \
+             status=$?; rm -f {list}; exit $status",
+            shell_quote(&self.artifacts_base_path().to_string_lossy()),
+            list = shell_quote(&list_path.to_string_lossy())
+        );
+        self.run_command("sh", &["-c", &chmod_cmd])?;
+        Ok(())
+    }
+
+    fn disk_space(&self, path: &Path) -> Result<Option<DiskSpace>> {
+        let df_cmd = format!(
+            "p={}; while [ ! -e \"$p\" ]; do p=$(dirname \"$p\"); done; \
+             df -Pk \"$p\" | tail -n 1; echo {QUOTA_SEPARATOR}; quota -w 2>/dev/null || true",
+            shell_quote(&path.to_string_lossy())
+        );
+        let output = self.run_command("sh", &["-c", &df_cmd])?;
+        Ok(parse_disk_space(&output))
+    }
+
+    fn directory_size(&self, paths: &[PathBuf]) -> Result<u64> {
+        if paths.is_empty() {
+            return Ok(0);
+        }
+        let quoted: Vec<String> = paths
+            .iter()
+            .map(|path| shell_quote(&path.to_string_lossy()))
+            .collect();
+        let du_cmd = format!("du -skc {} 2>/dev/null | tail -n 1", quoted.join(" "));
+        let output = self.run_command("sh", &["-c", &du_cmd])?;
+        Ok(output
+            .split_whitespace()
+            .next()
+            .and_then(|kib| kib.parse::<u64>().ok())
+            .unwrap_or(0)
+            * 1024)
+    }
+
     fn artifacts_base_path(&self) -> PathBuf {
         self.base_path().join("artifacts")
     }
@@ -128,43 +319,307 @@ pub trait Target: Send + Sync {
         Ok(parse_squeue(&output))
     }
 
+    fn sacct(&self, slurm_ids: &[u32]) -> Result<HashMap<u32, SacctJobInfo>> {
+        if slurm_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let id_list: Vec<String> = slurm_ids.iter().map(|id| id.to_string()).collect();
+        let sacct_command = format!(
+            "sacct -n -X -P -o JobIDRaw,State,ExitCode,Reason -j {}",
+            id_list.join(",")
+        );
+        let output = self.run_command("sh", &["-c", &sacct_command])?;
+        Ok(parse_sacct(&output))
+    }
+
+    fn qstat(&self, pbs_ids: &[u32]) -> Result<HashMap<u32, PbsJobInfo>> {
+        if pbs_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let id_list: Vec<String> = pbs_ids.iter().map(|id| id.to_string()).collect();
+        let qstat_command = format!(
+            "qstat -f -F json -x {} 2>/dev/null || true",
+            id_list.join(" ")
+        );
+        let output = self.run_command("sh", &["-c", &qstat_command])?;
+        Ok(parse_qstat_json(&output))
+    }
+
+    fn qdel(&self, pbs_id: u32) -> Result<()> {
+        self.run_command_once("qdel", &[&pbs_id.to_string()])?;
+        Ok(())
+    }
+
+    fn condor_q(&self, dag_ids: &[u32]) -> Result<Vec<CondorJobInfo>> {
+        if dag_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let condor_q_command = format!(
+            "condor_q -constraint '{}' -af:t ClusterId DAGManJobId DAGNodeName JobStatus ExitCode",
+            condor_dag_constraint(dag_ids)
+        );
+        let output = self.run_command("sh", &["-c", &condor_q_command])?;
+        Ok(parse_condor_ads(&output))
+    }
+
+    fn condor_history(&self, dag_ids: &[u32]) -> Result<Vec<CondorJobInfo>> {
+        if dag_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let condor_history_command = format!(
+            "condor_history -constraint '{}' -af:t ClusterId DAGManJobId DAGNodeName JobStatus ExitCode",
+            condor_dag_constraint(dag_ids)
+        );
+        let output = self.run_command("sh", &["-c", &condor_history_command])?;
+        Ok(parse_condor_ads(&output))
+    }
+
+    fn condor_rm(&self, dag_id: u32, node_names: &[String]) -> Result<()> {
+        let nodes: Vec<String> = node_names
+            .iter()
+            .map(|name| format!("DAGNodeName == \"{}\"", name))
+            .collect();
+        let condor_rm_command = format!(
+            "condor_rm -constraint 'DAGManJobId == {} && ({})'",
+            dag_id,
+            nodes.join(" || ")
+        );
+        self.run_command_once("sh", &["-c", &condor_rm_command])?;
+        Ok(())
+    }
+
     fn check_outcome_markers(&self) -> Result<HashMap<JobId, engine::JobStatus>> {
         let outputs_path = self.base_path().join("outputs");
         let find_cmd = format!(
-            "find {} -mindepth 3 -maxdepth 3 \\( -name SUCCESS -o -name FAIL \\) -path '*/repx/*'",
+            "find {} -mindepth 3 -maxdepth 3 \\( -name SUCCESS -o -name FAIL -o -name PARTIAL -o -name REQUEUED \\) -path '*/repx/*'",
             outputs_path.display()
         );
         let output = self
             .run_command("sh", &["-c", &find_cmd])
             .unwrap_or_default();
+        Ok(parse_marker_paths(&output, self.name()))
+    }
 
-        let mut outcomes = HashMap::new();
-        for line in output.lines() {
-            let path = Path::new(line);
-            let file_name = path.file_name().and_then(|s| s.to_str()).unwrap_or("");
-            if let Some(repx_dir) = path.parent() {
-                if let Some(job_dir) = repx_dir.parent() {
-                    let job_id_str = job_dir.file_name().and_then(|s| s.to_str()).unwrap_or("");
-                    let job_id = JobId(job_id_str.to_string());
-                    let location = self.name().to_string();
-
-                    let status = if file_name == "SUCCESS" {
-                        engine::JobStatus::Succeeded { location }
-                    } else if file_name == "FAIL" {
-                        engine::JobStatus::Failed { location }
-                    } else {
-                        continue;
-                    };
-                    outcomes.insert(job_id, status);
-                }
-            }
+    /// Reads the outcome markers of the given jobs only, without scanning `outputs`.
+    fn read_outcome_markers(&self, job_ids: &[JobId]) -> Result<HashMap<JobId, engine::JobStatus>> {
+        if job_ids.is_empty() {
+            return Ok(HashMap::new());
         }
-        Ok(outcomes)
+        let quoted_ids: Vec<String> = job_ids.iter().map(|id| shell_quote(&id.0)).collect();
+        let marker_cmd = format!(
+            "cd {} 2>/dev/null || exit 0; for j in {}; do \
+             for m in SUCCESS FAIL PARTIAL; do \
+             if [ -e \"$j/repx/$m\" ]; then echo \"$j/repx/$m\"; fi; \
+             done; done",
+            shell_quote(&self.base_path().join("outputs").to_string_lossy()),
+            quoted_ids.join(" ")
+        );
+        let output = self.run_command("sh", &["-c", &marker_cmd])?;
+        Ok(parse_marker_paths(&output, self.name()))
+    }
+
+    fn status_report(
+        &self,
+        repx_binary_path: &Path,
+        jobs_file: &Path,
+        submission_log: &Path,
+    ) -> Result<Option<StatusReport>> {
+        shell_status_report(self, repx_binary_path, jobs_file, submission_log)
+    }
+
+    fn run_orchestrator(
+        &self,
+        repx_binary_path: &Path,
+        args: &[String],
+        plan_file: &Path,
+    ) -> Result<String> {
+        self.run_command_once(
+            "sh",
+            &[
+                "-c",
+                &orchestrator_command(repx_binary_path, args, plan_file),
+            ],
+        )
+    }
+
+    fn read_worker_progress(&self, job_ids: &[JobId]) -> Result<HashMap<JobId, WorkerProgress>> {
+        if job_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let quoted_ids: Vec<String> = job_ids
+            .iter()
+            .map(|id| format!("'{}'", id.0.replace('\'', "'\\''")))
+            .collect();
+        let progress_cmd = format!(
+            "cd {} 2>/dev/null || exit 0; for j in {}; do \
+             [ -f \"$j/repx/progress.json\" ] || continue; \
+             s=$(find \"$j\" -mindepth 3 -maxdepth 3 -path \"$j/worker-*/repx/SUCCESS\" | wc -l); \
+             f=$(find \"$j\" -mindepth 3 -maxdepth 3 -path \"$j/worker-*/repx/FAIL\" | wc -l); \
+             printf '%s\\t%s\\t%s\\t' \"$j\" $s $f; cat \"$j/repx/progress.json\"; echo; \
+             done",
+            self.base_path().join("outputs").display(),
+            quoted_ids.join(" ")
+        );
+        let output = self.run_command("sh", &["-c", &progress_cmd])?;
+        Ok(parse_worker_progress(&output))
     }
 
     fn get_remote_path_str(&self, job_id: &JobId) -> String;
 }
 
+pub(crate) fn orchestrator_command(
+    repx_binary_path: &Path,
+    args: &[String],
+    plan_file: &Path,
+) -> String {
+    let mut parts = vec![
+        shell_quote(&repx_binary_path.to_string_lossy()),
+        "internal-orchestrate".to_string(),
+    ];
+    parts.extend(args.iter().map(|arg| shell_quote(arg)));
+    parts.push(shell_quote(&plan_file.to_string_lossy()));
+    parts.join(" ")
+}
+
+fn shell_status_report<T: Target + ?Sized>(
+    target: &T,
+    repx_binary_path: &Path,
+    jobs_file: &Path,
+    submission_log: &Path,
+) -> Result<Option<StatusReport>> {
+    let repx_bin = shell_quote(&repx_binary_path.to_string_lossy());
+    let status_cmd = format!(
+        "[ -x {0} ] || exit 0; {0} internal-status --base-path {1} --jobs-from {2} --submission-log {3}",
+        repx_bin,
+        shell_quote(&target.base_path().to_string_lossy()),
+        shell_quote(&jobs_file.to_string_lossy()),
+        shell_quote(&submission_log.to_string_lossy())
+    );
+    let output = target.run_command("sh", &["-c", &status_cmd])?;
+    if output.trim().is_empty() {
+        return Ok(None);
+    }
+    let report = serde_json::from_str(&output).map_err(AppError::from)?;
+    Ok(Some(report))
+}
+
+fn parse_marker_paths(output: &str, target_name: &str) -> HashMap<JobId, engine::JobStatus> {
+    let mut markers: HashMap<JobId, OutcomeMarker> = HashMap::new();
+    for line in output.lines() {
+        let path = Path::new(line);
+        let file_name = path.file_name().and_then(|s| s.to_str()).unwrap_or("");
+        let Some(marker) = OutcomeMarker::from_file_name(file_name) else {
+            continue;
+        };
+        if let Some(repx_dir) = path.parent() {
+            if let Some(job_dir) = repx_dir.parent() {
+                let job_id_str = job_dir.file_name().and_then(|s| s.to_str()).unwrap_or("");
+                let job_id = JobId(job_id_str.to_string());
+                let marker = match markers.get(&job_id) {
+                    Some(existing) => existing.prevailing(marker),
+                    None => marker,
+                };
+                markers.insert(job_id, marker);
+            }
+        }
+    }
+    markers
+        .into_iter()
+        .map(|(job_id, marker)| (job_id, marker.job_status(target_name.to_string())))
+        .collect()
+}
+
+pub(crate) fn parse_rsync_progress(segment: &str) -> Option<u64> {
+    let mut fields = segment.split_whitespace();
+    let bytes = fields.next()?.replace(',', "").parse().ok()?;
+    fields.next()?.strip_suffix('%')?;
+    Some(bytes)
+}
+
+const QUOTA_SEPARATOR: &str = "--quota--";
+
+fn parse_disk_space(output: &str) -> Option<DiskSpace> {
+    let (df, quota) = output.split_once(QUOTA_SEPARATOR).unwrap_or((output, ""));
+    let fields: Vec<&str> = df.split_whitespace().collect();
+    if fields.len() < 6 {
+        return None;
+    }
+    let filesystem = fields[0].to_string();
+    let available_bytes = fields[3].parse::<u64>().ok()? * 1024;
+
+    let quota_available_bytes = quota.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 4 || fields[0] != filesystem {
+            return None;
+        }
+        let used: u64 = fields[1].trim_end_matches('*').parse().ok()?;
+        let soft: u64 = fields[2].parse().ok()?;
+        let hard: u64 = fields[3].parse().ok()?;
+        let limit = if soft > 0 { soft } else { hard };
+        (limit > 0).then(|| limit.saturating_sub(used) * 1024)
+    });
+
+    Some(DiskSpace {
+        filesystem,
+        available_bytes,
+        quota_available_bytes,
+    })
+}
+
+fn write_artifact_list<T: Target + ?Sized>(target: &T, name: &str, list: &str) -> Result<PathBuf> {
+    let list_path =
+        target
+            .base_path()
+            .join("tmp")
+            .join(format!("{}-{}.list", name, std::process::id()));
+    target.write_remote_file(&list_path, list)?;
+    Ok(list_path)
+}
+
+fn parse_dir_modes(output: &str) -> Vec<(PathBuf, String)> {
+    let mut dir_modes: Vec<(PathBuf, String)> = Vec::new();
+    for line in output.lines() {
+        let Some((mode, dir)) = line.split_once(' ') else {
+            continue;
+        };
+        let dir = PathBuf::from(dir);
+        if !dir_modes.iter().any(|(seen, _)| *seen == dir) {
+            dir_modes.push((dir, mode.to_string()));
+        }
+    }
+    dir_modes
+}
+
+pub(crate) fn parse_sha256sum(output: &str) -> HashMap<PathBuf, String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (hash, path) = line.split_once("  ")?;
+            Some((PathBuf::from(path), hash.to_string()))
+        })
+        .collect()
+}
+
+fn parse_worker_progress(output: &str) -> HashMap<JobId, WorkerProgress> {
+    let mut progress_map = HashMap::new();
+    for line in output.lines() {
+        let parts: Vec<_> = line.splitn(4, '\t').collect();
+        if parts.len() < 4 {
+            continue;
+        }
+        let (Ok(succeeded), Ok(failed)) = (parts[1].parse(), parts[2].parse()) else {
+            continue;
+        };
+        if let Ok(progress) = serde_json::from_str::<WorkerProgress>(parts[3]) {
+            progress_map.insert(
+                JobId(parts[0].to_string()),
+                progress.with_worker_markers(succeeded, failed),
+            );
+        }
+    }
+    progress_map
+}
+
 fn parse_squeue(output: &str) -> HashMap<JobId, SlurmJobInfo> {
     let mut jobs = HashMap::new();
     for line in output.lines() {
@@ -193,6 +648,84 @@ fn parse_squeue(output: &str) -> HashMap<JobId, SlurmJobInfo> {
     jobs
 }
 
+fn parse_sacct(output: &str) -> HashMap<u32, SacctJobInfo> {
+    let mut jobs = HashMap::new();
+    for line in output.lines() {
+        let parts: Vec<_> = line.trim().split('|').collect();
+        if parts.len() < 4 {
+            continue;
+        }
+        let Ok(slurm_id) = parts[0].parse::<u32>() else {
+            continue;
+        };
+        let state = parts[1].split_whitespace().next().unwrap_or("").to_string();
+        jobs.insert(
+            slurm_id,
+            SacctJobInfo {
+                slurm_id,
+                state,
+                exit_code: parts[2].to_string(),
+                reason: parts[3].to_string(),
+            },
+        );
+    }
+    jobs
+}
+
+fn parse_qstat_json(output: &str) -> HashMap<u32, PbsJobInfo> {
+    let mut jobs = HashMap::new();
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(output) else {
+        return jobs;
+    };
+    let Some(entries) = value.get("Jobs").and_then(|j| j.as_object()) else {
+        return jobs;
+    };
+    for (full_id, attributes) in entries {
+        let Some(pbs_id) = parse_pbs_job_id(full_id) else {
+            continue;
+        };
+        let text = |key: &str| {
+            attributes
+                .get(key)
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string()
+        };
+        jobs.insert(
+            pbs_id,
+            PbsJobInfo {
+                pbs_id,
+                name: text("Job_Name"),
+                state: text("job_state"),
+                exit_status: attributes.get("Exit_status").and_then(|v| v.as_i64()),
+            },
+        );
+    }
+    jobs
+}
+
+fn parse_condor_ads(output: &str) -> Vec<CondorJobInfo> {
+    fn defined(field: &str) -> Option<&str> {
+        Some(field.trim()).filter(|f| !f.is_empty() && *f != "undefined")
+    }
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() < 5 {
+                return None;
+            }
+            Some(CondorJobInfo {
+                cluster_id: fields[0].trim().parse().ok()?,
+                dagman_id: defined(fields[1]).and_then(|f| f.parse().ok()),
+                node_name: defined(fields[2]).map(|f| f.to_string()),
+                job_status: fields[3].trim().parse().ok()?,
+                exit_code: defined(fields[4]).and_then(|f| f.parse().ok()),
+            })
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -228,4 +761,160 @@ garbage line to ignore
         let parsed = parse_squeue("");
         assert!(parsed.is_empty());
     }
+
+    #[test]
+    fn test_parse_rsync_progress() {
+        assert_eq!(
+            parse_rsync_progress("  1,234,567  45%   12.34MB/s    0:00:10 (xfr#3, to-chk=2/10)"),
+            Some(1_234_567)
+        );
+        assert_eq!(parse_rsync_progress("jobs/job-a/bin/main"), None);
+        assert_eq!(parse_rsync_progress("sending incremental file list"), None);
+    }
+
+    #[test]
+    fn test_parse_disk_space_with_quota() {
+        let output = "fs01:/scratch  1048576000 524288000 524288000  50% /scratch\n\
+                      --quota--\n\
+                      Disk quotas for user alice (uid 1000):\n     \
+                      Filesystem  blocks   quota   limit   grace   files   quota   limit   grace\n\
+                      fs01:/scratch  900000*  800000 1000000  6days     10       0       0\n\
+                      fs01:/home      1000       0  2000000              10       0       0\n";
+        let space = parse_disk_space(output).unwrap();
+        assert_eq!(space.filesystem, "fs01:/scratch");
+        assert_eq!(space.available_bytes, 524_288_000 * 1024);
+        assert_eq!(space.quota_available_bytes, Some(0));
+        assert_eq!(space.usable_bytes(), 0);
+
+        let space = parse_disk_space("/dev/nvme0n1p2  1000 400 600  40% /\n--quota--\n").unwrap();
+        assert_eq!(space.quota_available_bytes, None);
+        assert_eq!(space.usable_bytes(), 600 * 1024);
+        assert_eq!(parse_disk_space(""), None);
+    }
+
+    #[test]
+    fn test_parse_worker_progress_output() {
+        let output = "job-sg-local\t3\t1\t{\"total\":8,\"queued\":2,\"running\":2,\"succeeded\":3,\"failed\":1}\n\
+                      job-sg-slurm\t5\t0\t{\"total\":10,\"queued\":10,\"running\":0,\"succeeded\":0,\"failed\":0}\n\
+                      job-truncated\t0\t0\t{\"total\":\n";
+        let parsed = parse_worker_progress(output);
+        assert_eq!(parsed.len(), 2);
+
+        let local = parsed.get(&JobId("job-sg-local".into())).unwrap();
+        assert_eq!(local.running, 2);
+        assert_eq!(local.done(), 4);
+
+        let slurm = parsed.get(&JobId("job-sg-slurm".into())).unwrap();
+        assert_eq!(slurm.succeeded, 5);
+        assert_eq!(slurm.queued, 5);
+    }
+
+    #[test]
+    fn test_parse_sacct_output() {
+        let sacct_output = "\
+500|COMPLETED|0:0|None
+501|CANCELLED by 1000|0:0|DependencyNeverSatisfied
+502|FAILED|1:0|None
+503|NODE_FAIL|0:0|None
+504|PENDING|0:0|Priority
+garbage line to ignore
+";
+        let parsed = parse_sacct(sacct_output);
+        assert_eq!(parsed.len(), 5);
+
+        let reason = |id: u32| parsed.get(&id).unwrap().failure_reason();
+        assert_eq!(
+            reason(500).as_deref(),
+            Some("COMPLETED without outcome markers")
+        );
+        assert_eq!(reason(501).as_deref(), Some("DependencyNeverSatisfied"));
+        assert_eq!(reason(502).as_deref(), Some("FAILED (exit code 1:0)"));
+        assert_eq!(reason(503).as_deref(), Some("NODE_FAIL"));
+        assert_eq!(reason(504), None);
+    }
+
+    #[test]
+    fn test_parse_qstat_json_output() {
+        let qstat_output = r#"{
+    "timestamp":1760000000,
+    "pbs_version":"23.06.06",
+    "pbs_server":"pbs-head",
+    "Jobs":{
+        "700.pbs-head":{"Job_Name":"repx-job-a","job_state":"R","queue":"workq"},
+        "701.pbs-head":{"Job_Name":"repx-job-b","job_state":"H"},
+        "702.pbs-head":{"Job_Name":"repx-job-c","job_state":"F","Exit_status":0},
+        "703.pbs-head":{"Job_Name":"repx-job-d","job_state":"F","Exit_status":271}
+    }
+}"#;
+        let parsed = parse_qstat_json(qstat_output);
+        assert_eq!(parsed.len(), 4);
+        assert_eq!(parsed[&700].name, "repx-job-a");
+        assert_eq!(
+            parsed[&700].queue_status(),
+            Some(engine::JobStatus::Running)
+        );
+        assert_eq!(parsed[&701].queue_status(), Some(engine::JobStatus::Queued));
+        assert_eq!(parsed[&701].failure_reason(), None);
+        assert_eq!(parsed[&702].queue_status(), None);
+        assert_eq!(
+            parsed[&702].failure_reason().as_deref(),
+            Some("finished without outcome markers")
+        );
+        assert_eq!(
+            parsed[&703].failure_reason().as_deref(),
+            Some("finished (exit status 271)")
+        );
+
+        assert!(parse_qstat_json("").is_empty());
+        assert_eq!(parse_pbs_job_id("1234.pbs-head"), Some(1234));
+        assert_eq!(parse_pbs_job_id("1235[].pbs-head"), Some(1235));
+        assert_eq!(parse_pbs_job_id("qsub: error"), None);
+    }
+
+    #[test]
+    fn test_parse_condor_ads_output() {
+        let output = "900\tundefined\tundefined\t2\tundefined\n\
+                      901\t900\tjob-a\t4\t0\n\
+                      902\t900\tjob-b.workers\t2\tundefined\n\
+                      garbage line to ignore\n";
+        let parsed = parse_condor_ads(output);
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(parsed[0].dagman_id, None);
+        assert_eq!(parsed[0].node_name, None);
+        assert_eq!(parsed[1].dagman_id, Some(900));
+        assert_eq!(parsed[1].node_name.as_deref(), Some("job-a"));
+        assert_eq!(parsed[1].exit_code, Some(0));
+        assert_eq!(parsed[2].job_status, 2);
+        assert_eq!(
+            condor_dag_constraint(&[900, 950]),
+            "ClusterId == 900 || DAGManJobId == 900 || ClusterId == 950 || DAGManJobId == 950"
+        );
+    }
+
+    #[test]
+    fn test_read_outcome_markers_checks_only_the_given_jobs() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = local::LocalTarget {
+            name: "local".into(),
+            config: toml::from_str(&format!("base_path = \"{}\"", dir.path().display())).unwrap(),
+            local_tools_path: PathBuf::new(),
+        };
+        for (job, marker) in [
+            ("job-a", "SUCCESS"),
+            ("job-b", "REQUEUED"),
+            ("job-c", "FAIL"),
+        ] {
+            let repx_dir = dir.path().join("outputs").join(job).join("repx");
+            fs_err::create_dir_all(&repx_dir).unwrap();
+            fs_err::write(repx_dir.join(marker), "").unwrap();
+        }
+
+        let job_ids = [JobId("job-a".into()), JobId("job-b".into())];
+        let statuses = target.read_outcome_markers(&job_ids).unwrap();
+        assert_eq!(statuses.len(), 1);
+        assert!(matches!(
+            statuses[&JobId("job-a".into())],
+            engine::JobStatus::Succeeded { .. }
+        ));
+    }
 }
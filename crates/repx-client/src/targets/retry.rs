@@ -0,0 +1,446 @@
+use crate::error::{ClientError, Result};
+use repx_core::{config::SshConfig, error::AppError, log_warn};
+use std::{
+    io::{Read, Write},
+    process::{Command, Output, Stdio},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+const DEFAULT_RETRIES: u32 = 2;
+const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_secs(2);
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+const TRANSIENT_STDERR_PATTERNS: &[&str] = &[
+    "Connection reset",
+    "Could not resolve hostname",
+    "No route to host",
+    "Control socket connect",
+    "Connection timed out",
+    "Connection refused",
+    "Connection closed by",
+    "connection unexpectedly closed",
+    "Broken pipe",
+    "kex_exchange_identification",
+    "Network is unreachable",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Transport {
+    Ssh,
+    Rsync,
+}
+
+impl Transport {
+    fn is_transient(self, output: &Output) -> bool {
+        match output.status.code() {
+            Some(10 | 12 | 30 | 35) if self == Transport::Rsync => true,
+            Some(255) => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                TRANSIENT_STDERR_PATTERNS
+                    .iter()
+                    .any(|pattern| stderr.contains(pattern))
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Operation {
+    Command,
+    Sync,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RetryPolicy {
+    pub retries: u32,
+    pub backoff: Duration,
+    pub command_timeout: Option<Duration>,
+    pub sync_timeout: Option<Duration>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: DEFAULT_RETRIES,
+            backoff: DEFAULT_RETRY_BACKOFF,
+            command_timeout: None,
+            sync_timeout: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub(crate) fn from_config(ssh: Option<&SshConfig>) -> Self {
+        let defaults = Self::default();
+        let Some(ssh) = ssh else {
+            return defaults;
+        };
+        Self {
+            retries: ssh.retries.unwrap_or(defaults.retries),
+            backoff: ssh
+                .retry_backoff
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.backoff),
+            command_timeout: ssh.command_timeout.map(Duration::from_secs),
+            sync_timeout: ssh.sync_timeout.map(Duration::from_secs),
+        }
+    }
+
+    pub(crate) fn without_retries(&self) -> Self {
+        Self {
+            retries: 0,
+            ..self.clone()
+        }
+    }
+
+    fn timeout(&self, operation: Operation) -> Option<Duration> {
+        match operation {
+            Operation::Command => self.command_timeout,
+            Operation::Sync => self.sync_timeout,
+        }
+    }
+
+    fn delay(&self, attempt: u32) -> Duration {
+        self.backoff.saturating_mul(2u32.saturating_pow(attempt))
+    }
+
+    pub(crate) fn run(
+        &self,
+        target_name: &str,
+        operation: Operation,
+        transport: Transport,
+        stdin: Option<&[u8]>,
+        make_command: impl FnMut() -> Command,
+        on_transient: impl FnMut(),
+    ) -> Result<Output> {
+        self.run_streaming(
+            target_name,
+            operation,
+            transport,
+            stdin,
+            make_command,
+            on_transient,
+            &mut |_| {},
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn run_streaming(
+        &self,
+        target_name: &str,
+        operation: Operation,
+        transport: Transport,
+        stdin: Option<&[u8]>,
+        mut make_command: impl FnMut() -> Command,
+        mut on_transient: impl FnMut(),
+        on_stdout: &mut dyn FnMut(&str),
+    ) -> Result<Output> {
+        let mut attempt = 0;
+        loop {
+            let mut cmd = make_command();
+            let command_name = cmd.get_program().to_string_lossy().to_string();
+            let result = run_with_timeout(&mut cmd, stdin, self.timeout(operation), on_stdout)
+                .map_err(|e| AppError::ProcessLaunchFailed {
+                    command_name: command_name.clone(),
+                    source: e,
+                })?;
+
+            let failure = match &result {
+                Some(output) if output.status.success() || !transport.is_transient(output) => {
+                    return Ok(result.unwrap());
+                }
+                Some(output) => String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                None => format!(
+                    "'{}' timed out after {}s",
+                    command_name,
+                    self.timeout(operation).unwrap_or_default().as_secs()
+                ),
+            };
+
+            if attempt >= self.retries {
+                return match result {
+                    Some(output) => Ok(output),
+                    None => Err(ClientError::TargetCommandFailed {
+                        target: target_name.to_string(),
+                        source: AppError::ExecutionFailed {
+                            message: format!(
+                                "Remote operation on target '{}' timed out",
+                                target_name
+                            ),
+                            log_path: None,
+                            log_summary: failure,
+                        },
+                    }),
+                };
+            }
+
+            let delay = self.delay(attempt);
+            attempt += 1;
+            log_warn!(
+                "Transient connection failure on target '{}' ({}); retrying in {}s (attempt {} of {})",
+                target_name,
+                failure,
+                delay.as_secs(),
+                attempt,
+                self.retries
+            );
+            on_transient();
+            thread::sleep(delay);
+        }
+    }
+}
+
+fn run_with_timeout(
+    cmd: &mut Command,
+    stdin: Option<&[u8]>,
+    timeout: Option<Duration>,
+    on_stdout: &mut dyn FnMut(&str),
+) -> std::io::Result<Option<Output>> {
+    cmd.stdin(if stdin.is_some() {
+        Stdio::piped()
+    } else {
+        Stdio::null()
+    })
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped());
+    let mut child = cmd.spawn()?;
+
+    if let (Some(mut child_stdin), Some(content)) = (child.stdin.take(), stdin) {
+        let content = content.to_vec();
+        thread::spawn(move || {
+            let _ = child_stdin.write_all(&content);
+        });
+    }
+    let (segment_tx, segment_rx) = mpsc::channel();
+    let stdout_reader = child
+        .stdout
+        .take()
+        .map(|stdout| read_segments(stdout, segment_tx));
+    let stderr_reader = child.stderr.take().map(read_to_end);
+
+    let started = Instant::now();
+    let status = loop {
+        for segment in segment_rx.try_iter() {
+            on_stdout(&segment);
+        }
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if timeout.is_some_and(|limit| started.elapsed() >= limit) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let collect = |reader: Option<thread::JoinHandle<Vec<u8>>>| {
+        reader
+            .map(|handle| handle.join().unwrap_or_default())
+            .unwrap_or_default()
+    };
+    let stdout = collect(stdout_reader);
+    for segment in segment_rx.try_iter() {
+        on_stdout(&segment);
+    }
+    Ok(Some(Output {
+        status,
+        stdout,
+        stderr: collect(stderr_reader),
+    }))
+}
+
+fn read_to_end(mut source: impl Read + Send + 'static) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = source.read_to_end(&mut buffer);
+        buffer
+    })
+}
+
+fn read_segments(
+    mut source: impl Read + Send + 'static,
+    segments: mpsc::Sender<String>,
+) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let mut chunk = [0; 8192];
+        let mut segment_start = 0;
+        while let Ok(count) = source.read(&mut chunk) {
+            if count == 0 {
+                break;
+            }
+            buffer.extend_from_slice(&chunk[..count]);
+            while let Some(end) = buffer[segment_start..]
+                .iter()
+                .position(|&b| b == b'\n' || b == b'\r')
+            {
+                let segment = &buffer[segment_start..segment_start + end];
+                if !segment.is_empty() {
+                    let _ = segments.send(String::from_utf8_lossy(segment).to_string());
+                }
+                segment_start += end + 1;
+            }
+        }
+        if segment_start < buffer.len() {
+            let _ = segments.send(String::from_utf8_lossy(&buffer[segment_start..]).to_string());
+        }
+        buffer
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn policy(retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            retries,
+            backoff: Duration::ZERO,
+            command_timeout: Some(Duration::from_secs(5)),
+            sync_timeout: None,
+        }
+    }
+
+    fn sh(script: &str) -> Command {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(script);
+        cmd
+    }
+
+    #[test]
+    fn test_transport_failures_are_retried() {
+        let attempts = Cell::new(0);
+        let reconnects = Cell::new(0);
+        let output = policy(2)
+            .run(
+                "cluster",
+                Operation::Command,
+                Transport::Ssh,
+                None,
+                || {
+                    attempts.set(attempts.get() + 1);
+                    if attempts.get() < 3 {
+                        sh("echo 'Connection reset by peer' >&2; exit 255")
+                    } else {
+                        sh("echo ok")
+                    }
+                },
+                || reconnects.set(reconnects.get() + 1),
+            )
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+        assert_eq!(attempts.get(), 3);
+        assert_eq!(reconnects.get(), 2);
+    }
+
+    #[test]
+    fn test_remote_command_failures_are_not_retried() {
+        let attempts = Cell::new(0);
+        let output = policy(3)
+            .run(
+                "cluster",
+                Operation::Command,
+                Transport::Ssh,
+                None,
+                || {
+                    attempts.set(attempts.get() + 1);
+                    sh("echo 'sbatch: error: invalid partition' >&2; exit 1")
+                },
+                || {},
+            )
+            .unwrap();
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[test]
+    fn test_transport_messages_without_ssh_exit_code_are_not_retried() {
+        let attempts = Cell::new(0);
+        let output = policy(3)
+            .run(
+                "cluster",
+                Operation::Command,
+                Transport::Ssh,
+                None,
+                || {
+                    attempts.set(attempts.get() + 1);
+                    sh("echo 'squeue: error: Connection refused' >&2; exit 1")
+                },
+                || {},
+            )
+            .unwrap();
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[test]
+    fn test_policy_without_retries_runs_once() {
+        let attempts = Cell::new(0);
+        let output = policy(3)
+            .without_retries()
+            .run(
+                "cluster",
+                Operation::Command,
+                Transport::Ssh,
+                None,
+                || {
+                    attempts.set(attempts.get() + 1);
+                    sh("echo 'Connection reset by peer' >&2; exit 255")
+                },
+                || {},
+            )
+            .unwrap();
+        assert_eq!(output.status.code(), Some(255));
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[test]
+    fn test_stdout_is_streamed_in_segments() {
+        let mut segments = Vec::new();
+        let output = policy(0)
+            .run_streaming(
+                "cluster",
+                Operation::Sync,
+                Transport::Rsync,
+                None,
+                || sh("printf 'a/file\\n  10  1%%\\r  20  2%%\\rlast'"),
+                || {},
+                &mut |segment| segments.push(segment.to_string()),
+            )
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(segments, vec!["a/file", "  10  1%", "  20  2%", "last"]);
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "a/file\n  10  1%\r  20  2%\rlast"
+        );
+    }
+
+    #[test]
+    fn test_timeout_is_retried_then_reported() {
+        let attempts = Cell::new(0);
+        let mut policy = policy(1);
+        policy.command_timeout = Some(Duration::from_millis(100));
+        let err = policy
+            .run(
+                "cluster",
+                Operation::Command,
+                Transport::Ssh,
+                Some(b"ignored"),
+                || {
+                    attempts.set(attempts.get() + 1);
+                    sh("sleep 5")
+                },
+                || {},
+            )
+            .unwrap_err();
+        assert_eq!(attempts.get(), 2);
+        assert!(err.to_string().contains("timed out"), "{}", err);
+    }
+}
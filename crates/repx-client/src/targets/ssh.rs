@@ -1,28 +1,104 @@
-use super::Target;
+use super::agent::{AgentConnection, AgentOutcome};
+use super::retry::{Operation, RetryPolicy, Transport};
+use super::ssh_master::ControlMaster;
+use super::{orchestrator_command, shell_quote, Target};
+use crate::agent::AgentCall;
 use crate::error::{ClientError, Result};
-use repx_core::{error::AppError, log_info, logging, model::JobId};
+use repx_core::{error::AppError, log_info, logging, model::JobId, store::status::StatusReport};
+use serde::de::DeserializeOwned;
 use std::{
     collections::HashSet,
     io::Write,
     os::unix::fs::MetadataExt,
     path::{Path, PathBuf},
     process::Command,
-    sync::mpsc::Sender,
 };
 
-fn shell_quote(s: &str) -> String {
-    format!("'{}'", s.replace('\'', "'\\''"))
-}
-
 pub struct SshTarget {
     pub(crate) name: String,
     pub(crate) address: String,
     pub(crate) config: repx_core::config::Target,
     pub(crate) local_tools_path: PathBuf,
     pub(crate) host_tools_dir_name: String,
+    pub(crate) control_master: ControlMaster,
+    pub(crate) retry_policy: RetryPolicy,
+    pub(crate) agent: AgentConnection,
 }
 
 impl SshTarget {
+    fn ssh_command(&self) -> Command {
+        let ssh_binary = self.local_tool("ssh");
+        self.control_master.ensure(&ssh_binary);
+        let mut cmd = Command::new(ssh_binary);
+        cmd.args(self.control_master.ssh_options());
+        cmd
+    }
+
+    fn scp_command(&self) -> Command {
+        self.control_master.ensure(&self.local_tool("ssh"));
+        let mut cmd = Command::new(self.local_tool("scp"));
+        cmd.args(self.control_master.ssh_options());
+        cmd
+    }
+
+    fn rsync_command(&self) -> Command {
+        let ssh_binary = self.local_tool("ssh");
+        self.control_master.ensure(&ssh_binary);
+        let mut remote_shell = vec![shell_quote(&ssh_binary.to_string_lossy())];
+        remote_shell.extend(
+            self.control_master
+                .ssh_options()
+                .iter()
+                .map(|option| shell_quote(option)),
+        );
+        let mut cmd = Command::new(self.local_tool("rsync"));
+        cmd.arg("-e").arg(remote_shell.join(" "));
+        cmd
+    }
+
+    fn agent_call<T: DeserializeOwned>(&self, call: AgentCall) -> Option<Result<T>> {
+        let is_idempotent = call.is_idempotent();
+        let outcome = self.agent.call(self.base_path(), call, |repx_binary_path| {
+            let mut cmd = self.ssh_command();
+            cmd.arg(&self.address).arg(format!(
+                "{} agent",
+                shell_quote(&repx_binary_path.to_string_lossy())
+            ));
+            cmd
+        });
+        let response = match outcome {
+            AgentOutcome::Unavailable => return None,
+            AgentOutcome::Lost if is_idempotent => return None,
+            AgentOutcome::Lost => {
+                return Some(Err(ClientError::TargetCommandFailed {
+                    target: self.name.clone(),
+                    source: AppError::ExecutionFailed {
+                        message: format!(
+                            "Lost the connection to the repx agent on '{}' during a request that must not be repeated",
+                            self.name
+                        ),
+                        log_path: None,
+                        log_summary: String::new(),
+                    },
+                }))
+            }
+            AgentOutcome::Response(response) => response,
+        };
+        Some(match response {
+            Ok(value) => {
+                serde_json::from_value(value).map_err(|e| ClientError::Core(AppError::from(e)))
+            }
+            Err(error) => Err(ClientError::TargetCommandFailed {
+                target: self.name.clone(),
+                source: AppError::ExecutionFailed {
+                    message: format!("The repx agent on '{}' reported an error", self.name),
+                    log_path: None,
+                    log_summary: error.message,
+                },
+            }),
+        })
+    }
+
     fn local_tool(&self, name: &str) -> PathBuf {
         let tool_path = self.local_tools_path.join(name);
         if tool_path.exists() {
@@ -32,8 +108,55 @@ impl SshTarget {
         }
     }
 
+    fn run_remote(
+        &self,
+        command: &str,
+        args: &[&str],
+        retry_policy: &RetryPolicy,
+    ) -> Result<String> {
+        let remote_cmd_exe = self.remote_tool(command);
+
+        let remote_command_string = if command == "sh" && args.len() == 2 && args[0] == "-c" {
+            format!("sh -c {}", shell_quote(args[1]))
+        } else {
+            let mut all_parts = vec![remote_cmd_exe.as_str()];
+            all_parts.extend_from_slice(args);
+            all_parts.join(" ")
+        };
+
+        let output = retry_policy.run(
+            &self.name,
+            Operation::Command,
+            Transport::Ssh,
+            None,
+            || {
+                let mut cmd = self.ssh_command();
+                cmd.arg(&self.address).arg(&remote_command_string);
+                logging::log_and_print_command(&cmd);
+                cmd
+            },
+            || self.control_master.invalidate(),
+        )?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(ClientError::TargetCommandFailed {
+                target: self.name.clone(),
+                source: AppError::ExecutionFailed {
+                    message: format!(
+                        "Command '{}' failed on target '{}'",
+                        remote_command_string, self.name
+                    ),
+                    log_path: None,
+                    log_summary: stderr.to_string(),
+                },
+            });
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
     fn remote_tool(&self, name: &str) -> String {
-        if ["sbatch", "scancel", "squeue", "sacct", "sh"].contains(&name) {
+        if ["sbatch", "scancel", "squeue", "sacct", "qdel", "sh"].contains(&name) {
             return name.to_string();
         }
 
@@ -71,44 +194,32 @@ impl Target for SshTarget {
     }
 
     fn run_command(&self, command: &str, args: &[&str]) -> Result<String> {
-        let remote_cmd_exe = self.remote_tool(command);
-
-        let remote_command_string = if command == "sh" && args.len() == 2 && args[0] == "-c" {
-            format!("sh -c {}", shell_quote(args[1]))
-        } else {
-            let mut all_parts = vec![remote_cmd_exe.as_str()];
-            all_parts.extend_from_slice(args);
-            all_parts.join(" ")
-        };
-
-        let mut cmd = Command::new(self.local_tool("ssh"));
-        cmd.arg(&self.address).arg(&remote_command_string);
+        self.run_remote(command, args, &self.retry_policy)
+    }
 
-        logging::log_and_print_command(&cmd);
-        let output = cmd.output().map_err(|e| AppError::ProcessLaunchFailed {
-            command_name: "ssh".to_string(),
-            source: e,
-        })?;
+    fn run_command_once(&self, command: &str, args: &[&str]) -> Result<String> {
+        self.run_remote(command, args, &self.retry_policy.without_retries())
+    }
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(ClientError::TargetCommandFailed {
-                target: self.name.clone(),
-                source: AppError::ExecutionFailed {
-                    message: format!(
-                        "Command '{}' failed on target '{}'",
-                        remote_command_string, self.name
-                    ),
-                    log_path: None,
-                    log_summary: stderr.to_string(),
-                },
-            });
+    fn scancel(&self, slurm_id: u32) -> Result<()> {
+        if let Some(result) = self.agent_call(AgentCall::Cancel {
+            scheduler: "slurm".to_string(),
+            ids: vec![slurm_id],
+        }) {
+            return result;
         }
-        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        self.run_command_once("scancel", &[&slurm_id.to_string()])?;
+        Ok(())
     }
 
-    fn scancel(&self, slurm_id: u32) -> Result<()> {
-        self.run_command("scancel", &[&slurm_id.to_string()])?;
+    fn qdel(&self, pbs_id: u32) -> Result<()> {
+        if let Some(result) = self.agent_call(AgentCall::Cancel {
+            scheduler: "pbs".to_string(),
+            ids: vec![pbs_id],
+        }) {
+            return result;
+        }
+        self.run_command_once("qdel", &[&pbs_id.to_string()])?;
         Ok(())
     }
 
@@ -117,6 +228,17 @@ impl Target for SshTarget {
             return Ok(HashSet::new());
         }
 
+        if let Some(existing) = self.agent_call::<HashSet<PathBuf>>(AgentCall::ListArtifacts {
+            base_path: self.artifacts_base_path(),
+        }) {
+            let existing = existing?;
+            return Ok(artifacts
+                .iter()
+                .filter(|required| !existing.contains(*required))
+                .cloned()
+                .collect());
+        }
+
         let artifacts_base = self.artifacts_base_path();
         let find_bin = self.remote_tool("find");
         let mkdir_bin = self.remote_tool("mkdir");
@@ -149,7 +271,7 @@ impl Target for SshTarget {
         &self,
         local_lab_path: &Path,
         artifacts: &HashSet<PathBuf>,
-        _event_sender: Option<&Sender<super::super::ClientEvent>>,
+        on_progress: &mut dyn FnMut(&Path, u64),
     ) -> Result<()> {
         if artifacts.is_empty() {
             return Ok(());
@@ -165,20 +287,40 @@ impl Target for SshTarget {
         }
         temp_file.flush().map_err(AppError::from)?;
 
-        let mut rsync_cmd = Command::new(self.local_tool("rsync"));
-        rsync_cmd
-            .arg("-rLtpz")
-            .arg("--files-from")
-            .arg(temp_file.path())
-            .arg("./")
-            .arg(format!(
-                "{}:{}",
-                self.address,
-                self.artifacts_base_path().display()
-            ))
-            .current_dir(local_lab_path);
-        log_info!("[CMD] Syncing artifact batch with rsync: {:?}", rsync_cmd);
-        let rsync_output = rsync_cmd.output().map_err(AppError::from)?;
+        let mut current_path = PathBuf::new();
+        let rsync_output = self.retry_policy.run_streaming(
+            &self.name,
+            Operation::Sync,
+            Transport::Rsync,
+            None,
+            || {
+                let mut rsync_cmd = self.rsync_command();
+                rsync_cmd
+                    .arg("-rLtpz")
+                    .arg("--info=name1,progress2")
+                    .arg("--files-from")
+                    .arg(temp_file.path())
+                    .arg("./")
+                    .arg(format!(
+                        "{}:{}",
+                        self.address,
+                        self.artifacts_base_path().display()
+                    ))
+                    .current_dir(local_lab_path);
+                log_info!("[CMD] Syncing artifact batch with rsync: {:?}", rsync_cmd);
+                rsync_cmd
+            },
+            || self.control_master.invalidate(),
+            &mut |segment| match super::parse_rsync_progress(segment) {
+                Some(bytes_transferred) => on_progress(&current_path, bytes_transferred),
+                None => {
+                    let path = Path::new(segment.trim());
+                    if artifacts.contains(path) {
+                        current_path = path.to_path_buf();
+                    }
+                }
+            },
+        )?;
 
         if !rsync_output.status.success() {
             let stderr = String::from_utf8_lossy(&rsync_output.stderr);
@@ -211,7 +353,7 @@ impl Target for SshTarget {
         let mkdir_cmd = format!("mkdir -p {}", shell_quote(&remote_parent.to_string_lossy()));
         self.run_command("sh", &["-c", &mkdir_cmd])?;
 
-        let mut scp_cmd = Command::new(self.local_tool("scp"));
+        let mut scp_cmd = self.scp_command();
         if local_path.is_dir() {
             scp_cmd.arg("-r");
         }
@@ -287,7 +429,7 @@ impl Target for SshTarget {
             remote_args.join(" ")
         );
 
-        let mut cmd = Command::new(self.local_tool("ssh"));
+        let mut cmd = self.ssh_command();
         cmd.arg(&self.address).arg(remote_cmd);
         cmd.stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped());
@@ -301,6 +443,12 @@ impl Target for SshTarget {
         })
     }
     fn read_remote_file_tail(&self, path: &Path, line_count: u32) -> Result<Vec<String>> {
+        if let Some(result) = self.agent_call(AgentCall::TailLog {
+            path: path.to_path_buf(),
+            lines: line_count,
+        }) {
+            return result;
+        }
         let quoted_path = shell_quote(&path.to_string_lossy());
         let tail_bin = self.remote_tool("tail");
         let cmd_str = format!(
@@ -312,6 +460,12 @@ impl Target for SshTarget {
     }
 
     fn write_remote_file(&self, path: &Path, content: &str) -> Result<()> {
+        if let Some(result) = self.agent_call(AgentCall::WriteFile {
+            path: path.to_path_buf(),
+            content: content.to_string(),
+        }) {
+            return result;
+        }
         let parent = path.parent().ok_or_else(|| {
             ClientError::Core(AppError::Io(std::io::Error::new(
                 std::io::ErrorKind::InvalidInput,
@@ -329,33 +483,19 @@ impl Target for SshTarget {
             shell_quote(&path.to_string_lossy())
         );
 
-        let mut cmd = Command::new(self.local_tool("ssh"));
-        cmd.arg(&self.address)
-            .arg(&remote_command)
-            .stdin(std::process::Stdio::piped())
-            .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped());
-
-        logging::log_and_print_command(&cmd);
-
-        let mut child = cmd.spawn().map_err(|e| AppError::ProcessLaunchFailed {
-            command_name: "ssh".to_string(),
-            source: e,
-        })?;
-
-        let mut stdin = child.stdin.take().expect("Failed to open stdin for ssh");
-        let content_bytes = content.as_bytes().to_vec();
-        std::thread::spawn(move || {
-            let _ = std::io::Write::write_all(&mut stdin, &content_bytes);
-        });
-
-        let output = child.wait_with_output().map_err(|e| {
-            ClientError::Core(AppError::ExecutionFailed {
-                message: "Failed to wait for remote write command".to_string(),
-                log_path: None,
-                log_summary: e.to_string(),
-            })
-        })?;
+        let output = self.retry_policy.run(
+            &self.name,
+            Operation::Command,
+            Transport::Ssh,
+            Some(content.as_bytes()),
+            || {
+                let mut cmd = self.ssh_command();
+                cmd.arg(&self.address).arg(&remote_command);
+                logging::log_and_print_command(&cmd);
+                cmd
+            },
+            || self.control_master.invalidate(),
+        )?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -371,15 +511,23 @@ impl Target for SshTarget {
         Ok(())
     }
     fn sync_directory(&self, local_path: &Path, remote_path: &Path) -> Result<()> {
-        let mut rsync_cmd = Command::new(self.local_tool("rsync"));
-        rsync_cmd
-            .arg("-rLtpz")
-            .arg("--mkpath")
-            .arg(format!("{}/", local_path.display()))
-            .arg(format!("{}:{}", self.address, remote_path.display()));
-
-        logging::log_and_print_command(&rsync_cmd);
-        let rsync_output = rsync_cmd.output().map_err(AppError::from)?;
+        let rsync_output = self.retry_policy.run(
+            &self.name,
+            Operation::Sync,
+            Transport::Rsync,
+            None,
+            || {
+                let mut rsync_cmd = self.rsync_command();
+                rsync_cmd
+                    .arg("-rLtpz")
+                    .arg("--mkpath")
+                    .arg(format!("{}/", local_path.display()))
+                    .arg(format!("{}:{}", self.address, remote_path.display()));
+                logging::log_and_print_command(&rsync_cmd);
+                rsync_cmd
+            },
+            || self.control_master.invalidate(),
+        )?;
 
         if !rsync_output.status.success() {
             let stderr = String::from_utf8_lossy(&rsync_output.stderr);
@@ -456,7 +604,7 @@ impl Target for SshTarget {
         );
         self.run_command("sh", &["-c", &mkdir_cmd])?;
 
-        let mut scp_cmd = Command::new(self.local_tool("scp"));
+        let mut scp_cmd = self.scp_command();
         scp_cmd.arg(&runner_exe_path).arg(format!(
             "{}:{}",
             self.address,
@@ -484,10 +632,48 @@ impl Target for SshTarget {
         self.run_command("sh", &["-c", &chmod_cmd])?;
 
         verify_execution()?;
+        self.agent.reset();
 
         Ok(remote_dest_path)
     }
 
+    fn status_report(
+        &self,
+        repx_binary_path: &Path,
+        jobs_file: &Path,
+        submission_log: &Path,
+    ) -> Result<Option<StatusReport>> {
+        if let Some(result) = self.agent_call(AgentCall::Status {
+            base_path: self.base_path().to_path_buf(),
+            jobs_from: jobs_file.to_path_buf(),
+            submission_log: Some(submission_log.to_path_buf()),
+        }) {
+            return result.map(Some);
+        }
+        super::shell_status_report(self, repx_binary_path, jobs_file, submission_log)
+    }
+
+    fn run_orchestrator(
+        &self,
+        repx_binary_path: &Path,
+        args: &[String],
+        plan_file: &Path,
+    ) -> Result<String> {
+        if let Some(result) = self.agent_call(AgentCall::SubmitPlan {
+            plan_file: plan_file.to_path_buf(),
+            args: args.to_vec(),
+        }) {
+            return result;
+        }
+        self.run_command_once(
+            "sh",
+            &[
+                "-c",
+                &orchestrator_command(repx_binary_path, args, plan_file),
+            ],
+        )
+    }
+
     fn register_gc_root(&self, project_id: &str, lab_hash: &str) -> Result<()> {
         let gcroots_dir = self
             .base_path()
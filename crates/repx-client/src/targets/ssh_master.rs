@@ -0,0 +1,236 @@
+use repx_core::{log_debug, log_warn};
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+const CONTROL_PERSIST_SECS: u64 = 600;
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const RESTART_INTERVAL: Duration = Duration::from_secs(60);
+
+enum MasterState {
+    Unchecked,
+    Healthy(Instant),
+    Unavailable(Instant),
+}
+
+pub(crate) struct ControlMaster {
+    address: String,
+    connection_options: Vec<String>,
+    socket_path: Option<PathBuf>,
+    state: Mutex<MasterState>,
+}
+
+impl ControlMaster {
+    pub(crate) fn new(target_name: &str, address: &str, connection_options: Vec<String>) -> Self {
+        let socket_path = socket_dir()
+            .map(|dir| dir.join(socket_name(target_name, address, &connection_options)));
+        Self {
+            address: address.to_string(),
+            connection_options,
+            socket_path,
+            state: Mutex::new(MasterState::Unchecked),
+        }
+    }
+
+    pub(crate) fn ssh_options(&self) -> Vec<String> {
+        let mut options = self.connection_options.clone();
+        if let Some(socket_path) = &self.socket_path {
+            options.extend([
+                "-o".to_string(),
+                format!("ControlPath={}", socket_path.display()),
+                "-o".to_string(),
+                "ControlMaster=no".to_string(),
+            ]);
+        }
+        options
+    }
+
+    pub(crate) fn ensure(&self, ssh_binary: &Path) {
+        let Some(socket_path) = &self.socket_path else {
+            return;
+        };
+        let mut state = self.state.lock().unwrap();
+        match *state {
+            MasterState::Healthy(since) if since.elapsed() < HEALTH_CHECK_INTERVAL => return,
+            MasterState::Unavailable(since) if since.elapsed() < RESTART_INTERVAL => return,
+            _ => {}
+        }
+
+        if self.check(ssh_binary, socket_path) {
+            *state = MasterState::Healthy(Instant::now());
+            return;
+        }
+
+        let _ = fs::remove_file(socket_path);
+        log_debug!(
+            "Starting SSH control master for '{}' at {}",
+            self.address,
+            socket_path.display()
+        );
+        let started = Command::new(ssh_binary)
+            .args(&self.connection_options)
+            .arg("-o")
+            .arg("ControlMaster=yes")
+            .arg("-o")
+            .arg(format!("ControlPath={}", socket_path.display()))
+            .arg("-o")
+            .arg(format!("ControlPersist={}", CONTROL_PERSIST_SECS))
+            .arg("-N")
+            .arg("-f")
+            .arg(&self.address)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false);
+
+        if started && self.check(ssh_binary, socket_path) {
+            *state = MasterState::Healthy(Instant::now());
+        } else {
+            *state = MasterState::Unavailable(Instant::now());
+            log_warn!(
+                "Could not start an SSH control master for '{}'; falling back to one connection per command.",
+                self.address
+            );
+        }
+    }
+
+    pub(crate) fn invalidate(&self) {
+        let mut state = self.state.lock().unwrap();
+        if matches!(*state, MasterState::Healthy(_)) {
+            *state = MasterState::Unchecked;
+        }
+    }
+
+    fn check(&self, ssh_binary: &Path, socket_path: &Path) -> bool {
+        if !socket_path.exists() {
+            return false;
+        }
+        Command::new(ssh_binary)
+            .args(&self.connection_options)
+            .arg("-o")
+            .arg(format!("ControlPath={}", socket_path.display()))
+            .arg("-O")
+            .arg("check")
+            .arg(&self.address)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false)
+    }
+}
+
+fn socket_name(target_name: &str, address: &str, connection_options: &[String]) -> String {
+    let digest = Sha256::digest(
+        format!(
+            "{}\0{}\0{}",
+            target_name,
+            address,
+            connection_options.join("\0")
+        )
+        .as_bytes(),
+    );
+    format!("{:x}", digest)[..16].to_string()
+}
+
+fn socket_dir() -> Option<PathBuf> {
+    let xdg_dirs = xdg::BaseDirectories::with_prefix("repx");
+    if let Ok(dir) = xdg_dirs.create_runtime_directory("ssh") {
+        return Some(dir);
+    }
+
+    let dir = std::env::temp_dir().join(format!("repx-ssh-{}", whoami::username()));
+    fs::create_dir_all(&dir).ok()?;
+    fs::set_permissions(&dir, fs::Permissions::from_mode(0o700)).ok()?;
+    Some(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_socket_name_is_short_and_per_target() {
+        let port = vec!["-o".to_string(), "Port=2222".to_string()];
+        let name = socket_name("cluster", "user@login.example.org", &[]);
+        assert_eq!(name.len(), 16);
+        assert_eq!(name, socket_name("cluster", "user@login.example.org", &[]));
+        assert_ne!(name, socket_name("other", "user@login.example.org", &[]));
+        assert_ne!(name, socket_name("cluster", "user@login2.example.org", &[]));
+        assert_ne!(
+            name,
+            socket_name("cluster", "user@login.example.org", &port)
+        );
+    }
+
+    #[test]
+    fn test_connection_options_precede_control_options() {
+        let dir = tempfile::tempdir().unwrap();
+        let master = ControlMaster {
+            address: "nowhere".to_string(),
+            connection_options: vec!["-o".to_string(), "Port=2222".to_string()],
+            socket_path: Some(dir.path().join("socket")),
+            state: Mutex::new(MasterState::Unchecked),
+        };
+        assert!(!master.check(Path::new("ssh"), &dir.path().join("socket")));
+        assert_eq!(
+            master.ssh_options(),
+            vec![
+                "-o".to_string(),
+                "Port=2222".to_string(),
+                "-o".to_string(),
+                format!("ControlPath={}", dir.path().join("socket").display()),
+                "-o".to_string(),
+                "ControlMaster=no".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failed_start_is_not_retried_until_the_restart_interval() {
+        let dir = tempfile::tempdir().unwrap();
+        let ssh = dir.path().join("ssh");
+        fs::write(
+            &ssh,
+            format!(
+                "#!/bin/sh\necho \"$*\" >> {}/calls\nexit 255\n",
+                dir.path().display()
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&ssh, fs::Permissions::from_mode(0o755)).unwrap();
+        let master = ControlMaster {
+            address: "nowhere".to_string(),
+            connection_options: Vec::new(),
+            socket_path: Some(dir.path().join("socket")),
+            state: Mutex::new(MasterState::Unchecked),
+        };
+        let starts = || {
+            fs::read_to_string(dir.path().join("calls"))
+                .unwrap_or_default()
+                .lines()
+                .filter(|call| call.contains("ControlMaster=yes"))
+                .count()
+        };
+
+        master.ensure(&ssh);
+        assert_eq!(starts(), 1);
+        master.ensure(&ssh);
+        master.invalidate();
+        master.ensure(&ssh);
+        assert_eq!(starts(), 1);
+
+        *master.state.lock().unwrap() = MasterState::Unavailable(Instant::now() - RESTART_INTERVAL);
+        master.ensure(&ssh);
+        assert_eq!(starts(), 2);
+    }
+}
@@ -2,8 +2,9 @@ use crate::error::AppError;
 use crate::theme;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::time::Duration;
 use xdg::BaseDirectories;
@@ -24,7 +25,7 @@ theme = "default"
 submission_target = "local"
 
 # The default scheduler to use if not specified in a target or via the CLI.
-# Can be "slurm" or "local".
+# Can be "slurm", "pbs", "condor", "ssh-pool" or "local".
 default_scheduler = "local"
 
 # --- Logging Configuration ---
@@ -82,6 +83,69 @@ max_age_days = 7
   #
   #   [targets.safari.slurm]
   #   execution_types = ["podman", "native"]
+  #   # Optional: pack up to this many ready jobs with identical resources into one
+  #   # allocation, which runs them one after another. Useful for many short jobs.
+  #   # max_jobs_per_bundle = 50
+  #   # What to do with already queued jobs if a submission fails part-way:
+  #   # "cancel" (default) scancels them, "keep" leaves them queued and tracked.
+  #   # An orchestrator left running in the background always keeps them.
+  #   # on_submit_failure = "cancel"
+  #   # Optional: throttle submission to stay within account/QOS limits. When set, the
+  #   # orchestrator keeps running in the background on the login node and submits
+  #   # further jobs as earlier ones leave the queue.
+  #   # max_in_flight_jobs = 500
+  #   # max_submissions_per_minute = 120
+  #   # Optional: use your own sbatch script instead of the built-in one, e.g. to run
+  #   # `module load` or set up scratch space. It must contain `{{repx_command}}`; see the
+  #   # README for the other placeholders. Rules in resources.toml can override this.
+  #   # sbatch_template = "~/.config/repx/sbatch-safari.sh"
+
+  # Example of an HTCondor pool. Each submission becomes one DAGMan workflow.
+  # [targets.condor-pool]
+  # address = "condor-submit"
+  # base_path = "/shared/$USER/repx-store"
+  # default_scheduler = "condor"
+  #
+  #   [targets.condor-pool.condor]
+  #   execution_types = ["native"]
+
+  # Example of a PBS Pro / OpenPBS cluster. Jobs are submitted with qsub and tracked
+  # with `qstat -f -F json`; resources.toml rules are translated into #PBS directives.
+  # [targets.pbs-cluster]
+  # address = "pbs-login"
+  # base_path = "/scratch/$USER/repx-store"
+  # default_scheduler = "pbs"
+  #
+  #   # Optional: SSH settings for this target, applied to every ssh, scp and rsync call.
+  #   # Useful where ~/.ssh/config is not available, e.g. on CI runners.
+  #   [targets.pbs-cluster.ssh]
+  #   port = 2222
+  #   identity_file = "~/.ssh/id_ed25519_cluster"
+  #   proxy_jump = "user@bastion.example.org"
+  #   connect_timeout = 15
+  #   options = ["ServerAliveInterval=30", "StrictHostKeyChecking=accept-new"]
+  #   # Dropped connections are retried with exponential backoff (defaults: 2 retries,
+  #   # starting at 2 seconds). Timeouts in seconds are unset by default.
+  #   # retries = 2
+  #   # retry_backoff = 2
+  #   # command_timeout = 300
+  #   # sync_timeout = 3600
+  #
+  #   [targets.pbs-cluster.pbs]
+  #   execution_types = ["native", "podman"]
+  #   # on_submit_failure = "cancel"
+
+  # Example of a pool of workstations without a batch scheduler. Jobs are started over
+  # SSH on the listed targets, which must share this target's store (each may mount it
+  # under its own base_path). The numbers are how many jobs each host runs at once.
+  # [targets.workstations]
+  # address = "ws1"
+  # base_path = "/shared/$USER/repx-store"
+  # default_scheduler = "ssh-pool"
+  #
+  #   [targets.workstations.ssh-pool]
+  #   execution_types = ["native", "bwrap"]
+  #   hosts = { ws1 = 32, ws2 = 16 }
 "#;
 
 const DEFAULT_RESOURCES_CONTENT: &str = r#"# Repx Resource Configuration File
@@ -117,6 +181,25 @@ const DEFAULT_RESOURCES_CONTENT: &str = r#"# Repx Resource Configuration File
 #   mem = "8G"
 #   cpus-per-task = 1
 #   time = "02:00:00"
+
+[[rules]]
+# Example: Let a Monte-Carlo style scatter-gather job gather over the workers that
+# succeeded, as long as at least 90% of them did. An integer requires an absolute
+# number of successful workers instead (e.g. `min_worker_success = 50`).
+# job_id_glob = "*-monte-carlo"
+# min_worker_success = 0.9
+
+[[rules]]
+# Example: Resubmit parameter-sweep jobs that hit their SLURM time limit, doubling `time`
+# each time (at most twice, and never beyond 48 hours).
+# job_id_glob = "*-sweep-*"
+# time = "04:00:00"
+# on_timeout = { retries = 2, factor = 2.0, max = "48:00:00" }
+
+[[rules]]
+# Example: Submit GPU jobs with a custom sbatch script template instead of the built-in one.
+# job_id_glob = "*-gpu-*"
+# sbatch_template = "~/.config/repx/sbatch-gpu.sh"
 "#;
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
@@ -125,6 +208,54 @@ pub struct SchedulerConfig {
     #[serde(default)]
     pub execution_types: Vec<String>,
     pub local_concurrency: Option<usize>,
+    pub max_jobs_per_bundle: Option<usize>,
+    pub on_submit_failure: Option<SubmitFailurePolicy>,
+    pub max_in_flight_jobs: Option<usize>,
+    pub max_submissions_per_minute: Option<u32>,
+    pub sbatch_template: Option<PathBuf>,
+    /// Only read by the `ssh-pool` scheduler; rejected for the others when the config is loaded.
+    #[serde(default)]
+    pub hosts: BTreeMap<String, usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SubmitFailurePolicy {
+    #[default]
+    Cancel,
+    Keep,
+}
+
+impl fmt::Display for SubmitFailurePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubmitFailurePolicy::Cancel => write!(f, "cancel"),
+            SubmitFailurePolicy::Keep => write!(f, "keep"),
+        }
+    }
+}
+
+impl FromStr for SubmitFailurePolicy {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "cancel" => Ok(SubmitFailurePolicy::Cancel),
+            "keep" => Ok(SubmitFailurePolicy::Keep),
+            other => Err(format!(
+                "invalid submit failure policy '{}' (expected 'cancel' or 'keep')",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiskCheckPolicy {
+    Abort,
+    #[default]
+    Warn,
+    Off,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -140,9 +271,59 @@ pub struct Target {
     #[serde(default)]
     pub mount_paths: Vec<String>,
     #[serde(default)]
+    pub verify_artifacts: bool,
+    pub disk_check: Option<DiskCheckPolicy>,
+    #[serde(default)]
     pub local: Option<SchedulerConfig>,
     #[serde(default)]
     pub slurm: Option<SchedulerConfig>,
+    #[serde(default)]
+    pub pbs: Option<SchedulerConfig>,
+    #[serde(default)]
+    pub condor: Option<SchedulerConfig>,
+    #[serde(default, rename = "ssh-pool")]
+    pub ssh_pool: Option<SchedulerConfig>,
+    #[serde(default)]
+    pub ssh: Option<SshConfig>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct SshConfig {
+    pub port: Option<u16>,
+    pub identity_file: Option<PathBuf>,
+    pub proxy_jump: Option<String>,
+    pub connect_timeout: Option<u64>,
+    #[serde(default)]
+    pub options: Vec<String>,
+    pub retries: Option<u32>,
+    pub retry_backoff: Option<u64>,
+    pub command_timeout: Option<u64>,
+    pub sync_timeout: Option<u64>,
+}
+
+impl SshConfig {
+    pub fn to_ssh_options(&self) -> Vec<String> {
+        let mut options = Vec::new();
+        if let Some(port) = self.port {
+            options.push(format!("Port={}", port));
+        }
+        if let Some(identity_file) = &self.identity_file {
+            options.push(format!("IdentityFile={}", identity_file.display()));
+            options.push("IdentitiesOnly=yes".to_string());
+        }
+        if let Some(proxy_jump) = &self.proxy_jump {
+            options.push(format!("ProxyJump={}", proxy_jump));
+        }
+        if let Some(timeout) = self.connect_timeout {
+            options.push(format!("ConnectTimeout={}", timeout));
+        }
+        options.extend(self.options.iter().cloned());
+        options
+            .into_iter()
+            .flat_map(|option| ["-o".to_string(), option])
+            .collect()
+    }
 }
 
 const TUI_DEFAULT_TICK_RATE_MS: u64 = 1000;
@@ -199,6 +380,124 @@ pub struct ResourceRule {
     pub sbatch_opts: Vec<String>,
     #[serde(default)]
     pub worker_resources: Option<Box<ResourceRule>>,
+    pub min_worker_success: Option<WorkerSuccessThreshold>,
+    pub sbatch_template: Option<PathBuf>,
+    pub on_timeout: Option<TimeoutPolicy>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(try_from = "RawTimeoutPolicy")]
+pub struct TimeoutPolicy {
+    pub retries: u32,
+    pub factor: f64,
+    pub max: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawTimeoutPolicy {
+    retries: u32,
+    #[serde(default = "default_timeout_factor")]
+    factor: f64,
+    max: Option<String>,
+}
+
+fn default_timeout_factor() -> f64 {
+    2.0
+}
+
+impl TryFrom<RawTimeoutPolicy> for TimeoutPolicy {
+    type Error = String;
+    fn try_from(raw: RawTimeoutPolicy) -> Result<Self, Self::Error> {
+        if !(raw.factor > 1.0 && raw.factor.is_finite()) {
+            return Err(format!(
+                "on_timeout factor must be greater than 1, got '{}'",
+                raw.factor
+            ));
+        }
+        Ok(TimeoutPolicy {
+            retries: raw.retries,
+            factor: raw.factor,
+            max: raw.max,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(untagged, try_from = "RawWorkerSuccessThreshold")]
+pub enum WorkerSuccessThreshold {
+    Count(usize),
+    Fraction(f64),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawWorkerSuccessThreshold {
+    Count(usize),
+    Fraction(f64),
+}
+
+impl TryFrom<RawWorkerSuccessThreshold> for WorkerSuccessThreshold {
+    type Error = String;
+    fn try_from(raw: RawWorkerSuccessThreshold) -> Result<Self, Self::Error> {
+        match raw {
+            RawWorkerSuccessThreshold::Count(n) => Ok(WorkerSuccessThreshold::Count(n)),
+            RawWorkerSuccessThreshold::Fraction(f) => WorkerSuccessThreshold::fraction(f),
+        }
+    }
+}
+
+impl WorkerSuccessThreshold {
+    fn fraction(fraction: f64) -> Result<Self, String> {
+        if !(fraction > 0.0 && fraction <= 1.0) {
+            return Err(format!(
+                "worker success fraction must be in (0, 1], got '{}'",
+                fraction
+            ));
+        }
+        Ok(WorkerSuccessThreshold::Fraction(fraction))
+    }
+
+    /// A count above `total` is clamped, since it could never be met.
+    pub fn required(&self, total: usize) -> usize {
+        match *self {
+            WorkerSuccessThreshold::Count(n) => n.min(total),
+            WorkerSuccessThreshold::Fraction(f) => (f * total as f64).ceil() as usize,
+        }
+    }
+}
+
+impl fmt::Display for WorkerSuccessThreshold {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkerSuccessThreshold::Count(n) => write!(f, "{}", n),
+            WorkerSuccessThreshold::Fraction(x) => {
+                let fraction = x.to_string();
+                if fraction.contains('.') {
+                    write!(f, "{}", fraction)
+                } else {
+                    write!(f, "{}.0", fraction)
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for WorkerSuccessThreshold {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.contains('.') {
+            let fraction: f64 = s
+                .parse()
+                .map_err(|_| format!("invalid worker success fraction '{}'", s))?;
+            WorkerSuccessThreshold::fraction(fraction)
+        } else {
+            s.parse()
+                .map(WorkerSuccessThreshold::Count)
+                .map_err(|_| format!("invalid worker success count '{}'", s))
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
@@ -295,11 +594,19 @@ pub fn load_resources(
     if merged_value.as_table().is_none_or(|t| t.is_empty()) {
         Ok(None)
     } else {
-        let final_resources: Resources = merged_value.try_into().map_err(AppError::Toml)?;
+        let mut final_resources: Resources = merged_value.try_into().map_err(AppError::Toml)?;
+        for rule in std::iter::once(&mut final_resources.defaults).chain(&mut final_resources.rules)
+        {
+            rule.sbatch_template = rule.sbatch_template.as_deref().map(expand_tilde);
+        }
         Ok(Some(final_resources))
     }
 }
 
+fn expand_tilde(path: &Path) -> PathBuf {
+    PathBuf::from(shellexpand::tilde(&path.to_string_lossy()).into_owned())
+}
+
 pub fn load_config() -> Result<Config, AppError> {
     let xdg_dirs = BaseDirectories::with_prefix("repx");
 
@@ -326,6 +633,37 @@ pub fn load_config() -> Result<Config, AppError> {
             })?);
         }
 
+        if let Some(slurm) = target.slurm.as_mut() {
+            slurm.sbatch_template = slurm.sbatch_template.as_deref().map(expand_tilde);
+        }
+
+        if let Some(ssh) = target.ssh.as_mut() {
+            ssh.identity_file = ssh.identity_file.as_deref().map(expand_tilde);
+            if target.address.is_none() {
+                return Err(AppError::ConfigurationError(format!(
+                    "Target '{}': an [ssh] table requires an `address`.",
+                    name
+                )));
+            }
+        }
+
+        for (scheduler, scheduler_config) in [
+            ("local", &target.local),
+            ("slurm", &target.slurm),
+            ("pbs", &target.pbs),
+            ("condor", &target.condor),
+        ] {
+            if scheduler_config
+                .as_ref()
+                .is_some_and(|scheduler_config| !scheduler_config.hosts.is_empty())
+            {
+                return Err(AppError::ConfigurationError(format!(
+                    "Target '{}': `hosts` is only supported in the [ssh-pool] table, not in [{}].",
+                    name, scheduler
+                )));
+            }
+        }
+
         if target.address.is_none() && !target.base_path.is_absolute() {
             return Err(AppError::ConfigurationError(format!(
                 "Target '{}': `base_path` for local targets must be an absolute path or start with '~'. Got: '{}'",
@@ -6,10 +6,13 @@ use std::collections::{BTreeMap, HashMap, HashSet};
 #[serde(tag = "type", content = "payload")]
 pub enum JobStatus {
     Succeeded { location: String },
+    PartiallySucceeded { location: String },
     Failed { location: String },
+    SchedulerFailed { location: String, reason: String },
     Pending,
     Queued,
     Running,
+    Requeued { location: String },
     Blocked { missing_deps: HashSet<JobId> },
 }
 fn get_all_dependencies(job: &Job) -> impl Iterator<Item = &JobId> {
@@ -51,7 +54,10 @@ pub fn resolve_job_status<'a>(
     let dependencies = get_all_dependencies(job);
     for dep_id in dependencies {
         let dep_status = resolve_job_status(dep_id, lab, cache);
-        if !matches!(dep_status, JobStatus::Succeeded { .. }) {
+        if !matches!(
+            dep_status,
+            JobStatus::Succeeded { .. } | JobStatus::PartiallySucceeded { .. }
+        ) {
             all_deps_succeeded = false;
             missing_deps.insert(dep_id.clone());
         }
@@ -83,9 +89,14 @@ pub fn determine_run_aggregate_statuses(
 
             for job_id in &run.jobs {
                 match all_job_statuses.get(job_id) {
-                    Some(JobStatus::Succeeded { .. }) => succeeded_count += 1,
-                    Some(JobStatus::Failed { .. }) => has_failed = true,
-                    Some(JobStatus::Running) => has_running = true,
+                    Some(JobStatus::Succeeded { .. })
+                    | Some(JobStatus::PartiallySucceeded { .. }) => succeeded_count += 1,
+                    Some(JobStatus::Failed { .. }) | Some(JobStatus::SchedulerFailed { .. }) => {
+                        has_failed = true
+                    }
+                    Some(JobStatus::Running) | Some(JobStatus::Requeued { .. }) => {
+                        has_running = true
+                    }
                     Some(JobStatus::Queued) => has_queued = true,
                     Some(JobStatus::Pending) => has_pending = true,
                     Some(JobStatus::Blocked { .. }) => has_blocked = true,
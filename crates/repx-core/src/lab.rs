@@ -26,6 +26,17 @@ fn find_manifest_path(lab_path: &Path) -> Option<PathBuf> {
 }
 
 pub fn load_from_path(initial_path: &Path) -> Result<Lab, AppError> {
+    let (lab, lab_path) = load_metadata(initial_path)?;
+    validate_lab_files(&lab, &lab_path)?;
+    log_debug!("Lab validation successful.");
+    Ok(lab)
+}
+
+pub fn load_metadata_from_path(initial_path: &Path) -> Result<Lab, AppError> {
+    load_metadata(initial_path).map(|(lab, _)| lab)
+}
+
+fn load_metadata(initial_path: &Path) -> Result<(Lab, PathBuf), AppError> {
     log_debug!(
         "Attempting to load lab from initial path: '{}'",
         initial_path.display()
@@ -185,6 +196,10 @@ pub fn load_from_path(initial_path: &Path) -> Result<Lab, AppError> {
         lab.jobs.len()
     );
 
+    Ok((lab, lab_path))
+}
+
+fn validate_lab_files(lab: &Lab, lab_path: &Path) -> Result<(), AppError> {
     let jobs_dir = lab_path.join("jobs");
     if !jobs_dir.is_dir() {
         return Err(AppError::Io(std::io::Error::new(
@@ -225,6 +240,5 @@ pub fn load_from_path(initial_path: &Path) -> Result<Lab, AppError> {
         }
     }
 
-    log_debug!("Lab validation successful.");
-    Ok(lab)
+    Ok(())
 }
@@ -74,6 +74,48 @@ impl FromStr for RunId {
     }
 }
 
+#[derive(
+    Debug, Clone, Copy, Default, Eq, PartialEq, Hash, Serialize, Deserialize, Ord, PartialOrd,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyType {
+    #[default]
+    AfterOk,
+    AfterCorr,
+    AfterAny,
+}
+
+impl DependencyType {
+    pub fn requires_success(&self) -> bool {
+        !matches!(self, DependencyType::AfterAny)
+    }
+}
+
+impl fmt::Display for DependencyType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DependencyType::AfterOk => write!(f, "afterok"),
+            DependencyType::AfterCorr => write!(f, "aftercorr"),
+            DependencyType::AfterAny => write!(f, "afterany"),
+        }
+    }
+}
+
+impl FromStr for DependencyType {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "afterok" => Ok(DependencyType::AfterOk),
+            "aftercorr" => Ok(DependencyType::AfterCorr),
+            "afterany" => Ok(DependencyType::AfterAny),
+            _ => Err(format!(
+                "invalid dependency type '{}': expected 'afterok', 'afterany' or 'aftercorr'",
+                s
+            )),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct InputMapping {
     pub job_id: Option<JobId>,
@@ -191,4 +233,16 @@ mod tests {
     fn test_runid_from_str_err_pending() {
         assert!(RunId::from_str("pending").is_err());
     }
+
+    #[test]
+    fn test_dependency_type_from_str() {
+        assert_eq!(
+            DependencyType::from_str("afterany"),
+            Ok(DependencyType::AfterAny)
+        );
+        assert_eq!(DependencyType::AfterCorr.to_string(), "aftercorr");
+        assert!(DependencyType::from_str("after").is_err());
+        assert!(DependencyType::AfterOk.requires_success());
+        assert!(!DependencyType::AfterAny.requires_success());
+    }
 }
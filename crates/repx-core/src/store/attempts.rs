@@ -0,0 +1,101 @@
+use crate::error::AppError;
+use std::fs;
+use std::path::Path;
+
+pub const REQUEUED_MARKER: &str = "REQUEUED";
+pub const ATTEMPTS_DIR: &str = "attempts";
+const INPUTS_FILE: &str = "inputs.json";
+
+pub fn slurm_restart_count() -> u32 {
+    std::env::var("SLURM_RESTART_COUNT")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+pub fn prepare_attempt(repx_dir: &Path, restart_count: u32) -> Result<(), AppError> {
+    let marker_path = repx_dir.join(REQUEUED_MARKER);
+    if restart_count == 0 {
+        let _ = fs::remove_file(marker_path);
+        return Ok(());
+    }
+
+    let archive_dir = repx_dir
+        .join(ATTEMPTS_DIR)
+        .join((restart_count - 1).to_string());
+    if !archive_dir.exists() {
+        fs::create_dir_all(&archive_dir)?;
+        for entry in fs::read_dir(repx_dir)? {
+            let entry = entry?;
+            let file_name = entry.file_name();
+            let name = file_name.to_string_lossy();
+            if name == ATTEMPTS_DIR || name == INPUTS_FILE || name == REQUEUED_MARKER {
+                continue;
+            }
+            if name.starts_with("slurm-") {
+                // SLURM keeps the log open across restarts, so it is copied and emptied
+                // in place instead of being moved.
+                fs::copy(entry.path(), archive_dir.join(&file_name))?;
+                fs::OpenOptions::new()
+                    .write(true)
+                    .truncate(true)
+                    .open(entry.path())?;
+                continue;
+            }
+            fs::rename(entry.path(), archive_dir.join(&file_name))?;
+        }
+        crate::log_info!(
+            "SLURM restarted this job (restart {}); archived the previous attempt to {}",
+            restart_count,
+            archive_dir.display()
+        );
+    }
+    fs::write(marker_path, restart_count.to_string())?;
+    Ok(())
+}
+
+/// Called once the attempt has its outcome, so a finished retry no longer shows as requeued.
+pub fn finish_attempt(repx_dir: &Path) {
+    let _ = fs::remove_file(repx_dir.join(REQUEUED_MARKER));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requeued_attempt_is_archived() {
+        let dir = tempfile::tempdir().unwrap();
+        let repx_dir = dir.path();
+        for name in ["FAIL", "stderr.log", "inputs.json", "slurm-42.out"] {
+            fs::write(repx_dir.join(name), "previous").unwrap();
+        }
+
+        prepare_attempt(repx_dir, 1).unwrap();
+        let archive_dir = repx_dir.join(ATTEMPTS_DIR).join("0");
+        assert!(archive_dir.join("FAIL").exists());
+        assert!(archive_dir.join("stderr.log").exists());
+        assert!(!repx_dir.join("FAIL").exists());
+        assert!(repx_dir.join("inputs.json").exists());
+        assert_eq!(
+            fs::read_to_string(archive_dir.join("slurm-42.out")).unwrap(),
+            "previous"
+        );
+        assert_eq!(
+            fs::read_to_string(repx_dir.join("slurm-42.out")).unwrap(),
+            ""
+        );
+        assert_eq!(
+            fs::read_to_string(repx_dir.join(REQUEUED_MARKER)).unwrap(),
+            "1"
+        );
+
+        finish_attempt(repx_dir);
+        assert!(!repx_dir.join(REQUEUED_MARKER).exists());
+
+        prepare_attempt(repx_dir, 2).unwrap();
+        assert!(repx_dir.join(REQUEUED_MARKER).exists());
+        prepare_attempt(repx_dir, 0).unwrap();
+        assert!(!repx_dir.join(REQUEUED_MARKER).exists());
+    }
+}
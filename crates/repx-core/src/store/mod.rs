@@ -1,3 +1,6 @@
 pub mod artifacts;
+pub mod attempts;
 pub mod outcomes;
+pub mod progress;
+pub mod status;
 pub mod timing;
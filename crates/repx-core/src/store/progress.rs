@@ -0,0 +1,124 @@
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const PROGRESS_FILE: &str = "progress.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WorkerProgress {
+    pub total: usize,
+    pub queued: usize,
+    pub running: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+impl WorkerProgress {
+    pub fn new(total: usize) -> Self {
+        Self {
+            total,
+            queued: total,
+            ..Default::default()
+        }
+    }
+
+    pub fn done(&self) -> usize {
+        self.succeeded + self.failed
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.done() >= self.total
+    }
+
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            return 1.0;
+        }
+        self.done() as f64 / self.total as f64
+    }
+
+    pub fn worker_started(&mut self) {
+        self.queued = self.queued.saturating_sub(1);
+        self.running += 1;
+    }
+
+    pub fn worker_finished(&mut self, success: bool) {
+        self.running = self.running.saturating_sub(1);
+        if success {
+            self.succeeded += 1;
+        } else {
+            self.failed += 1;
+        }
+    }
+
+    pub fn with_worker_markers(mut self, succeeded: usize, failed: usize) -> Self {
+        self.succeeded = self.succeeded.max(succeeded);
+        self.failed = self.failed.max(failed);
+        let remaining = self.total.saturating_sub(self.done());
+        self.running = self.running.min(remaining);
+        self.queued = remaining - self.running;
+        self
+    }
+}
+
+fn get_progress_path(repx_dir: &Path) -> PathBuf {
+    repx_dir.join(PROGRESS_FILE)
+}
+
+pub fn read_progress(repx_dir: &Path) -> Result<Option<WorkerProgress>, AppError> {
+    let path = get_progress_path(repx_dir);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&path)?;
+    Ok(Some(serde_json::from_str(&content)?))
+}
+
+pub fn write_progress(repx_dir: &Path, progress: &WorkerProgress) -> Result<(), AppError> {
+    let path = get_progress_path(repx_dir);
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, serde_json::to_string(progress)?)?;
+    fs::rename(tmp_path, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_worker_progress_transitions() {
+        let mut progress = WorkerProgress::new(3);
+        progress.worker_started();
+        progress.worker_started();
+        progress.worker_finished(true);
+        progress.worker_finished(false);
+        assert_eq!(
+            progress,
+            WorkerProgress {
+                total: 3,
+                queued: 1,
+                running: 0,
+                succeeded: 1,
+                failed: 1,
+            }
+        );
+        assert!(!progress.is_finished());
+        assert!((progress.fraction() - 2.0 / 3.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_worker_markers_override_stale_progress() {
+        let submitted = WorkerProgress::new(10);
+        let progress = submitted.with_worker_markers(6, 1);
+        assert_eq!(progress.succeeded, 6);
+        assert_eq!(progress.failed, 1);
+        assert_eq!(progress.running, 0);
+        assert_eq!(progress.queued, 3);
+
+        let finished = progress.with_worker_markers(8, 2);
+        assert!(finished.is_finished());
+        assert_eq!(finished.queued, 0);
+    }
+}
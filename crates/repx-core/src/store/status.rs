@@ -0,0 +1,273 @@
+use super::attempts::REQUEUED_MARKER;
+use super::progress::{self, WorkerProgress};
+use super::timing::{self, JobTimestamps};
+use crate::{engine, error::AppError, model::JobId};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutcomeMarker {
+    Success,
+    Partial,
+    Fail,
+    Requeued,
+}
+
+impl OutcomeMarker {
+    const PRECEDENCE: [(OutcomeMarker, &'static str); 4] = [
+        (OutcomeMarker::Partial, "PARTIAL"),
+        (OutcomeMarker::Success, "SUCCESS"),
+        (OutcomeMarker::Fail, "FAIL"),
+        (OutcomeMarker::Requeued, REQUEUED_MARKER),
+    ];
+
+    pub fn find(repx_dir: &Path) -> Option<Self> {
+        Self::PRECEDENCE
+            .iter()
+            .find(|(_, file_name)| repx_dir.join(file_name).exists())
+            .map(|(marker, _)| *marker)
+    }
+
+    pub fn from_file_name(file_name: &str) -> Option<Self> {
+        Self::PRECEDENCE
+            .iter()
+            .find(|(_, name)| *name == file_name)
+            .map(|(marker, _)| *marker)
+    }
+
+    /// Returns whichever of two markers present in the same job directory decides its
+    /// outcome, using the same precedence as [`OutcomeMarker::find`].
+    pub fn prevailing(self, other: Self) -> Self {
+        let rank = |marker: Self| {
+            Self::PRECEDENCE
+                .iter()
+                .position(|(candidate, _)| *candidate == marker)
+        };
+        if rank(other) < rank(self) {
+            other
+        } else {
+            self
+        }
+    }
+
+    pub fn job_status(self, location: String) -> engine::JobStatus {
+        match self {
+            OutcomeMarker::Success => engine::JobStatus::Succeeded { location },
+            OutcomeMarker::Partial => engine::JobStatus::PartiallySucceeded { location },
+            OutcomeMarker::Fail => engine::JobStatus::Failed { location },
+            OutcomeMarker::Requeued => engine::JobStatus::Requeued { location },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct JobReport {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<OutcomeMarker>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heartbeat: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "is_empty_timing")]
+    pub timing: JobTimestamps,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<WorkerProgress>,
+}
+
+fn is_empty_timing(timing: &JobTimestamps) -> bool {
+    timing.dispatched.is_none() && timing.started.is_none() && timing.finished.is_none()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct StatusReport {
+    pub jobs: BTreeMap<JobId, JobReport>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub submissions: Vec<SubmittedJob>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SubmittedJob {
+    pub job_id: JobId,
+    pub scheduler_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduler: Option<String>,
+}
+
+pub fn collect_status_report(
+    base_path: &Path,
+    job_ids: &[JobId],
+    submission_log: Option<&Path>,
+) -> Result<StatusReport, AppError> {
+    let outputs_dir = base_path.join("outputs");
+    let mut jobs = BTreeMap::new();
+    for job_id in job_ids {
+        let job_dir = outputs_dir.join(&job_id.0);
+        let repx_dir = job_dir.join("repx");
+        if !repx_dir.is_dir() {
+            continue;
+        }
+        let progress = progress::read_progress(&repx_dir)
+            .ok()
+            .flatten()
+            .map(|progress| {
+                let (succeeded, failed) = count_worker_markers(&job_dir);
+                progress.with_worker_markers(succeeded, failed)
+            });
+        jobs.insert(
+            job_id.clone(),
+            JobReport {
+                outcome: OutcomeMarker::find(&repx_dir),
+                heartbeat: last_modified(&repx_dir),
+                timing: timing::read_timestamps(&repx_dir).unwrap_or_default(),
+                progress,
+            },
+        );
+    }
+
+    let submissions = match submission_log {
+        Some(path) if path.exists() => parse_submission_log(&fs::read_to_string(path)?),
+        _ => Vec::new(),
+    };
+
+    Ok(StatusReport { jobs, submissions })
+}
+
+fn count_worker_markers(job_dir: &Path) -> (usize, usize) {
+    let Ok(entries) = fs::read_dir(job_dir) else {
+        return (0, 0);
+    };
+    let mut counts = (0, 0);
+    for entry in entries.flatten() {
+        if !entry.file_name().to_string_lossy().starts_with("worker-") {
+            continue;
+        }
+        let repx_dir = entry.path().join("repx");
+        if repx_dir.join("SUCCESS").exists() {
+            counts.0 += 1;
+        } else if repx_dir.join("FAIL").exists() {
+            counts.1 += 1;
+        }
+    }
+    counts
+}
+
+fn last_modified(dir: &Path) -> Option<DateTime<Utc>> {
+    fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| entry.metadata().ok()?.modified().ok())
+        .max()
+        .map(DateTime::<Utc>::from)
+}
+
+pub fn parse_submission_log(content: &str) -> Vec<SubmittedJob> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let job_id = JobId(parts.next()?.to_string());
+            let scheduler_id = parts.next()?.to_string();
+            let scheduler = parts.next().map(str::to_string);
+            Some(SubmittedJob {
+                job_id,
+                scheduler_id,
+                scheduler,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_report_covers_requested_jobs() {
+        let dir = tempfile::tempdir().unwrap();
+        let outputs = dir.path().join("outputs");
+        for (job, markers) in [
+            ("job-a", vec!["SUCCESS"]),
+            ("job-b", vec!["SUCCESS", "PARTIAL"]),
+            ("job-c", vec![]),
+            ("job-d", vec!["FAIL"]),
+        ] {
+            let repx_dir = outputs.join(job).join("repx");
+            fs::create_dir_all(&repx_dir).unwrap();
+            for marker in markers {
+                fs::write(repx_dir.join(marker), "").unwrap();
+            }
+        }
+        let job_c = outputs.join("job-c");
+        progress::write_progress(&job_c.join("repx"), &WorkerProgress::new(3)).unwrap();
+        for (worker, marker) in [("worker-0", "SUCCESS"), ("worker-1", "FAIL")] {
+            fs::create_dir_all(job_c.join(worker).join("repx")).unwrap();
+            fs::write(job_c.join(worker).join("repx").join(marker), "").unwrap();
+        }
+        timing::record_started(&job_c.join("repx")).unwrap();
+        let submission_log = dir.path().join("submitted.log");
+        fs::write(&submission_log, "job-c 4242\nmalformed\njob-d 4243 slurm\n").unwrap();
+
+        let job_ids: Vec<JobId> = ["job-a", "job-b", "job-c", "job-missing"]
+            .iter()
+            .map(|id| JobId(id.to_string()))
+            .collect();
+        let report = collect_status_report(dir.path(), &job_ids, Some(&submission_log)).unwrap();
+
+        assert_eq!(report.jobs.len(), 3);
+        let job = |id: &str| &report.jobs[&JobId(id.to_string())];
+        assert_eq!(job("job-a").outcome, Some(OutcomeMarker::Success));
+        assert_eq!(job("job-b").outcome, Some(OutcomeMarker::Partial));
+        assert_eq!(job("job-c").outcome, None);
+        assert!(job("job-c").heartbeat.is_some());
+        assert!(job("job-c").timing.started.is_some());
+        let progress = job("job-c").progress.unwrap();
+        assert_eq!(
+            (progress.succeeded, progress.failed, progress.queued),
+            (1, 1, 1)
+        );
+        assert_eq!(
+            report.submissions,
+            vec![
+                SubmittedJob {
+                    job_id: JobId("job-c".to_string()),
+                    scheduler_id: "4242".to_string(),
+                    scheduler: None,
+                },
+                SubmittedJob {
+                    job_id: JobId("job-d".to_string()),
+                    scheduler_id: "4243".to_string(),
+                    scheduler: Some("slurm".to_string()),
+                },
+            ]
+        );
+
+        let round_trip: StatusReport =
+            serde_json::from_str(&serde_json::to_string(&report).unwrap()).unwrap();
+        assert_eq!(round_trip, report);
+    }
+
+    #[test]
+    fn test_marker_precedence_matches_find() {
+        let dir = tempfile::tempdir().unwrap();
+        for (first, second) in [
+            ("SUCCESS", "PARTIAL"),
+            ("FAIL", "SUCCESS"),
+            ("REQUEUED", "FAIL"),
+            ("REQUEUED", "PARTIAL"),
+        ] {
+            let repx_dir = dir.path().join(format!("{}-{}", first, second));
+            fs::create_dir_all(&repx_dir).unwrap();
+            fs::write(repx_dir.join(first), "").unwrap();
+            fs::write(repx_dir.join(second), "").unwrap();
+
+            let first = OutcomeMarker::from_file_name(first).unwrap();
+            let second = OutcomeMarker::from_file_name(second).unwrap();
+            let found = OutcomeMarker::find(&repx_dir);
+            assert_eq!(Some(first.prevailing(second)), found);
+            assert_eq!(Some(second.prevailing(first)), found);
+        }
+        assert_eq!(OutcomeMarker::from_file_name("stdout.log"), None);
+    }
+}
@@ -6,7 +6,7 @@ use std::path::{Path, PathBuf};
 
 const TIMING_FILE: &str = "timing.json";
 
-#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
 pub struct JobTimestamps {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dispatched: Option<DateTime<Utc>>,
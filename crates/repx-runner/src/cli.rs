@@ -1,4 +1,5 @@
 use clap::{Args, Parser, Subcommand};
+use repx_core::config::{SubmitFailurePolicy, WorkerSuccessThreshold};
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -35,7 +36,7 @@ pub struct Cli {
     #[arg(
         long,
         global = true,
-        help = "The scheduler to use: 'slurm' or 'local'. Overrides the target's configuration."
+        help = "The scheduler to use: 'slurm', 'pbs', 'condor', 'ssh-pool' or 'local'. Overrides the target's configuration."
     )]
     pub scheduler: Option<String>,
 }
@@ -44,6 +45,8 @@ pub struct Cli {
 pub enum Commands {
     Run(RunArgs),
     Gc(GcArgs),
+    #[command(about = "Check the lab artifacts on a target against their local checksums.")]
+    Verify(VerifyArgs),
 
     #[command(hide = true)]
     InternalOrchestrate(InternalOrchestrateArgs),
@@ -52,11 +55,20 @@ pub enum Commands {
     InternalExecute(InternalExecuteArgs),
 
     #[command(hide = true)]
-    InternalScatterGather(InternalScatterGatherArgs),
+    InternalScatterGather(Box<InternalScatterGatherArgs>),
 
     #[command(hide = true)]
     InternalGc(InternalGcArgs),
 
+    #[command(hide = true)]
+    InternalStatus(InternalStatusArgs),
+
+    #[command(
+        hide = true,
+        about = "Serve JSON-RPC requests on stdin and stdout for a remote repx client."
+    )]
+    Agent,
+
     List(ListArgs),
 }
 
@@ -87,12 +99,34 @@ pub struct GcArgs {
     pub target: Option<String>,
 }
 
+#[derive(Args)]
+pub struct VerifyArgs {
+    #[arg(
+        long,
+        help = "Only report mismatching artifacts instead of syncing them again."
+    )]
+    pub no_resync: bool,
+}
+
 #[derive(Args)]
 pub struct InternalGcArgs {
     #[arg(long)]
     pub base_path: PathBuf,
 }
 
+#[derive(Args)]
+pub struct InternalStatusArgs {
+    #[arg(long)]
+    pub base_path: PathBuf,
+    #[arg(long, help = "File with one job ID per line to report on.")]
+    pub jobs_from: PathBuf,
+    #[arg(
+        long,
+        help = "Submission log whose entries are included in the report."
+    )]
+    pub submission_log: Option<PathBuf>,
+}
+
 #[derive(Args)]
 pub struct RunArgs {
     #[arg(value_name = "RUN_OR_JOB_ID")]
@@ -110,6 +144,21 @@ pub struct RunArgs {
 pub struct InternalOrchestrateArgs {
     #[arg(value_name = "PLAN_FILE")]
     pub plan_file: PathBuf,
+    #[arg(
+        long,
+        default_value_t = SubmitFailurePolicy::Cancel,
+        help = "What to do with already submitted jobs if a later sbatch fails ('cancel' or 'keep')."
+    )]
+    pub on_failure: SubmitFailurePolicy,
+    #[arg(
+        long,
+        help = "Maximum number of this user's jobs allowed in the SLURM queue before submission pauses."
+    )]
+    pub max_in_flight: Option<usize>,
+    #[arg(long, help = "Maximum number of sbatch calls per minute.")]
+    pub max_submissions_per_minute: Option<u32>,
+    #[arg(long, default_value_t = 30, hide = true)]
+    pub poll_interval_secs: u64,
 }
 
 #[derive(Args)]
@@ -169,6 +218,24 @@ pub struct InternalScatterGatherArgs {
     #[arg(long, default_value = "all")]
     pub phase: String,
 
+    #[arg(
+        long,
+        help = "Minimum number (e.g. 10) or fraction (e.g. 0.9) of workers that must succeed for the gather phase to run."
+    )]
+    pub min_worker_success: Option<WorkerSuccessThreshold>,
+
+    #[arg(
+        long,
+        help = "Maximum number of workers to run in parallel with the local scheduler. Defaults to the number of CPU cores."
+    )]
+    pub worker_concurrency: Option<usize>,
+
+    #[arg(
+        long,
+        help = "HTCondor submit description used for every worker node of the generated worker DAG."
+    )]
+    pub worker_submit_description: Option<PathBuf>,
+
     #[arg(long, default_value_t = false)]
     pub mount_host_paths: bool,
     #[arg(long)]
@@ -0,0 +1,148 @@
+use repx_client::agent::{
+    AgentCall, AgentRequest, AgentResponse, AGENT_PROTOCOL_VERSION, EXECUTION_ERROR,
+    INVALID_REQUEST, PARSE_ERROR,
+};
+use repx_core::error::AppError;
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use walkdir::WalkDir;
+
+pub fn handle_agent() -> Result<(), AppError> {
+    let stdin = io::stdin().lock();
+    let mut stdout = io::stdout().lock();
+    for line in stdin.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = handle_line(&line);
+        writeln!(stdout, "{}", serde_json::to_string(&response)?)?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
+
+fn handle_line(line: &str) -> AgentResponse {
+    let value: Value = match serde_json::from_str(line) {
+        Ok(value) => value,
+        Err(e) => return AgentResponse::failure(None, PARSE_ERROR, e.to_string()),
+    };
+    let id = value.get("id").and_then(Value::as_u64);
+    let request: AgentRequest = match serde_json::from_value(value) {
+        Ok(request) => request,
+        Err(e) => return AgentResponse::failure(id, INVALID_REQUEST, e.to_string()),
+    };
+    match dispatch(request.call) {
+        Ok(result) => AgentResponse::success(request.id, result),
+        Err(e) => AgentResponse::failure(Some(request.id), EXECUTION_ERROR, e.to_string()),
+    }
+}
+
+fn dispatch(call: AgentCall) -> Result<Value, AppError> {
+    match call {
+        AgentCall::Hello => Ok(json!({
+            "protocol": AGENT_PROTOCOL_VERSION,
+            "version": env!("CARGO_PKG_VERSION"),
+        })),
+        AgentCall::Status {
+            base_path,
+            jobs_from,
+            submission_log,
+        } => {
+            let report =
+                super::status::status_report(&base_path, &jobs_from, submission_log.as_deref())?;
+            Ok(serde_json::to_value(report)?)
+        }
+        AgentCall::TailLog { path, lines } => Ok(json!(tail_file(&path, lines)?)),
+        AgentCall::Cancel { scheduler, ids } => {
+            let command = match scheduler.as_str() {
+                "slurm" => "scancel",
+                "pbs" => "qdel",
+                other => {
+                    return Err(AppError::ConfigurationError(format!(
+                        "The agent cannot cancel jobs of scheduler '{}'.",
+                        other
+                    )))
+                }
+            };
+            let ids: Vec<String> = ids.iter().map(u32::to_string).collect();
+            run(Command::new(command).args(&ids), command)?;
+            Ok(Value::Null)
+        }
+        AgentCall::WriteFile { path, content } => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| AppError::PathIo {
+                    path: parent.to_path_buf(),
+                    source: e,
+                })?;
+            }
+            fs::write(&path, content).map_err(|e| AppError::PathIo { path, source: e })?;
+            Ok(Value::Null)
+        }
+        AgentCall::ListArtifacts { base_path } => Ok(json!(list_files(&base_path)?)),
+        AgentCall::SubmitPlan { plan_file, args } => {
+            let repx_binary = std::env::current_exe()?;
+            let stdout = run(
+                Command::new(repx_binary)
+                    .arg("internal-orchestrate")
+                    .args(&args)
+                    .arg(&plan_file),
+                "internal-orchestrate",
+            )?;
+            Ok(json!(stdout))
+        }
+    }
+}
+
+fn run(command: &mut Command, command_name: &str) -> Result<String, AppError> {
+    let output =
+        command
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| AppError::ProcessLaunchFailed {
+                command_name: command_name.to_string(),
+                source: e,
+            })?;
+    if !output.status.success() {
+        return Err(AppError::ExecutionFailed {
+            message: format!("'{}' exited with {}", command_name, output.status),
+            log_path: None,
+            log_summary: String::from_utf8_lossy(&output.stderr).to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn tail_file(path: &Path, line_count: u32) -> Result<Vec<String>, AppError> {
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read(path).map_err(|e| AppError::PathIo {
+        path: path.to_path_buf(),
+        source: e,
+    })?;
+    let content = String::from_utf8_lossy(&content);
+    let lines: Vec<&str> = content.lines().collect();
+    let start = lines.len().saturating_sub(line_count as usize);
+    Ok(lines[start..].iter().map(|line| line.to_string()).collect())
+}
+
+fn list_files(base_path: &Path) -> Result<Vec<PathBuf>, AppError> {
+    fs::create_dir_all(base_path).map_err(|e| AppError::PathIo {
+        path: base_path.to_path_buf(),
+        source: e,
+    })?;
+    let mut files = Vec::new();
+    for entry in WalkDir::new(base_path) {
+        let entry = entry?;
+        if entry.file_type().is_file() {
+            if let Ok(relative) = entry.path().strip_prefix(base_path) {
+                files.push(relative.to_path_buf());
+            }
+        }
+    }
+    Ok(files)
+}
@@ -1,5 +1,5 @@
 use crate::cli::InternalExecuteArgs;
-use repx_core::{error::AppError, log_debug, model::JobId};
+use repx_core::{error::AppError, log_debug, model::JobId, store::attempts};
 use repx_executor::{ExecutionRequest, Executor, Runtime};
 use std::fs;
 use tokio::runtime::Runtime as TokioRuntime;
@@ -19,6 +19,7 @@ async fn async_handle_execute(args: InternalExecuteArgs) -> Result<(), AppError>
     fs::create_dir_all(&user_out_dir)?;
     fs::create_dir_all(&repx_dir)?;
 
+    attempts::prepare_attempt(&repx_dir, attempts::slurm_restart_count())?;
     let _ = fs::remove_file(repx_dir.join("SUCCESS"));
     let _ = fs::remove_file(repx_dir.join("FAIL"));
 
@@ -88,6 +89,7 @@ async fn async_handle_execute(args: InternalExecuteArgs) -> Result<(), AppError>
     ];
 
     let result = executor.execute_script(&script_path, &exec_args).await;
+    attempts::finish_attempt(&repx_dir);
 
     match result {
         Ok(_) => {
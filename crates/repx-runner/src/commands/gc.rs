@@ -77,7 +77,7 @@ pub fn handle_internal_gc(args: InternalGcArgs) -> Result<(), AppError> {
                     }
                     let lab_root = canonical.clone();
 
-                    if let Ok(lab) = lab::load_from_path(&lab_root) {
+                    if let Ok(lab) = lab::load_metadata_from_path(&lab_root) {
                         for job_id in lab.jobs.keys() {
                             live_js.insert(job_id.0.clone());
                         }
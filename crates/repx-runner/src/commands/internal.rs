@@ -1,8 +1,305 @@
 use crate::cli::InternalOrchestrateArgs;
-use repx_client::orchestration::OrchestrationPlan;
-use repx_core::{error::AppError, model::JobId};
-use std::collections::{HashMap, HashSet};
+use repx_client::{
+    orchestration::{
+        OrchestrationPlan, SubmissionInfo, BUNDLE_JOB_PREFIX, BUNDLE_SKIP_MARKER,
+        SUBMISSION_INFO_FILE, SUBMISSION_LOG_FILE,
+    },
+    resources::{self, SbatchDirectives},
+};
+use repx_core::{
+    config::SubmitFailurePolicy,
+    error::AppError,
+    model::{DependencyType, JobId},
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const QUEUE_SNAPSHOT_MAX_AGE: Duration = Duration::from_secs(60);
+const SACCT_SETTLE_TIMEOUT: Duration = Duration::from_secs(600);
+const SQUEUE_RETRY_DELAYS: [Duration; 3] = [
+    Duration::from_secs(5),
+    Duration::from_secs(15),
+    Duration::from_secs(45),
+];
+
+struct SubmissionRecord {
+    log_path: PathBuf,
+    log_file: File,
+    slurm_ids: Vec<u32>,
+    scatter_job_ids: Vec<JobId>,
+}
+
+impl SubmissionRecord {
+    fn create(submissions_dir: &Path) -> Result<Self, AppError> {
+        let log_path = submissions_dir.join(SUBMISSION_LOG_FILE);
+        let log_file = File::create(&log_path).map_err(|e| AppError::PathIo {
+            path: log_path.clone(),
+            source: e,
+        })?;
+        Ok(Self {
+            log_path,
+            log_file,
+            slurm_ids: Vec::new(),
+            scatter_job_ids: Vec::new(),
+        })
+    }
+
+    fn track(&mut self, slurm_id: u32) {
+        self.slurm_ids.push(slurm_id);
+    }
+
+    fn record(&mut self, job_id: &JobId, slurm_id: u32) -> Result<(), AppError> {
+        println!("{} {} slurm", job_id, slurm_id);
+        writeln!(self.log_file, "{} {} slurm", job_id, slurm_id)
+            .and_then(|_| self.log_file.flush())
+            .map_err(|e| AppError::PathIo {
+                path: self.log_path.clone(),
+                source: e,
+            })
+    }
+
+    fn rollback(&mut self) {
+        if self.slurm_ids.is_empty() {
+            return;
+        }
+        eprintln!(
+            "[REPX-ORCH] Cancelling {} SLURM jobs submitted before the failure...",
+            self.slurm_ids.len()
+        );
+        let ids: Vec<String> = self.slurm_ids.iter().map(|id| id.to_string()).collect();
+        if !scancel(&ids) {
+            return;
+        }
+        // Scatter-gather jobs that already started have queued workers of their own.
+        let worker_ids = self.scatter_worker_ids();
+        if !worker_ids.is_empty() {
+            eprintln!(
+                "[REPX-ORCH] Cancelling {} scatter-gather workers...",
+                worker_ids.len()
+            );
+            if !scancel(&worker_ids) {
+                return;
+            }
+        }
+        let _ = self.log_file.set_len(0);
+    }
+
+    fn scatter_worker_ids(&self) -> Vec<String> {
+        if self.scatter_job_ids.is_empty() {
+            return Vec::new();
+        }
+        let user = std::env::var("USER").unwrap_or_default();
+        let mut squeue_cmd = Command::new("squeue");
+        squeue_cmd.arg("-h").arg("-o").arg("%i %j");
+        if !user.is_empty() {
+            squeue_cmd.arg("-u").arg(&user);
+        }
+        let output = match squeue_cmd.output() {
+            Ok(output) if output.status.success() => output,
+            _ => {
+                eprintln!(
+                    "[REPX-ORCH] Could not list scatter-gather workers; they may still be queued."
+                );
+                return Vec::new();
+            }
+        };
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.trim().split_once(' '))
+            .filter(|(_, name)| {
+                self.scatter_job_ids
+                    .iter()
+                    .any(|job_id| is_scatter_worker_name(name, job_id))
+            })
+            .map(|(id, _)| id.to_string())
+            .collect()
+    }
+}
+
+fn is_scatter_worker_name(name: &str, job_id: &JobId) -> bool {
+    name.strip_prefix(job_id.0.as_str())
+        .and_then(|rest| rest.strip_prefix('-'))
+        .is_some_and(|suffix| {
+            suffix == "gather"
+                || suffix
+                    .strip_prefix('w')
+                    .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+        })
+}
+
+fn scancel(ids: &[String]) -> bool {
+    match Command::new("scancel").args(ids).output() {
+        Ok(output) if output.status.success() => true,
+        Ok(output) => {
+            eprintln!(
+                "[REPX-ORCH] scancel failed, jobs may still be queued: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            false
+        }
+        Err(e) => {
+            eprintln!(
+                "[REPX-ORCH] Could not run scancel, jobs may still be queued: {}",
+                e
+            );
+            false
+        }
+    }
+}
+
+struct SubmitThrottle {
+    max_in_flight: Option<usize>,
+    min_interval: Option<Duration>,
+    poll_interval: Duration,
+    last_submit: Option<Instant>,
+    queued_ids: HashSet<u32>,
+    submitted_since_snapshot: HashSet<u32>,
+    snapshot_taken: Option<Instant>,
+    watch_queue: bool,
+}
+
+impl SubmitThrottle {
+    fn new(
+        max_in_flight: Option<usize>,
+        max_submissions_per_minute: Option<u32>,
+        poll_interval: Duration,
+        watch_queue: bool,
+    ) -> Self {
+        Self {
+            max_in_flight: max_in_flight.map(|n| n.max(1)),
+            min_interval: max_submissions_per_minute
+                .filter(|&n| n > 0)
+                .map(|n| Duration::from_secs_f64(60.0 / n as f64)),
+            poll_interval,
+            last_submit: None,
+            queued_ids: HashSet::new(),
+            submitted_since_snapshot: HashSet::new(),
+            snapshot_taken: None,
+            watch_queue,
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        self.max_in_flight.is_some() || self.min_interval.is_some() || self.watch_queue
+    }
+
+    fn refresh(&mut self) {
+        let mut delays = SQUEUE_RETRY_DELAYS.iter();
+        loop {
+            match queued_slurm_ids() {
+                Ok(ids) => {
+                    self.queued_ids = ids;
+                    self.submitted_since_snapshot.clear();
+                    self.snapshot_taken = Some(Instant::now());
+                    return;
+                }
+                Err(e) => match delays.next() {
+                    Some(delay) => {
+                        eprintln!(
+                            "[REPX-ORCH] squeue failed, retrying in {}s: {}",
+                            delay.as_secs(),
+                            e
+                        );
+                        thread::sleep(*delay);
+                    }
+                    None => {
+                        eprintln!(
+                            "[REPX-ORCH] squeue keeps failing, continuing with the last known queue: {}",
+                            e
+                        );
+                        // Jobs submitted since the last snapshot stay counted as queued, so
+                        // the stale view can only delay submissions, never release them early.
+                        self.snapshot_taken = Some(Instant::now());
+                        return;
+                    }
+                },
+            }
+        }
+    }
+
+    fn refresh_if_stale(&mut self) {
+        if self
+            .snapshot_taken
+            .is_none_or(|taken| taken.elapsed() >= QUEUE_SNAPSHOT_MAX_AGE)
+        {
+            self.refresh();
+        }
+    }
+
+    fn in_flight(&self) -> usize {
+        self.queued_ids.len() + self.submitted_since_snapshot.len()
+    }
+
+    fn is_queued(&self, slurm_id: u32) -> bool {
+        self.queued_ids.contains(&slurm_id) || self.submitted_since_snapshot.contains(&slurm_id)
+    }
+
+    fn wait_for_slot(&mut self) {
+        if let Some(max_in_flight) = self.max_in_flight {
+            self.refresh_if_stale();
+            let mut announced = false;
+            while self.in_flight() >= max_in_flight {
+                self.refresh();
+                if self.in_flight() < max_in_flight {
+                    break;
+                }
+                if !announced {
+                    eprintln!(
+                        "[REPX-ORCH] {} jobs in the queue (limit {}), waiting for some to finish...",
+                        self.in_flight(),
+                        max_in_flight
+                    );
+                    announced = true;
+                }
+                thread::sleep(self.poll_interval);
+            }
+        }
+        if let (Some(interval), Some(last)) = (self.min_interval, self.last_submit) {
+            let elapsed = last.elapsed();
+            if elapsed < interval {
+                thread::sleep(interval - elapsed);
+            }
+        }
+        self.last_submit = Some(Instant::now());
+    }
+
+    fn submitted(&mut self, slurm_id: u32) {
+        if self.is_active() {
+            self.submitted_since_snapshot.insert(slurm_id);
+        }
+    }
+}
+
+fn queued_slurm_ids() -> Result<HashSet<u32>, AppError> {
+    let user = std::env::var("USER").unwrap_or_default();
+    let mut squeue_cmd = Command::new("squeue");
+    squeue_cmd.arg("-h").arg("-o").arg("%i");
+    if !user.is_empty() {
+        squeue_cmd.arg("-u").arg(&user);
+    }
+    let output = squeue_cmd
+        .output()
+        .map_err(|e| AppError::ProcessLaunchFailed {
+            command_name: "squeue".to_string(),
+            source: e,
+        })?;
+    if !output.status.success() {
+        return Err(AppError::ExecutionFailed {
+            message: "squeue failed while throttling submission".to_string(),
+            log_path: None,
+            log_summary: String::from_utf8_lossy(&output.stderr).to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.trim().parse::<u32>().ok())
+        .collect())
+}
 
 pub fn handle_internal_orchestrate(args: InternalOrchestrateArgs) -> Result<(), AppError> {
     let plan_content = std::fs::read_to_string(&args.plan_file).map_err(|e| AppError::PathIo {
@@ -11,19 +308,59 @@ pub fn handle_internal_orchestrate(args: InternalOrchestrateArgs) -> Result<(),
     })?;
     let plan: OrchestrationPlan = serde_json::from_str(&plan_content)?;
 
+    let mut record = SubmissionRecord::create(&plan.submissions_dir)?;
+    let mut throttle = SubmitThrottle::new(
+        args.max_in_flight,
+        args.max_submissions_per_minute,
+        Duration::from_secs(args.poll_interval_secs),
+        plan.needs_supervision(),
+    );
+    if let Err(e) = submit_plan(&plan, &mut record, &mut throttle) {
+        eprintln!("[REPX-ORCH] Submission failed: {}", e);
+        // A throttled or supervising orchestrator runs for hours in the background, long after
+        // the first jobs have started; cancelling them then would throw away finished work.
+        match args.on_failure {
+            SubmitFailurePolicy::Cancel if !throttle.is_active() => record.rollback(),
+            _ => eprintln!(
+                "[REPX-ORCH] Keeping {} already submitted SLURM jobs (see {}).",
+                record.slurm_ids.len(),
+                record.log_path.display()
+            ),
+        }
+        return Err(e);
+    }
+
+    eprintln!("[REPX-ORCH] All jobs submitted successfully.");
+    Ok(())
+}
+
+fn submit_plan(
+    plan: &OrchestrationPlan,
+    record: &mut SubmissionRecord,
+    throttle: &mut SubmitThrottle,
+) -> Result<(), AppError> {
     let mut submitted_slurm_ids: HashMap<JobId, u32> = HashMap::new();
+    let mut skipped_jobs: HashSet<JobId> = HashSet::new();
+    let mut supervised: HashMap<JobId, SupervisedJob> = HashMap::new();
+    let mut failed_jobs: Vec<JobId> = Vec::new();
+    let mut bundle_sizes: HashMap<&str, usize> = HashMap::new();
+    let depended_on: HashSet<&JobId> = plan
+        .jobs
+        .values()
+        .flat_map(|job_plan| &job_plan.dependencies)
+        .collect();
     let mut jobs_left: HashSet<JobId> = plan.jobs.keys().cloned().collect();
     let mut wave_num = 0;
 
-    while !jobs_left.is_empty() {
+    while !jobs_left.is_empty() || !supervised.is_empty() {
         let mut current_wave: Vec<JobId> = Vec::new();
 
         for job_id in &jobs_left {
             let job_plan = plan.jobs.get(job_id).unwrap();
-            let all_deps_met = job_plan
-                .dependencies
-                .iter()
-                .all(|dep_id| submitted_slurm_ids.contains_key(dep_id));
+            let all_deps_met = job_plan.dependencies.iter().all(|dep_id| {
+                (submitted_slurm_ids.contains_key(dep_id) && !supervised.contains_key(dep_id))
+                    || skipped_jobs.contains(dep_id)
+            });
             if all_deps_met {
                 current_wave.push(job_id.clone());
             }
@@ -31,9 +368,20 @@ pub fn handle_internal_orchestrate(args: InternalOrchestrateArgs) -> Result<(),
         current_wave.sort();
 
         if current_wave.is_empty() {
-            return Err(AppError::ConfigurationError(
-                "Cycle detected in job dependency graph.".to_string(),
-            ));
+            if supervised.is_empty() {
+                return Err(AppError::ConfigurationError(
+                    "Cycle detected in job dependency graph.".to_string(),
+                ));
+            }
+            supervise(
+                plan,
+                &mut supervised,
+                &mut submitted_slurm_ids,
+                &mut failed_jobs,
+                record,
+                throttle,
+            )?;
+            continue;
         }
 
         eprintln!(
@@ -42,112 +390,621 @@ pub fn handle_internal_orchestrate(args: InternalOrchestrateArgs) -> Result<(),
             current_wave.len()
         );
 
+        if throttle.is_active() {
+            throttle.refresh_if_stale();
+        }
+
+        let mut single_jobs = Vec::new();
+        let mut bundled_jobs: BTreeMap<&str, Vec<JobId>> = BTreeMap::new();
         for job_id in current_wave {
             jobs_left.remove(&job_id);
-            let job_plan = plan.jobs.get(&job_id).unwrap();
-            let script_path = plan
-                .submissions_dir
-                .join(format!("{}.sbatch", job_plan.script_hash));
-
-            let dep_ids: Vec<String> = job_plan
-                .dependencies
-                .iter()
-                .filter_map(|dep_id| submitted_slurm_ids.get(dep_id))
-                .map(|id| id.to_string())
-                .collect();
-
-            let mut anchor_id = None;
-
-            if job_plan.job_type == "scatter-gather" {
-                let mut anchor_cmd = Command::new("sbatch");
-                anchor_cmd
-                    .arg("--parsable")
-                    .arg("--hold")
-                    .arg(format!("--job-name=anchor-{}", job_id.0))
-                    .arg("--time=00:01:00")
-                    .arg("--output=/dev/null")
-                    .arg("--error=/dev/null")
-                    .arg("--wrap=exit 0");
-
-                let anchor_out =
-                    anchor_cmd
-                        .output()
-                        .map_err(|e| AppError::ProcessLaunchFailed {
-                            command_name: "sbatch (anchor)".to_string(),
-                            source: e,
-                        })?;
-
-                if !anchor_out.status.success() {
-                    let stderr = String::from_utf8_lossy(&anchor_out.stderr);
-                    return Err(AppError::ExecutionFailed {
-                        message: format!("Failed to submit anchor for job '{}'", job_id),
-                        log_path: None,
-                        log_summary: stderr.to_string(),
-                    });
+            if let Some(dep_id) = unsatisfiable_dependency(
+                plan,
+                &job_id,
+                &submitted_slurm_ids,
+                &skipped_jobs,
+                throttle,
+            ) {
+                eprintln!(
+                    "[REPX-ORCH] Not submitting '{}': dependency '{}' did not succeed.",
+                    job_id, dep_id
+                );
+                skipped_jobs.insert(job_id);
+                continue;
+            }
+            match plan.jobs[&job_id].bundle.as_deref() {
+                Some(bundle_key) if plan.bundles.contains_key(bundle_key) => {
+                    bundled_jobs.entry(bundle_key).or_default().push(job_id)
                 }
-                let aid_str = String::from_utf8_lossy(&anchor_out.stdout)
-                    .trim()
-                    .to_string();
-                let aid = aid_str
-                    .parse::<u32>()
-                    .map_err(|_| AppError::ExecutionFailed {
-                        message: format!("Failed to parse Anchor ID for job '{}'", job_id),
-                        log_path: None,
-                        log_summary: aid_str.clone(),
-                    })?;
-                anchor_id = Some(aid);
+                _ => single_jobs.push(job_id),
             }
+        }
 
-            let mut sbatch_cmd = Command::new("sbatch");
-            sbatch_cmd.arg("--parsable");
-
-            if !dep_ids.is_empty() {
-                sbatch_cmd.arg(format!("--dependency=afterok:{}", dep_ids.join(":")));
-                sbatch_cmd.arg("--kill-on-invalid-dep=yes");
+        for (bundle_key, members) in bundled_jobs {
+            let bundle_size = *bundle_sizes.entry(bundle_key).or_insert_with(|| {
+                let bundle = &plan.bundles[bundle_key];
+                resources::bundle_size(
+                    bundle.directives.time.as_deref(),
+                    bundle.max_jobs,
+                    partition_time_limit(bundle.directives.partition.as_deref()),
+                )
+            });
+            for (chunk_idx, chunk) in members.chunks(bundle_size).enumerate() {
+                if chunk.len() == 1 {
+                    single_jobs.push(chunk[0].clone());
+                    continue;
+                }
+                let bundle_name = format!(
+                    "{}{}-{}-{}",
+                    BUNDLE_JOB_PREFIX,
+                    &bundle_key[..bundle_key.len().min(12)],
+                    wave_num,
+                    chunk_idx
+                );
+                throttle.wait_for_slot();
+                let result = submit_bundle(
+                    plan,
+                    bundle_key,
+                    &bundle_name,
+                    chunk,
+                    &submitted_slurm_ids,
+                    throttle,
+                );
+                let bundle_id = match result {
+                    Ok(bundle_id) => bundle_id,
+                    Err(e) if throttle.is_active() => {
+                        eprintln!(
+                            "[REPX-ORCH] Could not submit {}, skipping its jobs: {}",
+                            bundle_name, e
+                        );
+                        skipped_jobs.extend(chunk.iter().cloned());
+                        failed_jobs.extend(chunk.iter().cloned());
+                        continue;
+                    }
+                    Err(e) => return Err(e),
+                };
+                record.track(bundle_id);
+                throttle.submitted(bundle_id);
+                for job_id in chunk {
+                    submitted_slurm_ids.insert(job_id.clone(), bundle_id);
+                    record.record(job_id, bundle_id)?;
+                }
+                let directives = bundle_directives(plan, bundle_key, chunk.len());
+                for job_id in chunk {
+                    write_submission_info(plan, job_id, bundle_id, 0, &directives);
+                    if depended_on.contains(job_id) {
+                        supervised.insert(
+                            job_id.clone(),
+                            SupervisedJob {
+                                slurm_id: bundle_id,
+                                attempt: 0,
+                                directives: directives.clone(),
+                                left_queue_at: None,
+                            },
+                        );
+                    }
+                }
             }
+        }
 
-            if let Some(aid) = anchor_id {
-                sbatch_cmd.arg(format!("--export=ALL,REPX_ANCHOR_ID={}", aid));
-            }
-
-            sbatch_cmd.arg(&script_path);
-
-            let output = sbatch_cmd
-                .output()
-                .map_err(|e| AppError::ProcessLaunchFailed {
-                    command_name: "sbatch".to_string(),
-                    source: e,
-                })?;
-
-            if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                return Err(AppError::ExecutionFailed {
-                    message: format!("sbatch command failed for job '{}'", job_id),
-                    log_path: Some(script_path),
-                    log_summary: stderr.to_string(),
-                });
-            }
-
-            let slurm_id_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            let slurm_id = slurm_id_str
-                .parse::<u32>()
-                .map_err(|_| AppError::ExecutionFailed {
-                    message: format!(
-                        "Failed to parse SLURM ID from sbatch output for job '{}'",
-                        job_id
-                    ),
-                    log_path: Some(script_path),
-                    log_summary: format!("sbatch output was: '{}'", slurm_id_str),
-                })?;
-
-            let track_id = anchor_id.unwrap_or(slurm_id);
+        single_jobs.sort();
+        for job_id in single_jobs {
+            throttle.wait_for_slot();
+            let track_id = match submit_job(plan, &job_id, &submitted_slurm_ids, record, throttle) {
+                Ok(track_id) => track_id,
+                Err(e) if throttle.is_active() => {
+                    eprintln!(
+                        "[REPX-ORCH] Could not submit '{}', skipping it: {}",
+                        job_id, e
+                    );
+                    skipped_jobs.insert(job_id.clone());
+                    failed_jobs.push(job_id);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
             submitted_slurm_ids.insert(job_id.clone(), track_id);
-
-            println!("{} {}", job_id, track_id);
+            record.record(&job_id, track_id)?;
+            let job_plan = &plan.jobs[&job_id];
+            write_submission_info(plan, &job_id, track_id, 0, &job_plan.directives);
+            if job_plan.on_timeout.is_some() {
+                supervised.insert(
+                    job_id,
+                    SupervisedJob {
+                        slurm_id: track_id,
+                        attempt: 0,
+                        directives: job_plan.directives.clone(),
+                        left_queue_at: None,
+                    },
+                );
+            }
         }
         wave_num += 1;
     }
+    if !failed_jobs.is_empty() {
+        failed_jobs.sort();
+        return Err(AppError::ExecutionFailed {
+            message: format!("{} jobs could not be submitted", failed_jobs.len()),
+            log_path: Some(record.log_path.clone()),
+            log_summary: failed_jobs
+                .iter()
+                .map(|job_id| job_id.to_string())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        });
+    }
+    Ok(())
+}
 
-    eprintln!("[REPX-ORCH] All jobs submitted successfully.");
+struct SupervisedJob {
+    slurm_id: u32,
+    attempt: u32,
+    directives: SbatchDirectives,
+    left_queue_at: Option<Instant>,
+}
+
+fn supervise(
+    plan: &OrchestrationPlan,
+    supervised: &mut HashMap<JobId, SupervisedJob>,
+    submitted_slurm_ids: &mut HashMap<JobId, u32>,
+    failed_jobs: &mut Vec<JobId>,
+    record: &mut SubmissionRecord,
+    throttle: &mut SubmitThrottle,
+) -> Result<(), AppError> {
+    thread::sleep(throttle.poll_interval);
+    throttle.refresh();
+
+    let mut finished: Vec<JobId> = supervised
+        .iter()
+        .filter(|(_, job)| !throttle.is_queued(job.slurm_id))
+        .map(|(job_id, _)| job_id.clone())
+        .collect();
+    finished.sort();
+
+    for job_id in finished {
+        if plan
+            .outputs_dir
+            .join(&job_id.0)
+            .join("repx")
+            .join("SUCCESS")
+            .exists()
+        {
+            supervised.remove(&job_id);
+            continue;
+        }
+        let Some(policy) = plan.jobs[&job_id].on_timeout.as_ref() else {
+            // Bundled jobs: the bundle has finished, so each job's markers are final.
+            supervised.remove(&job_id);
+            continue;
+        };
+        let job = supervised.get_mut(&job_id).unwrap();
+        let state = slurm_final_state(job.slurm_id);
+        if !state.as_deref().is_some_and(is_terminal_state) {
+            // sacct lags behind squeue; dependents stay held until the job's outcome is known.
+            let left_queue_at = *job.left_queue_at.get_or_insert_with(Instant::now);
+            if left_queue_at.elapsed() < SACCT_SETTLE_TIMEOUT {
+                continue;
+            }
+            eprintln!(
+                "[REPX-ORCH] sacct reported no final state for '{}' (SLURM job {}) within {}s; no longer supervising it.",
+                job_id,
+                job.slurm_id,
+                SACCT_SETTLE_TIMEOUT.as_secs()
+            );
+            supervised.remove(&job_id);
+            continue;
+        }
+        let job = supervised.remove(&job_id).unwrap();
+        if state.as_deref() != Some("TIMEOUT") {
+            continue;
+        }
+
+        let escalated_time = job
+            .directives
+            .time
+            .as_deref()
+            .and_then(|time| resources::escalate_walltime(time, policy));
+        let Some(escalated_time) = escalated_time.filter(|_| job.attempt < policy.retries) else {
+            eprintln!(
+                "[REPX-ORCH] '{}' hit its time limit again; giving up after {} retries.",
+                job_id, job.attempt
+            );
+            continue;
+        };
+
+        let attempt = job.attempt + 1;
+        eprintln!(
+            "[REPX-ORCH] '{}' hit its time limit; resubmitting with --time={} (retry {} of {}).",
+            job_id, escalated_time, attempt, policy.retries
+        );
+        let mut directives = job.directives;
+        directives.time = Some(escalated_time);
+
+        throttle.wait_for_slot();
+        let script_path = plan
+            .submissions_dir
+            .join(format!("{}.sbatch", plan.jobs[&job_id].script_hash));
+        let mut sbatch_cmd = Command::new("sbatch");
+        sbatch_cmd
+            .arg("--parsable")
+            .arg(format!("--time={}", directives.time.as_deref().unwrap()))
+            .arg(&script_path);
+        let slurm_id = match run_sbatch(&mut sbatch_cmd, &format!("job '{}'", job_id), &script_path)
+        {
+            Ok(slurm_id) => slurm_id,
+            Err(e) => {
+                eprintln!("[REPX-ORCH] Could not resubmit '{}': {}", job_id, e);
+                failed_jobs.push(job_id);
+                continue;
+            }
+        };
+        record.track(slurm_id);
+        throttle.submitted(slurm_id);
+        submitted_slurm_ids.insert(job_id.clone(), slurm_id);
+        record.record(&job_id, slurm_id)?;
+        write_submission_info(plan, &job_id, slurm_id, attempt, &directives);
+        supervised.insert(
+            job_id,
+            SupervisedJob {
+                slurm_id,
+                attempt,
+                directives,
+                left_queue_at: None,
+            },
+        );
+    }
     Ok(())
 }
+
+fn slurm_final_state(slurm_id: u32) -> Option<String> {
+    let output = Command::new("sacct")
+        .args(["-n", "-X", "-P", "-o", "State", "-j"])
+        .arg(slurm_id.to_string())
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().next())
+        .map(str::to_string)
+}
+
+fn is_terminal_state(state: &str) -> bool {
+    !matches!(
+        state,
+        "PENDING"
+            | "CONFIGURING"
+            | "RUNNING"
+            | "COMPLETING"
+            | "REQUEUED"
+            | "REQUEUE_HOLD"
+            | "REQUEUE_FED"
+            | "RESIZING"
+            | "SUSPENDED"
+            | "STOPPED"
+            | "SIGNALING"
+            | "STAGE_OUT"
+    )
+}
+
+fn write_submission_info(
+    plan: &OrchestrationPlan,
+    job_id: &JobId,
+    slurm_id: u32,
+    attempt: u32,
+    directives: &SbatchDirectives,
+) {
+    let repx_dir = plan.outputs_dir.join(&job_id.0).join("repx");
+    let info = SubmissionInfo {
+        slurm_id,
+        attempt,
+        directives: directives.clone(),
+    };
+    let result = std::fs::create_dir_all(&repx_dir).and_then(|_| {
+        let content = serde_json::to_string_pretty(&info).map_err(std::io::Error::other)?;
+        std::fs::write(repx_dir.join(SUBMISSION_INFO_FILE), content)
+    });
+    if let Err(e) = result {
+        eprintln!(
+            "[REPX-ORCH] Could not record submission of '{}': {}",
+            job_id, e
+        );
+    }
+}
+
+fn bundle_directives(plan: &OrchestrationPlan, bundle_key: &str, size: usize) -> SbatchDirectives {
+    let mut directives = plan.bundles[bundle_key].directives.clone();
+    if let Some(time) = &directives.time {
+        if let Some(total_time) = resources::multiply_walltime(time, size as f64) {
+            directives.time = Some(total_time);
+        }
+    }
+    directives
+}
+
+fn partition_time_limit(partition: Option<&str>) -> Option<u64> {
+    let output = Command::new("sinfo")
+        .args(["-h", "-o", "%P %l"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .find(|(name, _)| match partition {
+            Some(partition) => name.trim_end_matches('*') == partition,
+            None => name.ends_with('*'),
+        })
+        .and_then(|(_, limit)| resources::parse_walltime(limit.trim()))
+}
+
+fn unsatisfiable_dependency(
+    plan: &OrchestrationPlan,
+    job_id: &JobId,
+    submitted_slurm_ids: &HashMap<JobId, u32>,
+    skipped_jobs: &HashSet<JobId>,
+    throttle: &SubmitThrottle,
+) -> Option<JobId> {
+    let job_plan = &plan.jobs[job_id];
+    job_plan
+        .dependencies
+        .iter()
+        .filter(|dep_id| job_plan.dependency_type(dep_id).requires_success())
+        .find(|dep_id| {
+            if skipped_jobs.contains(*dep_id) {
+                return true;
+            }
+            let left_queue = throttle.is_active()
+                && submitted_slurm_ids
+                    .get(*dep_id)
+                    .is_some_and(|&id| !throttle.is_queued(id));
+            left_queue
+                && !plan
+                    .outputs_dir
+                    .join(&dep_id.0)
+                    .join("repx")
+                    .join("SUCCESS")
+                    .exists()
+        })
+        .cloned()
+}
+
+fn dependency_spec<'a>(
+    plan: &OrchestrationPlan,
+    job_ids: impl IntoIterator<Item = &'a JobId>,
+    submitted_slurm_ids: &HashMap<JobId, u32>,
+    throttle: &SubmitThrottle,
+) -> Option<String> {
+    let mut dep_ids: BTreeMap<DependencyType, BTreeSet<u32>> = BTreeMap::new();
+    for job_id in job_ids {
+        let job_plan = &plan.jobs[job_id];
+        for dep_id in &job_plan.dependencies {
+            let Some(&slurm_id) = submitted_slurm_ids.get(dep_id) else {
+                continue;
+            };
+            if throttle.is_active() && !throttle.is_queued(slurm_id) {
+                continue;
+            }
+            dep_ids
+                .entry(job_plan.dependency_type(dep_id))
+                .or_default()
+                .insert(slurm_id);
+        }
+    }
+    if dep_ids.is_empty() {
+        return None;
+    }
+    let spec = dep_ids
+        .iter()
+        .map(|(dependency_type, ids)| {
+            let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+            format!("{}:{}", dependency_type, ids.join(":"))
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    Some(spec)
+}
+
+fn submit_job(
+    plan: &OrchestrationPlan,
+    job_id: &JobId,
+    submitted_slurm_ids: &HashMap<JobId, u32>,
+    record: &mut SubmissionRecord,
+    throttle: &mut SubmitThrottle,
+) -> Result<u32, AppError> {
+    let job_plan = plan.jobs.get(job_id).unwrap();
+    let script_path = plan
+        .submissions_dir
+        .join(format!("{}.sbatch", job_plan.script_hash));
+
+    let dependency = dependency_spec(plan, [job_id], submitted_slurm_ids, throttle);
+
+    let mut anchor_id = None;
+
+    if job_plan.job_type == "scatter-gather" {
+        let mut anchor_cmd = Command::new("sbatch");
+        anchor_cmd
+            .arg("--parsable")
+            .arg("--hold")
+            .arg(format!("--job-name=anchor-{}", job_id.0))
+            .arg("--time=00:01:00")
+            .arg("--output=/dev/null")
+            .arg("--error=/dev/null")
+            .arg("--wrap=exit 0");
+
+        let anchor_out = anchor_cmd
+            .output()
+            .map_err(|e| AppError::ProcessLaunchFailed {
+                command_name: "sbatch (anchor)".to_string(),
+                source: e,
+            })?;
+
+        if !anchor_out.status.success() {
+            let stderr = String::from_utf8_lossy(&anchor_out.stderr);
+            return Err(AppError::ExecutionFailed {
+                message: format!("Failed to submit anchor for job '{}'", job_id),
+                log_path: None,
+                log_summary: stderr.to_string(),
+            });
+        }
+        let aid_str = String::from_utf8_lossy(&anchor_out.stdout)
+            .trim()
+            .to_string();
+        let aid = aid_str
+            .parse::<u32>()
+            .map_err(|_| AppError::ExecutionFailed {
+                message: format!("Failed to parse Anchor ID for job '{}'", job_id),
+                log_path: None,
+                log_summary: aid_str.clone(),
+            })?;
+        anchor_id = Some(aid);
+    }
+
+    let mut sbatch_cmd = Command::new("sbatch");
+    sbatch_cmd.arg("--parsable");
+
+    if let Some(dependency) = &dependency {
+        sbatch_cmd.arg(format!("--dependency={}", dependency));
+        sbatch_cmd.arg("--kill-on-invalid-dep=yes");
+    }
+
+    if let Some(aid) = anchor_id {
+        sbatch_cmd.arg(format!("--export=ALL,REPX_ANCHOR_ID={}", aid));
+    }
+
+    sbatch_cmd.arg(&script_path);
+
+    let slurm_id = match run_sbatch(&mut sbatch_cmd, &format!("job '{}'", job_id), &script_path) {
+        Ok(id) => id,
+        Err(e) => {
+            if let Some(aid) = anchor_id {
+                let _ = Command::new("scancel").arg(aid.to_string()).output();
+            }
+            return Err(e);
+        }
+    };
+    record.track(slurm_id);
+    throttle.submitted(slurm_id);
+    if let Some(aid) = anchor_id {
+        record.track(aid);
+        record.scatter_job_ids.push(job_id.clone());
+        throttle.submitted(aid);
+    }
+    Ok(anchor_id.unwrap_or(slurm_id))
+}
+
+fn submit_bundle(
+    plan: &OrchestrationPlan,
+    bundle_key: &str,
+    bundle_name: &str,
+    members: &[JobId],
+    submitted_slurm_ids: &HashMap<JobId, u32>,
+    throttle: &SubmitThrottle,
+) -> Result<u32, AppError> {
+    let bundles_dir = plan.submissions_dir.join("bundles");
+    std::fs::create_dir_all(&bundles_dir).map_err(|e| AppError::PathIo {
+        path: bundles_dir.clone(),
+        source: e,
+    })?;
+
+    let mut script = String::from("#!/usr/bin/env bash\n");
+    script.push_str(&format!(
+        "# Runs {} repx jobs one after another in a single allocation.\n",
+        members.len()
+    ));
+    script.push_str("# Each job records its own outcome, so the allocation always exits 0.\n");
+    script.push_str("run_job() {\n");
+    script.push_str(&format!(
+        "    [ -e \"$1/repx/{}\" ] && return 0\n",
+        BUNDLE_SKIP_MARKER
+    ));
+    script.push_str("    mkdir -p \"$1/repx\"\n");
+    script.push_str(
+        "    ( cd \"$1\" && bash \"$2\" ) >> \"$1/repx/slurm-${SLURM_JOB_ID}.out\" 2>&1\n",
+    );
+    script.push_str("}\n");
+    for job_id in members {
+        let _ = std::fs::remove_file(
+            plan.outputs_dir
+                .join(&job_id.0)
+                .join("repx")
+                .join(BUNDLE_SKIP_MARKER),
+        );
+        let job_plan = &plan.jobs[job_id];
+        let job_script = plan
+            .submissions_dir
+            .join(format!("{}.sbatch", job_plan.script_hash));
+        script.push_str(&format!(
+            "run_job {} {}\n",
+            shell_quote(&plan.outputs_dir.join(&job_id.0)),
+            shell_quote(&job_script)
+        ));
+    }
+    script.push_str("exit 0\n");
+
+    let script_path = bundles_dir.join(format!("{}.sh", bundle_name));
+    std::fs::write(&script_path, script).map_err(|e| AppError::PathIo {
+        path: script_path.clone(),
+        source: e,
+    })?;
+
+    let directives = bundle_directives(plan, bundle_key, members.len());
+
+    let mut sbatch_cmd = Command::new("sbatch");
+    sbatch_cmd
+        .arg("--parsable")
+        .args(directives.to_args())
+        .arg(format!("--job-name={}", bundle_name))
+        .arg(format!(
+            "--output={}",
+            bundles_dir.join("slurm-%j.out").display()
+        ));
+
+    if let Some(dependency) = dependency_spec(plan, members, submitted_slurm_ids, throttle) {
+        sbatch_cmd.arg(format!("--dependency={}", dependency));
+        sbatch_cmd.arg("--kill-on-invalid-dep=yes");
+    }
+    sbatch_cmd.arg(&script_path);
+
+    eprintln!(
+        "[REPX-ORCH] Bundling {} jobs into {}",
+        members.len(),
+        bundle_name
+    );
+    run_sbatch(
+        &mut sbatch_cmd,
+        &format!("bundle '{}'", bundle_name),
+        &script_path,
+    )
+}
+
+fn run_sbatch(sbatch_cmd: &mut Command, what: &str, script_path: &Path) -> Result<u32, AppError> {
+    let output = sbatch_cmd
+        .output()
+        .map_err(|e| AppError::ProcessLaunchFailed {
+            command_name: "sbatch".to_string(),
+            source: e,
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(AppError::ExecutionFailed {
+            message: format!("sbatch command failed for {}", what),
+            log_path: Some(script_path.to_path_buf()),
+            log_summary: stderr.to_string(),
+        });
+    }
+
+    let slurm_id_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    slurm_id_str
+        .parse::<u32>()
+        .map_err(|_| AppError::ExecutionFailed {
+            message: format!("Failed to parse SLURM ID from sbatch output for {}", what),
+            log_path: Some(script_path.to_path_buf()),
+            log_summary: format!("sbatch output was: '{}'", slurm_id_str),
+        })
+}
+
+fn shell_quote(path: &Path) -> String {
+    format!("'{}'", path.to_string_lossy().replace('\'', "'\\''"))
+}
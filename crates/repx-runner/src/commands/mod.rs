@@ -1,12 +1,15 @@
 use repx_client::Client;
 use std::path::PathBuf;
 
+pub mod agent;
 pub mod execute;
 pub mod gc;
 pub mod internal;
 pub mod list;
 pub mod run;
 pub mod scatter_gather;
+pub mod status;
+pub mod verify;
 
 pub struct AppContext<'a> {
     pub lab_path: &'a PathBuf,
@@ -1,11 +1,12 @@
 use crate::{cli::RunArgs, commands::AppContext};
 use colored::Colorize;
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{HumanBytes, MultiProgress, ProgressBar, ProgressStyle};
 use repx_client::{ClientEvent, SubmitOptions};
 use repx_core::{
     config::{Config, Resources},
     error::AppError,
 };
+use std::collections::HashMap;
 use std::sync::mpsc;
 use std::thread;
 
@@ -45,7 +46,16 @@ pub fn handle_run(
         };
         client.submit_batch_run(run_specs, &target_name_clone, &scheduler_clone, options)
     });
+    let scheduler_label = match scheduler {
+        "slurm" => "SLURM",
+        "pbs" => "PBS",
+        "condor" => "HTCondor",
+        "ssh-pool" => "SSH pool",
+        _ => "local executor",
+    };
     let mut pb: Option<ProgressBar> = None;
+    let worker_bars = MultiProgress::new();
+    let mut worker_pbs: HashMap<String, ProgressBar> = HashMap::new();
 
     for event in rx {
         match event {
@@ -53,27 +63,39 @@ pub fn handle_run(
                 println!("- Deploying repx binary...");
             }
             ClientEvent::GeneratingSlurmScripts { num_jobs } => {
-                println!("- Generating {} SLURM scripts...", num_jobs);
+                println!("- Generating {} {} scripts...", num_jobs, scheduler_label);
             }
             ClientEvent::ExecutingOrchestrator => {
                 println!("- Executing orchestrator on target...");
             }
-            ClientEvent::SyncingArtifacts { total } => {
-                let new_pb = ProgressBar::new(total);
+            ClientEvent::SyncingArtifacts { files, total_bytes } => {
+                if files == 0 {
+                    continue;
+                }
+                let new_pb = ProgressBar::new(total_bytes);
                 new_pb
                     .set_style(
                         ProgressStyle::default_bar()
-                        .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({percent}%) {msg}")
+                        .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({percent}%) {msg}")
                         .unwrap()
                         .progress_chars("#>-"),
                     );
-                new_pb.set_message("Syncing artifacts...");
+                new_pb.set_message(format!("Syncing {} artifacts...", files));
                 pb = Some(new_pb);
             }
-            ClientEvent::SyncingArtifactProgress { path } => {
+            ClientEvent::SyncingArtifactProgress {
+                path,
+                bytes_transferred,
+                bytes_per_second,
+                ..
+            } => {
                 if let Some(pb) = pb.as_ref() {
-                    pb.inc(1);
-                    pb.set_message(format!("{}", path.display()));
+                    pb.set_position(bytes_transferred);
+                    pb.set_message(format!(
+                        "{}/s {}",
+                        HumanBytes(bytes_per_second),
+                        path.display()
+                    ));
                 }
             }
             ClientEvent::SyncingFinished => {
@@ -83,15 +105,7 @@ pub fn handle_run(
                 pb = None;
             }
             ClientEvent::SubmittingJobs { total } => {
-                println!(
-                    "- Submitting {} jobs to {}...",
-                    total,
-                    if scheduler == "slurm" {
-                        "SLURM"
-                    } else {
-                        "local executor"
-                    }
-                );
+                println!("- Submitting {} jobs to {}...", total, scheduler_label);
             }
             ClientEvent::JobSubmitted {
                 job_id,
@@ -100,8 +114,8 @@ pub fn handle_run(
                 current,
             } => {
                 println!(
-                    "  [{}/{}] Submitted job {} as SLURM ID {}",
-                    current, total, job_id, slurm_id
+                    "  [{}/{}] Submitted job {} as {} ID {}",
+                    current, total, job_id, scheduler_label, slurm_id
                 );
             }
             ClientEvent::JobStarted {
@@ -110,13 +124,39 @@ pub fn handle_run(
                 total,
                 current,
             } => {
-                println!(
-                    "  [{}/{}] Started job {} as PID {}",
-                    current, total, job_id, pid
-                );
+                worker_bars.suspend(|| {
+                    println!(
+                        "  [{}/{}] Started job {} as PID {}",
+                        current, total, job_id, pid
+                    )
+                });
             }
             ClientEvent::WaveCompleted { wave, num_jobs } => {
-                println!("- Wave {} completed ({} jobs finished).", wave, num_jobs);
+                worker_bars.suspend(|| {
+                    println!("- Wave {} completed ({} jobs finished).", wave, num_jobs)
+                });
+            }
+            ClientEvent::WorkerProgress { job_id, progress } => {
+                let worker_pb = worker_pbs.entry(job_id.0.clone()).or_insert_with(|| {
+                    let new_pb = worker_bars.add(ProgressBar::new(progress.total as u64));
+                    new_pb.set_style(
+                        ProgressStyle::default_bar()
+                            .template("  {prefix} [{bar:30.cyan/blue}] {pos}/{len} workers {msg}")
+                            .unwrap()
+                            .progress_chars("#>-"),
+                    );
+                    new_pb.set_prefix(job_id.short_id());
+                    new_pb
+                });
+                worker_pb.set_length(progress.total as u64);
+                worker_pb.set_position(progress.done() as u64);
+                worker_pb.set_message(format!(
+                    "({} running, {} queued, {} failed)",
+                    progress.running, progress.queued, progress.failed
+                ));
+                if progress.is_finished() {
+                    worker_pb.finish();
+                }
             }
         }
     }
@@ -1,21 +1,40 @@
 use crate::cli::InternalScatterGatherArgs;
 use futures::future::join_all;
-use repx_core::{error::AppError, log_debug, log_error, log_info, model::JobId};
+use repx_client::orchestration::CONDOR_WORKER_DAG_FILE;
+use repx_core::{
+    config::WorkerSuccessThreshold,
+    error::AppError,
+    log_debug, log_error, log_info, log_warn,
+    model::JobId,
+    store::{
+        attempts,
+        progress::{self, WorkerProgress},
+    },
+};
 use repx_executor::{ExecutionRequest, Executor, Runtime};
+use serde::Serialize;
 use serde_json::Value;
 use std::{
     collections::HashMap,
     fs,
+    os::unix::fs::PermissionsExt,
     path::{Path, PathBuf},
     process::Command,
+    sync::{Arc, Mutex},
 };
-use tokio::{process::Command as TokioCommand, runtime::Runtime as TokioRuntime};
+use tokio::{process::Command as TokioCommand, runtime::Runtime as TokioRuntime, sync::Semaphore};
 
 pub fn handle_scatter_gather(args: InternalScatterGatherArgs) -> Result<(), AppError> {
     let rt = TokioRuntime::new().unwrap();
     rt.block_on(async_handle_scatter_gather(args))
 }
 
+#[derive(Serialize)]
+struct FailedWorker {
+    index: usize,
+    item: Value,
+}
+
 struct ScatterGatherOrchestrator {
     job_id: JobId,
     base_path: PathBuf,
@@ -98,8 +117,11 @@ impl ScatterGatherOrchestrator {
         ] {
             fs::create_dir_all(dir)?;
         }
+        attempts::prepare_attempt(&self.repx_dir, attempts::slurm_restart_count())?;
         let _ = fs::remove_file(self.repx_dir.join("SUCCESS"));
         let _ = fs::remove_file(self.repx_dir.join("FAIL"));
+        let _ = fs::remove_file(self.repx_dir.join("PARTIAL"));
+        let _ = fs::remove_file(self.repx_dir.join(progress::PROGRESS_FILE));
 
         if self.inputs_json_path.exists() {
             self.static_inputs =
@@ -140,13 +162,113 @@ impl ScatterGatherOrchestrator {
                 log_summary: e.to_string(),
             })
     }
+    fn collect_worker_results(&self, work_items: &[Value]) -> (Vec<PathBuf>, Vec<FailedWorker>) {
+        let mut succeeded = Vec::new();
+        let mut failed = Vec::new();
+        for (i, item) in work_items.iter().enumerate() {
+            let worker_root = self.job_root.join(format!("worker-{}", i));
+            if worker_root.join("repx").join("SUCCESS").exists() {
+                succeeded.push(worker_root.join("out"));
+            } else {
+                failed.push(FailedWorker {
+                    index: i,
+                    item: item.clone(),
+                });
+            }
+        }
+        let final_progress = WorkerProgress {
+            total: work_items.len(),
+            succeeded: succeeded.len(),
+            failed: failed.len(),
+            ..Default::default()
+        };
+        if let Err(e) = progress::write_progress(&self.repx_dir, &final_progress) {
+            log_warn!(
+                "Could not write worker progress for job '{}': {}",
+                self.job_id,
+                e
+            );
+        }
+        (succeeded, failed)
+    }
+
+    fn check_worker_threshold(
+        &self,
+        num_succeeded: usize,
+        failed_workers: &[FailedWorker],
+        threshold: Option<WorkerSuccessThreshold>,
+    ) -> Result<(), AppError> {
+        let total = num_succeeded + failed_workers.len();
+        if let Some(WorkerSuccessThreshold::Count(count)) = threshold {
+            if count > total {
+                log_warn!(
+                    "min_worker_success = {} for job '{}' exceeds its {} workers; requiring all of them.",
+                    count,
+                    self.job_id,
+                    total
+                );
+            }
+        }
+        let required = threshold.map_or(total, |t| t.required(total));
+        if num_succeeded >= required {
+            if !failed_workers.is_empty() {
+                log_warn!(
+                    "{} of {} workers failed for job '{}'; gathering over the {} that succeeded (required: {}).",
+                    failed_workers.len(),
+                    total,
+                    self.job_id,
+                    num_succeeded,
+                    required
+                );
+            }
+            return Ok(());
+        }
+
+        let failed_indices: Vec<String> =
+            failed_workers.iter().map(|w| w.index.to_string()).collect();
+        Err(AppError::ExecutionFailed {
+            message: format!(
+                "Only {} of {} workers succeeded for job '{}', but {} are required.",
+                num_succeeded, total, self.job_id, required
+            ),
+            log_path: failed_workers.first().map(|w| {
+                self.job_root
+                    .join(format!("worker-{}", w.index))
+                    .join("repx")
+            }),
+            log_summary: format!("Failed workers: {}", failed_indices.join(", ")),
+        })
+    }
+
+    fn mark_success(&self, total: usize, failed_workers: &[FailedWorker]) -> Result<(), AppError> {
+        if !failed_workers.is_empty() {
+            let summary = serde_json::json!({
+                "total": total,
+                "succeeded": total - failed_workers.len(),
+                "failed": failed_workers.iter().map(|w| w.index).collect::<Vec<_>>(),
+            });
+            fs::write(
+                self.repx_dir.join("PARTIAL"),
+                serde_json::to_string_pretty(&summary)?,
+            )?;
+        }
+        attempts::finish_attempt(&self.repx_dir);
+        fs::File::create(self.repx_dir.join("SUCCESS"))?;
+        Ok(())
+    }
+
     async fn run_gather(
         &self,
         exe_path: &Path,
         worker_output_dirs: &[PathBuf],
+        failed_workers: &[FailedWorker],
         worker_outputs_template_json: &str,
     ) -> Result<(), AppError> {
-        log_info!("[4/4] All workers completed. Starting gather phase...");
+        log_info!(
+            "[4/4] {} of {} workers succeeded. Starting gather phase...",
+            worker_output_dirs.len(),
+            worker_output_dirs.len() + failed_workers.len()
+        );
 
         let mut worker_outs_manifest = Vec::new();
         let worker_job_outputs: HashMap<String, Value> =
@@ -173,11 +295,21 @@ impl ScatterGatherOrchestrator {
             serde_json::to_string_pretty(&worker_outs_manifest)?,
         )?;
 
+        let failed_items_path = self.repx_dir.join("failed_work_items.json");
+        fs::write(
+            &failed_items_path,
+            serde_json::to_string_pretty(failed_workers)?,
+        )?;
+
         let mut gather_inputs = self.static_inputs.as_object().cloned().unwrap_or_default();
         gather_inputs.insert(
             "worker__outs".to_string(),
             Value::String(worker_manifest_path.to_string_lossy().to_string()),
         );
+        gather_inputs.insert(
+            "worker__failed".to_string(),
+            Value::String(failed_items_path.to_string_lossy().to_string()),
+        );
 
         let gather_inputs_json_path = self.repx_dir.join("gather_inputs.json");
         fs::write(
@@ -242,36 +374,31 @@ async fn async_handle_scatter_gather(args: InternalScatterGatherArgs) -> Result<
         let work_items_str = fs::read_to_string(orch.scatter_out_dir.join("work_items.json"))?;
         let work_items: Vec<Value> = serde_json::from_str(&work_items_str)?;
 
-        let mut worker_out_dirs = Vec::new();
-        for i in 0..work_items.len() {
-            let worker_root = orch.job_root.join(format!("worker-{}", i));
-            let worker_repx = worker_root.join("repx");
-            if !worker_repx.join("SUCCESS").exists() {
-                let msg = format!("Worker #{} SUCCESS marker not found.", i);
-                log_error!("{}", msg);
-                fs::File::create(orch.repx_dir.join("FAIL"))?;
-                if let Some(anchor) = args.anchor_id {
-                    let _ = Command::new("scancel").arg(anchor.to_string()).output();
-                }
-                return Err(AppError::ExecutionFailed {
-                    message: msg,
-                    log_path: Some(worker_repx),
-                    log_summary: "Worker did not complete successfully".into(),
-                });
+        let (worker_out_dirs, failed_workers) = orch.collect_worker_results(&work_items);
+        if let Err(e) = orch.check_worker_threshold(
+            worker_out_dirs.len(),
+            &failed_workers,
+            args.min_worker_success,
+        ) {
+            log_error!("{}", e);
+            fs::File::create(orch.repx_dir.join("FAIL"))?;
+            if let Some(anchor) = args.anchor_id {
+                let _ = Command::new("scancel").arg(anchor.to_string()).output();
             }
-            worker_out_dirs.push(worker_root.join("out"));
+            return Err(e);
         }
 
         match orch
             .run_gather(
                 &args.gather_exe_path,
                 &worker_out_dirs,
+                &failed_workers,
                 &args.worker_outputs_json,
             )
             .await
         {
             Ok(_) => {
-                fs::File::create(orch.repx_dir.join("SUCCESS"))?;
+                orch.mark_success(work_items.len(), &failed_workers)?;
                 if let Some(anchor) = args.anchor_id {
                     log_info!("Releasing anchor job {}", anchor);
                     let _ = Command::new("scontrol")
@@ -309,33 +436,30 @@ async fn async_handle_scatter_gather(args: InternalScatterGatherArgs) -> Result<
     let work_items_str = fs::read_to_string(orch.scatter_out_dir.join("work_items.json"))?;
     let work_items: Vec<Value> = serde_json::from_str(&work_items_str)?;
 
-    let mut worker_out_dirs = Vec::new();
-    let mut worker_repx_dirs = Vec::new();
-
     if args.scheduler == "local" {
+        let worker_concurrency = args.worker_concurrency.unwrap_or_else(num_cpus::get);
         run_local_workers(
             &orch,
             &work_items,
             &args.worker_exe_path,
-            &mut worker_out_dirs,
-            &mut worker_repx_dirs,
+            worker_concurrency,
         )
         .await?;
 
-        for (i, repx_dir) in worker_repx_dirs.iter().enumerate() {
-            if !repx_dir.join("SUCCESS").exists() {
-                let _ = fs::File::create(orch.repx_dir.join("FAIL"));
-                return Err(AppError::ExecutionFailed {
-                    message: format!("Worker #{} failed", i),
-                    log_path: Some(repx_dir.clone()),
-                    log_summary: "Worker failure".into(),
-                });
-            }
+        let (worker_out_dirs, failed_workers) = orch.collect_worker_results(&work_items);
+        if let Err(e) = orch.check_worker_threshold(
+            worker_out_dirs.len(),
+            &failed_workers,
+            args.min_worker_success,
+        ) {
+            let _ = fs::File::create(orch.repx_dir.join("FAIL"));
+            return Err(e);
         }
         if let Err(e) = orch
             .run_gather(
                 &args.gather_exe_path,
                 &worker_out_dirs,
+                &failed_workers,
                 &args.worker_outputs_json,
             )
             .await
@@ -343,7 +467,7 @@ async fn async_handle_scatter_gather(args: InternalScatterGatherArgs) -> Result<
             fs::File::create(orch.repx_dir.join("FAIL"))?;
             return Err(e);
         }
-        fs::File::create(orch.repx_dir.join("SUCCESS"))?;
+        orch.mark_success(work_items.len(), &failed_workers)?;
     } else if args.scheduler == "slurm" {
         let slurm_ids = submit_slurm_workers_async(
             &orch,
@@ -352,10 +476,30 @@ async fn async_handle_scatter_gather(args: InternalScatterGatherArgs) -> Result<
             &args.worker_sbatch_opts,
         )
         .await?;
+        progress::write_progress(&orch.repx_dir, &WorkerProgress::new(work_items.len()))?;
 
         submit_slurm_gather_job(&orch, &args, &slurm_ids).await?;
 
         log_info!("Orchestrator finished submitting workers and gather job. Exiting to free slot.");
+    } else if args.scheduler == "condor" {
+        let submit_description = args.worker_submit_description.as_ref().ok_or_else(|| {
+            AppError::ConfigurationError(
+                "The condor scheduler requires --worker-submit-description".into(),
+            )
+        })?;
+        write_condor_worker_dag(
+            &orch,
+            &work_items,
+            &args.worker_exe_path,
+            submit_description,
+        )
+        .await?;
+        progress::write_progress(&orch.repx_dir, &WorkerProgress::new(work_items.len()))?;
+
+        log_info!(
+            "Wrote a worker DAG with {} nodes. DAGMan runs the workers and then the gather node.",
+            work_items.len()
+        );
     } else {
         return Err(AppError::ConfigurationError(format!(
             "Unknown scheduler: {}",
@@ -424,6 +568,10 @@ async fn submit_slurm_gather_job(
         gather_cmd_parts.push("--anchor-id".to_string());
         gather_cmd_parts.push(anchor.to_string());
     }
+    if let Some(threshold) = args.min_worker_success {
+        gather_cmd_parts.push("--min-worker-success".to_string());
+        gather_cmd_parts.push(threshold.to_string());
+    }
 
     let cmd_str = gather_cmd_parts.join(" ");
 
@@ -464,26 +612,8 @@ async fn submit_slurm_workers_async(
     for (i, item) in work_items.iter().enumerate() {
         let (w_out, w_repx, w_inputs) = orch.prepare_worker(i, item)?;
 
-        let executor = orch.create_executor(w_out.clone(), w_repx.clone());
-        let args = vec![
-            w_out.to_string_lossy().to_string(),
-            w_inputs.to_string_lossy().to_string(),
-        ];
-        let cmd = executor
-            .build_command_for_script(worker_exe, &args)
-            .await
-            .map_err(|e| AppError::ExecutionFailed {
-                message: format!("Failed to build command for worker #{}", i),
-                log_path: None,
-                log_summary: e.to_string(),
-            })?;
-        let cmd_str = command_to_shell_string(&cmd);
-        let wrapped_cmd = format!(
-            "( {} && touch {}/SUCCESS ) || ( touch {}/FAIL; exit 1 )",
-            cmd_str,
-            w_repx.display(),
-            w_repx.display()
-        );
+        let wrapped_cmd =
+            worker_shell_command(orch, worker_exe, &w_out, &w_repx, &w_inputs, i).await?;
 
         let mut sbatch = Command::new("sbatch");
         sbatch
@@ -507,19 +637,75 @@ async fn submit_slurm_workers_async(
     log_info!("Submitted {} workers to Slurm.", slurm_ids.len());
     Ok(slurm_ids)
 }
+async fn worker_shell_command(
+    orch: &ScatterGatherOrchestrator,
+    worker_exe: &Path,
+    w_out: &Path,
+    w_repx: &Path,
+    w_inputs: &Path,
+    idx: usize,
+) -> Result<String, AppError> {
+    let executor = orch.create_executor(w_out.to_path_buf(), w_repx.to_path_buf());
+    let args = vec![
+        w_out.to_string_lossy().to_string(),
+        w_inputs.to_string_lossy().to_string(),
+    ];
+    let cmd = executor
+        .build_command_for_script(worker_exe, &args)
+        .await
+        .map_err(|e| AppError::ExecutionFailed {
+            message: format!("Failed to build command for worker #{}", idx),
+            log_path: None,
+            log_summary: e.to_string(),
+        })?;
+    Ok(format!(
+        "rm -f {repx}/SUCCESS {repx}/FAIL; ( {} && touch {repx}/SUCCESS ) || ( touch {repx}/FAIL; exit 1 )",
+        command_to_shell_string(&cmd),
+        repx = w_repx.display()
+    ))
+}
+
+async fn write_condor_worker_dag(
+    orch: &ScatterGatherOrchestrator,
+    work_items: &[Value],
+    worker_exe: &Path,
+    submit_description: &Path,
+) -> Result<(), AppError> {
+    let mut dag = String::new();
+    for (i, item) in work_items.iter().enumerate() {
+        let (w_out, w_repx, w_inputs) = orch.prepare_worker(i, item)?;
+        let wrapped_cmd =
+            worker_shell_command(orch, worker_exe, &w_out, &w_repx, &w_inputs, i).await?;
+
+        let script_path = w_repx.join("run.sh");
+        fs::write(&script_path, format!("#!/bin/sh\n{}\n", wrapped_cmd))?;
+        fs::set_permissions(&script_path, fs::Permissions::from_mode(0o755))?;
+
+        dag.push_str(&format!("JOB w{} {}\n", i, submit_description.display()));
+        dag.push_str(&format!(
+            "VARS w{} script=\"{}\" repx_dir=\"{}\"\n",
+            i,
+            script_path.display(),
+            w_repx.display()
+        ));
+    }
+    fs::write(orch.repx_dir.join(CONDOR_WORKER_DAG_FILE), dag)?;
+    Ok(())
+}
+
 async fn run_local_workers(
     orch: &ScatterGatherOrchestrator,
     work_items: &[Value],
     worker_exe: &Path,
-    out_dirs: &mut Vec<PathBuf>,
-    repx_dirs: &mut Vec<PathBuf>,
+    concurrency: usize,
 ) -> Result<(), AppError> {
     let mut tasks = Vec::new();
+    let slots = Arc::new(Semaphore::new(concurrency.max(1)));
+    let worker_progress = Arc::new(Mutex::new(WorkerProgress::new(work_items.len())));
+    progress::write_progress(&orch.repx_dir, &worker_progress.lock().unwrap())?;
 
     for (i, item) in work_items.iter().enumerate() {
         let (w_out, w_repx, w_inputs) = orch.prepare_worker(i, item)?;
-        out_dirs.push(w_out.clone());
-        repx_dirs.push(w_repx.clone());
 
         let executor = orch.create_executor(w_out.clone(), w_repx.clone());
         let exe = worker_exe.to_path_buf();
@@ -529,44 +715,53 @@ async fn run_local_workers(
         ];
 
         let repx_dir_for_task = w_repx;
+        let job_repx_dir = orch.repx_dir.clone();
+        let slots = slots.clone();
+        let worker_progress = worker_progress.clone();
         tasks.push(tokio::spawn(async move {
+            let _slot = slots
+                .acquire_owned()
+                .await
+                .expect("worker slot semaphore closed");
+            update_progress(&worker_progress, &job_repx_dir, |p| p.worker_started());
             let result = executor.execute_script(&exe, &args).await;
             if result.is_ok() {
                 let _ = fs::File::create(repx_dir_for_task.join("SUCCESS"));
             } else {
                 let _ = fs::File::create(repx_dir_for_task.join("FAIL"));
             }
+            let success = result.is_ok();
+            update_progress(&worker_progress, &job_repx_dir, |p| {
+                p.worker_finished(success)
+            });
             result
         }));
     }
     log_info!(
-        "[3/4] Waiting for {} local worker jobs to complete...",
-        tasks.len()
+        "[3/4] Waiting for {} local worker jobs to complete ({} at a time)...",
+        tasks.len(),
+        concurrency.max(1)
     );
     let results = join_all(tasks).await;
     for (i, res) in results.into_iter().enumerate() {
         match res {
             Ok(Ok(_)) => {}
-            Ok(Err(e)) => {
-                fs::File::create(orch.repx_dir.join("FAIL"))?;
-                return Err(AppError::ExecutionFailed {
-                    message: format!("Local worker #{} failed", i),
-                    log_path: None,
-                    log_summary: e.to_string(),
-                });
-            }
-            Err(e) => {
-                fs::File::create(orch.repx_dir.join("FAIL"))?;
-                return Err(AppError::ExecutionFailed {
-                    message: format!("Local worker #{} panicked", i),
-                    log_path: None,
-                    log_summary: e.to_string(),
-                });
-            }
+            Ok(Err(e)) => log_error!("Local worker #{} failed: {}", i, e),
+            Err(e) => log_error!("Local worker #{} panicked: {}", i, e),
         }
     }
     Ok(())
 }
+fn update_progress(
+    worker_progress: &Mutex<WorkerProgress>,
+    repx_dir: &Path,
+    update_fn: impl FnOnce(&mut WorkerProgress),
+) {
+    let mut progress_guard = worker_progress.lock().unwrap();
+    update_fn(&mut progress_guard);
+    let _ = progress::write_progress(repx_dir, &progress_guard);
+}
+
 fn command_to_shell_string(cmd: &TokioCommand) -> String {
     let program = cmd.as_std().get_program().to_string_lossy();
     let args: Vec<String> = cmd
@@ -0,0 +1,37 @@
+use crate::cli::InternalStatusArgs;
+use repx_core::{
+    error::AppError,
+    model::JobId,
+    store::status::{self, StatusReport},
+};
+use std::fs;
+use std::path::Path;
+
+pub fn handle_internal_status(args: InternalStatusArgs) -> Result<(), AppError> {
+    let report = status_report(
+        &args.base_path,
+        &args.jobs_from,
+        args.submission_log.as_deref(),
+    )?;
+    println!("{}", serde_json::to_string(&report)?);
+    Ok(())
+}
+
+pub(crate) fn status_report(
+    base_path: &Path,
+    jobs_from: &Path,
+    submission_log: Option<&Path>,
+) -> Result<StatusReport, AppError> {
+    let job_list = fs::read_to_string(jobs_from).map_err(|e| AppError::PathIo {
+        path: jobs_from.to_path_buf(),
+        source: e,
+    })?;
+    let job_ids: Vec<JobId> = job_list
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| JobId(line.to_string()))
+        .collect();
+
+    status::collect_status_report(base_path, &job_ids, submission_log)
+}
@@ -0,0 +1,50 @@
+use crate::cli::VerifyArgs;
+use colored::Colorize;
+use repx_client::{error::ClientError, Client};
+use repx_core::error::AppError;
+
+pub fn handle_verify(args: VerifyArgs, client: &Client, target_name: &str) -> Result<(), AppError> {
+    println!(
+        "- Verifying lab artifacts on target '{}'...",
+        target_name.cyan()
+    );
+    let report = client
+        .verify_artifacts(target_name, !args.no_resync)
+        .map_err(|e| match e {
+            ClientError::Core(ae) => ae,
+            _ => AppError::ExecutionFailed {
+                message: "Artifact verification failed".into(),
+                log_path: None,
+                log_summary: e.to_string(),
+            },
+        })?;
+
+    for path in &report.mismatched {
+        let state = if report.resynced.contains(path) {
+            "re-synced".green()
+        } else {
+            "mismatch".red()
+        };
+        println!("  [{}] {}", state, path.display());
+    }
+    println!(
+        "- Checked {} artifacts: {} mismatched, {} re-synced, {} not yet synced.",
+        report.checked,
+        report.mismatched.len(),
+        report.resynced.len(),
+        report.missing.len()
+    );
+
+    if report.resynced.len() < report.mismatched.len() {
+        return Err(AppError::ExecutionFailed {
+            message: format!(
+                "{} artifacts on target '{}' do not match the local lab",
+                report.mismatched.len() - report.resynced.len(),
+                target_name
+            ),
+            log_path: None,
+            log_summary: String::new(),
+        });
+    }
+    Ok(())
+}
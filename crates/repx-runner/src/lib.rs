@@ -26,9 +26,11 @@ pub fn run(cli: Cli) -> Result<(), AppError> {
         }
         Commands::InternalExecute(args) => commands::execute::handle_execute(args),
         Commands::InternalScatterGather(args) => {
-            commands::scatter_gather::handle_scatter_gather(args)
+            commands::scatter_gather::handle_scatter_gather(*args)
         }
         Commands::InternalGc(args) => commands::gc::handle_internal_gc(args),
+        Commands::InternalStatus(args) => commands::status::handle_internal_status(args),
+        Commands::Agent => commands::agent::handle_agent(),
         Commands::List(args) => commands::list::handle_list(args, &cli.lab),
         Commands::Gc(args) => {
             let config = config::load_config()?;
@@ -46,6 +48,26 @@ pub fn run(cli: Cli) -> Result<(), AppError> {
             };
             commands::gc::handle_gc(args, &context, &config)
         }
+        Commands::Verify(args) => {
+            let config = config::load_config()?;
+            let client = Client::new(config.clone(), cli.lab.clone()).map_err(|e| {
+                AppError::ExecutionFailed {
+                    message: "Failed to initialize client".to_string(),
+                    log_path: None,
+                    log_summary: e.to_string(),
+                }
+            })?;
+            let target_name = cli
+                .target
+                .as_ref()
+                .or(config.submission_target.as_ref())
+                .ok_or_else(|| {
+                    AppError::ConfigurationError(
+                        "No target specified. Set 'submission_target' in your config or use the --target flag.".to_string(),
+                    )
+                })?;
+            commands::verify::handle_verify(args, &client, target_name)
+        }
         Commands::Run(args) => {
             let config = config::load_config()?;
             let resources = config::load_resources(cli.resources.as_ref())?;
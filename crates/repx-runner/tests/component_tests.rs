@@ -113,3 +113,51 @@ fn test_internal_execute_fails_if_lab_not_staged() {
         .stderr(predicates::str::contains("Execution failed for job"))
         .stderr(predicates::str::contains("No such file or directory"));
 }
+
+#[test]
+fn test_agent_serves_json_rpc_over_stdio() {
+    let dir = tempfile::tempdir().unwrap();
+    let log_path = dir.path().join("outputs/job/repx/stdout.log");
+    let requests = [
+        serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "hello"}),
+        serde_json::json!({
+            "jsonrpc": "2.0", "id": 2, "method": "write_file",
+            "params": {"path": log_path, "content": "one\ntwo 'quoted' $HOME\nthree\n"}
+        }),
+        serde_json::json!({
+            "jsonrpc": "2.0", "id": 3, "method": "tail_log",
+            "params": {"path": log_path, "lines": 2}
+        }),
+        serde_json::json!({
+            "jsonrpc": "2.0", "id": 4, "method": "list_artifacts",
+            "params": {"base_path": dir.path().join("outputs")}
+        }),
+        serde_json::json!({"jsonrpc": "2.0", "id": 5, "method": "unknown"}),
+    ];
+    let stdin: String = requests.iter().map(|r| format!("{}\n", r)).collect();
+
+    let output = assert_cmd::Command::new(env!("CARGO_BIN_EXE_repx-runner"))
+        .arg("agent")
+        .write_stdin(stdin)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let responses: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    assert_eq!(responses.len(), 5);
+    assert_eq!(responses[0]["result"]["protocol"], 1);
+    assert!(responses[1]["error"].is_null());
+    assert_eq!(
+        responses[2]["result"],
+        serde_json::json!(["two 'quoted' $HOME", "three"])
+    );
+    assert_eq!(
+        responses[3]["result"],
+        serde_json::json!(["job/repx/stdout.log"])
+    );
+    assert_eq!(responses[4]["id"], 5);
+    assert!(responses[4]["error"]["code"].is_i64());
+}
@@ -152,8 +152,15 @@ fn test_gc_root_rotation_keeps_last_5() {
         default_scheduler: None,
         default_execution_type: None,
         mount_host_paths: false,
+        mount_paths: vec![],
+        verify_artifacts: false,
+        disk_check: None,
         local: None,
         slurm: None,
+        pbs: None,
+        condor: None,
+        ssh_pool: None,
+        ssh: None,
     };
     let config = Config {
         targets: BTreeMap::from([("local".to_string(), target_config)]),
@@ -14,6 +14,12 @@ pub struct TestHarness {
     pub metadata: Value,
 }
 
+impl Default for TestHarness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TestHarness {
     pub fn new() -> Self {
         Self::with_execution_type("native")
@@ -0,0 +1,388 @@
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+struct StubSlurm {
+    dir: TempDir,
+}
+
+impl StubSlurm {
+    fn new() -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let bin_dir = dir.path().join("bin");
+        fs::create_dir_all(&bin_dir).unwrap();
+        fs::create_dir_all(dir.path().join("submissions")).unwrap();
+        let state = dir.path().display();
+        let stubs = [
+            (
+                "sbatch",
+                format!(
+                    "echo \"sbatch $*\" >> {state}/calls\n\
+                     case \"$*\" in *fail*) echo 'sbatch: error: Batch job submission failed' >&2; exit 1;; esac\n\
+                     id=$(( $(cat {state}/last_id 2>/dev/null || echo 100) + 1 ))\n\
+                     echo $id > {state}/last_id\n\
+                     echo $id >> {state}/queue\n\
+                     echo $id\n"
+                ),
+            ),
+            (
+                "squeue",
+                format!(
+                    "echo \"squeue $*\" >> {state}/calls\n\
+                     case \"$*\" in *%j*) cat {state}/named_queue 2>/dev/null; exit 0;; esac\n\
+                     cat {state}/queue 2>/dev/null\n\
+                     : > {state}/queue\n"
+                ),
+            ),
+            (
+                "sacct",
+                format!(
+                    "echo \"sacct $*\" >> {state}/calls\n\
+                     state=$(head -n 1 {state}/sacct_states 2>/dev/null)\n\
+                     sed -i 1d {state}/sacct_states 2>/dev/null\n\
+                     echo ${{state:-FAILED}}\n"
+                ),
+            ),
+            (
+                "sinfo",
+                "echo 'short 00:30:00'\necho 'long* 1-00:00:00'\n".to_string(),
+            ),
+            (
+                "scancel",
+                format!("echo \"scancel $*\" >> {state}/calls\n"),
+            ),
+        ];
+        for (name, body) in stubs {
+            let path = bin_dir.join(name);
+            fs::write(&path, format!("#!/bin/sh\n{}", body)).unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        Self { dir }
+    }
+
+    fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    fn write_plan(&self, jobs: serde_json::Value) -> PathBuf {
+        self.write_bundled_plan(jobs, serde_json::json!({}))
+    }
+
+    fn write_bundled_plan(&self, jobs: serde_json::Value, bundles: serde_json::Value) -> PathBuf {
+        let plan = serde_json::json!({
+            "submissions_dir": self.path().join("submissions"),
+            "outputs_dir": self.path().join("outputs"),
+            "jobs": jobs,
+            "bundles": bundles,
+        });
+        let plan_path = self.path().join("plan.json");
+        fs::write(&plan_path, plan.to_string()).unwrap();
+        plan_path
+    }
+
+    fn orchestrate(&self, plan_path: &Path, args: &[&str]) -> std::process::Output {
+        let path = format!(
+            "{}:{}",
+            self.path().join("bin").display(),
+            std::env::var("PATH").unwrap_or_default()
+        );
+        assert_cmd::Command::new(env!("CARGO_BIN_EXE_repx-runner"))
+            .arg("internal-orchestrate")
+            .arg(plan_path)
+            .args(["--poll-interval-secs", "1"])
+            .args(args)
+            .env("PATH", path)
+            .output()
+            .unwrap()
+    }
+
+    fn calls(&self) -> Vec<String> {
+        fs::read_to_string(self.path().join("calls"))
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn submitted_log(&self) -> String {
+        fs::read_to_string(self.path().join("submissions/submitted.log")).unwrap()
+    }
+}
+
+fn job(dependencies: &[&str]) -> serde_json::Value {
+    serde_json::json!({ "script_hash": "script", "dependencies": dependencies })
+}
+
+#[test]
+fn test_max_in_flight_waits_for_the_queue_to_drain() {
+    let slurm = StubSlurm::new();
+    let plan = slurm.write_plan(serde_json::json!({
+        "job-a": job(&[]),
+        "job-b": job(&[]),
+        "job-c": job(&[]),
+    }));
+
+    let output = slurm.orchestrate(&plan, &["--max-in-flight", "2"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let commands: Vec<String> = slurm
+        .calls()
+        .iter()
+        .map(|call| call.split_whitespace().next().unwrap().to_string())
+        .collect();
+    assert_eq!(
+        commands,
+        ["squeue", "sbatch", "sbatch", "squeue", "squeue", "sbatch"]
+    );
+    assert_eq!(
+        slurm.submitted_log(),
+        "job-a 101 slurm\njob-b 102 slurm\njob-c 103 slurm\n"
+    );
+}
+
+#[test]
+fn test_submission_rate_is_limited() {
+    let slurm = StubSlurm::new();
+    let plan = slurm.write_plan(serde_json::json!({
+        "job-a": job(&[]),
+        "job-b": job(&[]),
+        "job-c": job(&[]),
+    }));
+
+    let started = Instant::now();
+    let output = slurm.orchestrate(&plan, &["--max-submissions-per-minute", "120"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    let sbatch_calls = slurm
+        .calls()
+        .iter()
+        .filter(|call| call.starts_with("sbatch"))
+        .count();
+    assert_eq!(sbatch_calls, 3);
+}
+
+#[test]
+fn test_dependents_of_unsuccessful_jobs_are_not_submitted() {
+    let slurm = StubSlurm::new();
+    let timeout_job = |name: &str| {
+        serde_json::json!({
+            "script_hash": name,
+            "dependencies": [],
+            "on_timeout": { "retries": 1 },
+        })
+    };
+    let plan = slurm.write_plan(serde_json::json!({
+        "job-ok": timeout_job("ok"),
+        "job-bad": timeout_job("bad"),
+        "after-ok": job(&["job-ok"]),
+        "after-bad": job(&["job-bad"]),
+    }));
+    let success_marker = slurm.path().join("outputs/job-ok/repx/SUCCESS");
+    fs::create_dir_all(success_marker.parent().unwrap()).unwrap();
+    fs::write(&success_marker, "").unwrap();
+
+    let output = slurm.orchestrate(&plan, &[]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Not submitting 'after-bad': dependency 'job-bad' did not succeed."));
+    let sbatch_calls: Vec<String> = slurm
+        .calls()
+        .into_iter()
+        .filter(|call| call.starts_with("sbatch"))
+        .collect();
+    assert_eq!(sbatch_calls.len(), 3);
+    assert!(sbatch_calls
+        .iter()
+        .all(|call| !call.contains("--dependency")));
+    assert!(slurm.submitted_log().contains("after-ok 103 slurm\n"));
+}
+
+#[test]
+fn test_background_orchestrator_keeps_going_after_a_failed_sbatch() {
+    let slurm = StubSlurm::new();
+    let plan = slurm.write_plan(serde_json::json!({
+        "job-a": job(&[]),
+        "job-fail": { "script_hash": "fail", "dependencies": [] },
+        "job-z": job(&[]),
+        "after-fail": job(&["job-fail"]),
+    }));
+
+    let output = slurm.orchestrate(&plan, &["--max-in-flight", "10"]);
+    assert!(!output.status.success());
+
+    let calls = slurm.calls();
+    assert!(calls.iter().all(|call| !call.starts_with("scancel")));
+    assert_eq!(slurm.submitted_log(), "job-a 101 slurm\njob-z 102 slurm\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Not submitting 'after-fail'"));
+    assert!(stderr.contains("1 jobs could not be submitted"));
+}
+
+#[test]
+fn test_timed_out_job_is_resubmitted_once_sacct_reports_it() {
+    let slurm = StubSlurm::new();
+    let plan = slurm.write_plan(serde_json::json!({
+        "job-slow": {
+            "script_hash": "slow",
+            "dependencies": [],
+            "directives": { "time": "00:10:00", "sbatch_opts": [] },
+            "on_timeout": { "retries": 1 },
+        },
+        "after-slow": job(&["job-slow"]),
+    }));
+    fs::write(slurm.path().join("sacct_states"), "RUNNING\nTIMEOUT\n").unwrap();
+
+    let output = slurm.orchestrate(&plan, &[]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let calls: Vec<String> = slurm
+        .calls()
+        .into_iter()
+        .filter(|call| !call.starts_with("squeue"))
+        .collect();
+    assert_eq!(calls.len(), 5, "{:?}", calls);
+    assert!(calls[1].starts_with("sacct") && calls[2].starts_with("sacct"));
+    assert!(calls[3].starts_with("sbatch --parsable --time=00:20:00"));
+    assert!(calls[4].starts_with("sacct"));
+    assert_eq!(
+        slurm.submitted_log(),
+        "job-slow 101 slurm\njob-slow 102 slurm\n"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Not submitting 'after-slow'"));
+}
+
+#[test]
+fn test_bundles_fit_the_partition_time_limit_and_hold_dependents() {
+    let slurm = StubSlurm::new();
+    let member = |name: &str| serde_json::json!({ "script_hash": name, "dependencies": [], "bundle": "short-jobs" });
+    let plan = slurm.write_bundled_plan(
+        serde_json::json!({
+            "job-1": member("one"),
+            "job-2": member("two"),
+            "job-3": member("three"),
+            "job-4": member("four"),
+            "after-1": job(&["job-1"]),
+            "after-2": job(&["job-2"]),
+        }),
+        serde_json::json!({
+            "short-jobs": {
+                "directives": { "partition": "short", "time": "00:10:00", "sbatch_opts": [] },
+                "max_jobs": 5,
+            }
+        }),
+    );
+    let success_marker = slurm.path().join("outputs/job-1/repx/SUCCESS");
+    fs::create_dir_all(success_marker.parent().unwrap()).unwrap();
+    fs::write(&success_marker, "").unwrap();
+    fs::create_dir_all(slurm.path().join("outputs/job-2/repx")).unwrap();
+    fs::write(slurm.path().join("outputs/job-2/repx/CANCELLED"), "").unwrap();
+
+    let output = slurm.orchestrate(&plan, &[]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let sbatch_calls: Vec<String> = slurm
+        .calls()
+        .into_iter()
+        .filter(|call| call.starts_with("sbatch"))
+        .collect();
+    assert_eq!(sbatch_calls.len(), 3, "{:?}", sbatch_calls);
+    assert!(sbatch_calls[0].contains("--partition=short --time=00:30:00"));
+    assert!(sbatch_calls[0].contains("--job-name=repx-bundle-short-jobs-0-0"));
+    assert!(sbatch_calls[1].ends_with("four.sbatch"));
+    assert!(!sbatch_calls[2].contains("--dependency"));
+    assert_eq!(
+        slurm.submitted_log(),
+        "job-1 101 slurm\njob-2 101 slurm\njob-3 101 slurm\njob-4 102 slurm\nafter-1 103 slurm\n"
+    );
+
+    let bundle_script = fs::read_to_string(
+        slurm
+            .path()
+            .join("submissions/bundles/repx-bundle-short-jobs-0-0.sh"),
+    )
+    .unwrap();
+    assert_eq!(bundle_script.matches("run_job '").count(), 3);
+    assert!(bundle_script.ends_with("exit 0\n"));
+    assert!(!slurm.path().join("outputs/job-2/repx/CANCELLED").exists());
+
+    for script_hash in ["one", "two", "three"] {
+        fs::write(
+            slurm
+                .path()
+                .join(format!("submissions/{}.sbatch", script_hash)),
+            "touch ran\n",
+        )
+        .unwrap();
+    }
+    fs::create_dir_all(slurm.path().join("outputs/job-3/repx")).unwrap();
+    fs::write(slurm.path().join("outputs/job-3/repx/CANCELLED"), "").unwrap();
+    let bundle_run = std::process::Command::new("bash")
+        .arg(
+            slurm
+                .path()
+                .join("submissions/bundles/repx-bundle-short-jobs-0-0.sh"),
+        )
+        .env("SLURM_JOB_ID", "101")
+        .status()
+        .unwrap();
+    assert!(bundle_run.success());
+    assert!(slurm.path().join("outputs/job-1/ran").exists());
+    assert!(slurm.path().join("outputs/job-2/ran").exists());
+    assert!(!slurm.path().join("outputs/job-3/ran").exists());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Not submitting 'after-2': dependency 'job-2' did not succeed."));
+}
+
+#[test]
+fn test_failed_submission_cancels_jobs_and_scatter_workers() {
+    let slurm = StubSlurm::new();
+    let plan = slurm.write_plan(serde_json::json!({
+        "job-a": job(&[]),
+        "job-sg": { "script_hash": "sg", "dependencies": [], "job_type": "scatter-gather" },
+        "job-z": { "script_hash": "fail", "dependencies": [] },
+    }));
+    fs::write(
+        slurm.path().join("named_queue"),
+        "201 job-sg-w0\n202 job-sg-gather\n203 job-sg-other-w0\n204 job-a\n",
+    )
+    .unwrap();
+
+    let output = slurm.orchestrate(&plan, &[]);
+    assert!(!output.status.success());
+
+    let scancels: Vec<String> = slurm
+        .calls()
+        .into_iter()
+        .filter(|call| call.starts_with("scancel"))
+        .collect();
+    assert_eq!(scancels, ["scancel 101 103 102", "scancel 201 202"]);
+    assert_eq!(slurm.submitted_log(), "");
+}
+
+#[test]
+fn test_keep_policy_leaves_submitted_jobs_recorded() {
+    let slurm = StubSlurm::new();
+    let plan = slurm.write_plan(serde_json::json!({
+        "job-a": job(&[]),
+        "job-b": job(&[]),
+        "job-z": { "script_hash": "fail", "dependencies": [] },
+    }));
+
+    let output = slurm.orchestrate(&plan, &["--on-failure", "keep"]);
+    assert!(!output.status.success());
+
+    assert!(slurm
+        .calls()
+        .iter()
+        .all(|call| !call.starts_with("scancel")));
+    assert_eq!(slurm.submitted_log(), "job-a 101 slurm\njob-b 102 slurm\n");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "job-a 101 slurm\njob-b 102 slurm\n"
+    );
+}
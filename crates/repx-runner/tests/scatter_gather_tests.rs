@@ -0,0 +1,71 @@
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+fn write_script(path: &Path, body: &str) {
+    fs::write(path, format!("#!/bin/sh\n{}", body)).unwrap();
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+#[test]
+fn test_local_workers_respect_worker_concurrency() {
+    let dir = tempfile::tempdir().unwrap();
+    let base_path = dir.path().join("store");
+    let bin_dir = dir.path().join("bin");
+    let running_dir = dir.path().join("running");
+    fs::create_dir_all(&bin_dir).unwrap();
+    fs::create_dir_all(&running_dir).unwrap();
+    fs::create_dir_all(base_path.join("outputs/job-sg/repx")).unwrap();
+    fs::write(base_path.join("outputs/job-sg/repx/inputs.json"), "{}").unwrap();
+
+    let scatter = bin_dir.join("scatter");
+    let worker = bin_dir.join("worker");
+    let gather = bin_dir.join("gather");
+    write_script(
+        &scatter,
+        "echo '[1, 2, 3, 4, 5, 6]' > \"$1/work_items.json\"\n",
+    );
+    let running = running_dir.display();
+    let concurrency_log = dir.path().join("concurrency").display().to_string();
+    write_script(
+        &worker,
+        &format!(
+            "touch {running}/$$\n\
+             ls {running} | wc -l >> {concurrency_log}\n\
+             sleep 0.3\n\
+             rm {running}/$$\n"
+        ),
+    );
+    write_script(&gather, "exit 0\n");
+
+    let output = assert_cmd::Command::new(env!("CARGO_BIN_EXE_repx-runner"))
+        .arg("internal-scatter-gather")
+        .args(["--job-id", "job-sg", "--runtime", "native"])
+        .arg("--base-path")
+        .arg(&base_path)
+        .args(["--host-tools-dir", "none", "--scheduler", "local"])
+        .args(["--worker-sbatch-opts", ""])
+        .arg("--job-package-path")
+        .arg(dir.path())
+        .arg("--scatter-exe-path")
+        .arg(&scatter)
+        .arg("--worker-exe-path")
+        .arg(&worker)
+        .arg("--gather-exe-path")
+        .arg(&gather)
+        .args(["--worker-outputs-json", "{}"])
+        .args(["--worker-concurrency", "2"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    let counts: Vec<usize> = fs::read_to_string(dir.path().join("concurrency"))
+        .unwrap()
+        .lines()
+        .map(|line| line.trim().parse().unwrap())
+        .collect();
+    assert_eq!(counts.len(), 6);
+    let max_concurrency = counts.iter().copied().max().unwrap();
+    assert!(max_concurrency <= 2, "{:?}", counts);
+    assert!(base_path.join("outputs/job-sg/repx/SUCCESS").exists());
+}
@@ -3,16 +3,17 @@ pub mod targets;
 
 use crate::app::{jobs::JobsState, targets::TargetsState};
 use crate::model::{StatusCounts, TuiExecutor, TuiRowItem, TuiScheduler, TuiTarget};
-use repx_client::{error::ClientError, Client, SubmitOptions};
+use repx_client::{error::ClientError, Client, ClientEvent, SubmitOptions};
 use repx_core::{
     config::Resources,
     engine, log_info, log_warn,
     model::{JobId, Lab},
+    store::progress::WorkerProgress,
     theme::Theme,
 };
 use std::collections::{HashSet, VecDeque};
 use std::path::PathBuf;
-use std::sync::mpsc::{Receiver, Sender};
+use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
@@ -63,6 +64,7 @@ type TargetPollUpdate = Result<
     (
         String,
         std::collections::HashMap<repx_core::model::JobId, repx_core::engine::JobStatus>,
+        std::collections::HashMap<repx_core::model::JobId, WorkerProgress>,
     ),
     ClientError,
 >;
@@ -82,20 +84,40 @@ pub enum SubmissionResult {
         affected_job_ids: HashSet<JobId>,
         error: String,
     },
+    SyncProgress(SyncProgress),
+    SyncFinished,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncProgress {
+    pub path: PathBuf,
+    pub bytes_transferred: u64,
+    pub total_bytes: u64,
+    pub bytes_per_second: u64,
 }
 
 pub enum ExternalAction {
     ExploreLocal(PathBuf),
     ExploreRemote {
         address: String,
+        ssh_options: Vec<String>,
         path: PathBuf,
     },
     EditLocal(Vec<PathBuf>),
     EditRemote {
         address: String,
+        ssh_options: Vec<String>,
         paths: Vec<PathBuf>,
     },
 }
+fn ssh_options(target_config: &repx_core::config::Target) -> Vec<String> {
+    target_config
+        .ssh
+        .as_ref()
+        .map(|ssh| ssh.to_ssh_options())
+        .unwrap_or_default()
+}
+
 pub struct App {
     pub client: Arc<Client>,
     pub theme: Theme,
@@ -118,6 +140,7 @@ pub struct App {
     pub focused_panel: PanelFocus,
     pub pending_action: Option<ExternalAction>,
     pub system_logs: Vec<String>,
+    pub sync_progress: Option<SyncProgress>,
     system_log_rx: Receiver<String>,
 }
 
@@ -169,6 +192,36 @@ impl App {
                         .collect();
                     available_executors.insert(TuiScheduler::Slurm, executors);
                 }
+                if let Some(conf) = &target_config.pbs {
+                    available_schedulers.push(TuiScheduler::Pbs);
+                    let executors: Vec<TuiExecutor> = conf
+                        .execution_types
+                        .iter()
+                        .filter_map(|s| s.parse().ok())
+                        .filter(|e| !is_native_lab || *e == TuiExecutor::Native)
+                        .collect();
+                    available_executors.insert(TuiScheduler::Pbs, executors);
+                }
+                if let Some(conf) = &target_config.condor {
+                    available_schedulers.push(TuiScheduler::Condor);
+                    let executors: Vec<TuiExecutor> = conf
+                        .execution_types
+                        .iter()
+                        .filter_map(|s| s.parse().ok())
+                        .filter(|e| !is_native_lab || *e == TuiExecutor::Native)
+                        .collect();
+                    available_executors.insert(TuiScheduler::Condor, executors);
+                }
+                if let Some(conf) = &target_config.ssh_pool {
+                    available_schedulers.push(TuiScheduler::SshPool);
+                    let executors: Vec<TuiExecutor> = conf
+                        .execution_types
+                        .iter()
+                        .filter_map(|s| s.parse().ok())
+                        .filter(|e| !is_native_lab || *e == TuiExecutor::Native)
+                        .collect();
+                    available_executors.insert(TuiScheduler::SshPool, executors);
+                }
 
                 available_schedulers.retain(|s| {
                     available_executors
@@ -248,6 +301,7 @@ impl App {
             submission_rx,
             system_log_rx,
             system_logs: Vec::new(),
+            sync_progress: None,
             is_loading: true,
             resources,
             focused_panel: PanelFocus::Jobs,
@@ -275,7 +329,7 @@ impl App {
     pub fn check_for_updates(&mut self) {
         while let Ok(update_result) = self.status_rx.try_recv() {
             match update_result {
-                Ok((target_name, job_statuses)) => {
+                Ok((target_name, job_statuses, worker_progress)) => {
                     let active_target = self.targets_state.get_active_target_name();
                     if target_name != active_target {
                         log_info!(
@@ -291,6 +345,7 @@ impl App {
 
                     log_info!("Received status update. Applying new statuses.");
                     self.jobs_state.apply_statuses(&self.lab, job_statuses);
+                    self.jobs_state.apply_worker_progress(worker_progress);
                     if was_loading {
                         let (_, current_completed_count) = self.calculate_current_counts();
                         self.last_completed_count = current_completed_count;
@@ -333,7 +388,16 @@ impl App {
     pub fn check_for_submission_updates(&mut self) {
         while let Ok(result) = self.submission_rx.try_recv() {
             match result {
+                SubmissionResult::SyncProgress(progress) => {
+                    self.sync_progress = Some(progress);
+                    continue;
+                }
+                SubmissionResult::SyncFinished => {
+                    self.sync_progress = None;
+                    continue;
+                }
                 SubmissionResult::Success { submitted_job_ids } => {
+                    self.sync_progress = None;
                     log_info!(
                         "Received submission success for {} jobs.",
                         submitted_job_ids.len()
@@ -350,6 +414,7 @@ impl App {
                     affected_job_ids,
                     error,
                 } => {
+                    self.sync_progress = None;
                     log_info!(
                         "Received submission failure for '{}': {} (affected {} jobs)",
                         failed_run_or_job_id,
@@ -385,15 +450,15 @@ impl App {
         for job in &self.jobs_state.jobs {
             counts.total += 1;
             match job.status.as_str() {
-                "Succeeded" => {
+                "Succeeded" | "Partial" => {
                     counts.succeeded += 1;
                     current_completed_count += 1;
                 }
-                "Failed" | "Submit Failed" => {
+                "Failed" | "Submit Failed" | "Slurm Failed" | "Dep Unsatisfied" => {
                     counts.failed += 1;
                     current_completed_count += 1;
                 }
-                "Running" => counts.running += 1,
+                "Running" | "Requeued" => counts.running += 1,
                 "Pending" => counts.pending += 1,
                 "Queued" => counts.queued += 1,
                 "Blocked" => counts.blocked += 1,
@@ -662,6 +727,7 @@ impl App {
         if let Some(addr) = &target_config.address {
             self.pending_action = Some(ExternalAction::EditRemote {
                 address: addr.clone(),
+                ssh_options: ssh_options(target_config),
                 paths: vec![full_path],
             });
         } else {
@@ -708,6 +774,7 @@ impl App {
         if let Some(addr) = &target_config.address {
             self.pending_action = Some(ExternalAction::EditRemote {
                 address: addr.clone(),
+                ssh_options: ssh_options(target_config),
                 paths,
             });
         } else {
@@ -811,7 +878,7 @@ impl App {
                 if let Some(job) = self.jobs_state.jobs.iter().find(|j| j.full_id.0 == *id_str) {
                     let is_submittable = !matches!(
                         job.status.as_str(),
-                        "Succeeded" | "Running" | "Queued" | "Submitting..."
+                        "Succeeded" | "Partial" | "Running" | "Queued" | "Submitting..."
                     );
                     if !is_submittable {
                         log_info!(
@@ -849,7 +916,10 @@ impl App {
         );
         for job in self.jobs_state.jobs.iter_mut() {
             if all_jobs_to_submit.contains(&job.full_id)
-                && !matches!(job.status.as_str(), "Succeeded" | "Running" | "Queued")
+                && !matches!(
+                    job.status.as_str(),
+                    "Succeeded" | "Partial" | "Running" | "Queued"
+                )
             {
                 job.status = "Submitting...".to_string();
             }
@@ -894,11 +964,36 @@ impl App {
                 &target_name
             );
 
+            let (event_tx, event_rx) = mpsc::channel();
+            let progress_tx = submission_tx_clone.clone();
+            thread::spawn(move || {
+                for event in event_rx {
+                    let update = match event {
+                        ClientEvent::SyncingArtifactProgress {
+                            path,
+                            bytes_transferred,
+                            total_bytes,
+                            bytes_per_second,
+                        } => SubmissionResult::SyncProgress(SyncProgress {
+                            path,
+                            bytes_transferred,
+                            total_bytes,
+                            bytes_per_second,
+                        }),
+                        ClientEvent::SyncingFinished => SubmissionResult::SyncFinished,
+                        _ => continue,
+                    };
+                    if progress_tx.send(update).is_err() {
+                        break;
+                    }
+                }
+            });
+
             let options = SubmitOptions {
                 execution_type: Some(execution_type),
                 resources: resources_clone,
                 num_jobs,
-                event_sender: None,
+                event_sender: Some(event_tx),
             };
 
             match client_clone.submit_batch_run(
@@ -992,9 +1087,12 @@ impl App {
             let config = self.client.config();
             let target_config = config.targets.get(&target_name);
 
-            if let Some(addr) = target_config.and_then(|t| t.address.as_ref()) {
+            if let Some((addr, target_config)) =
+                target_config.and_then(|t| t.address.as_ref().map(|addr| (addr, t)))
+            {
                 self.pending_action = Some(ExternalAction::ExploreRemote {
                     address: addr.clone(),
+                    ssh_options: ssh_options(target_config),
                     path,
                 });
             } else if path.exists() {
@@ -3,7 +3,8 @@ use crate::model::{TuiDisplayRow, TuiJob, TuiRowItem};
 use ratatui::widgets::TableState;
 use repx_core::engine::{self, JobStatus};
 use repx_core::model::{JobId, Lab};
-use std::collections::{HashSet, VecDeque};
+use repx_core::store::progress::WorkerProgress;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 #[derive(Debug, Clone)]
 enum FilterType {
@@ -75,6 +76,7 @@ impl JobsState {
                     context_depends_on: "-".to_string(),
                     context_dependents: "-".to_string(),
                     logs: vec!["Awaiting update...".to_string()],
+                    progress: None,
                 };
                 all_jobs.push(tui_job);
             }
@@ -106,16 +108,33 @@ impl JobsState {
 
             let status_str = match full_job_statuses.get(&job.full_id) {
                 Some(JobStatus::Succeeded { .. }) => "Succeeded",
+                Some(JobStatus::PartiallySucceeded { .. }) => "Partial",
                 Some(JobStatus::Failed { .. }) => "Failed",
+                Some(JobStatus::SchedulerFailed { reason, .. })
+                    if reason == "DependencyNeverSatisfied" =>
+                {
+                    "Dep Unsatisfied"
+                }
+                Some(JobStatus::SchedulerFailed { .. }) => "Slurm Failed",
                 Some(JobStatus::Pending) => "Pending",
                 Some(JobStatus::Queued) => "Queued",
                 Some(JobStatus::Running) => "Running",
+                Some(JobStatus::Requeued { .. }) => "Requeued",
                 Some(JobStatus::Blocked { .. }) => "Blocked",
                 None => "Unknown",
             };
             job.status = status_str.to_string();
         }
     }
+    pub fn apply_worker_progress(&mut self, progress: HashMap<JobId, WorkerProgress>) {
+        for job in self.jobs.iter_mut() {
+            job.progress = match job.status.as_str() {
+                "Succeeded" | "Partial" | "Failed" | "Slurm Failed" | "Dep Unsatisfied" => None,
+                _ => progress.get(&job.full_id).copied(),
+            };
+        }
+    }
+
     pub fn next(&mut self) {
         let max_len = self.display_rows.len();
         if max_len == 0 {
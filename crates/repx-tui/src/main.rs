@@ -93,7 +93,12 @@ fn main() -> Result<(), AppError> {
 
         let statuses = status_client_clone
             .get_statuses_for_active_target(&target_name, Some(&scheduler_name))
-            .map(|job_statuses| (target_name, job_statuses));
+            .map(|job_statuses| {
+                let worker_progress = status_client_clone
+                    .get_worker_progress(&target_name, &job_statuses)
+                    .unwrap_or_default();
+                (target_name, job_statuses, worker_progress)
+            });
         if status_tx.send(statuses).is_err() {
             break;
         }
@@ -242,11 +247,16 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &mut App) ->
                     resume_tui(terminal)?;
                     terminal.clear()?;
                 }
-                ExternalAction::ExploreRemote { address, path } => {
+                ExternalAction::ExploreRemote {
+                    address,
+                    ssh_options,
+                    path,
+                } => {
                     suspend_tui(terminal)?;
                     let remote_path = path.to_string_lossy().replace('\\', "/");
                     let remote_cmd = format!("yazi '{}'", remote_path);
                     let _ = std::process::Command::new("ssh")
+                        .args(&ssh_options)
                         .arg("-t")
                         .arg(address)
                         .arg(remote_cmd)
@@ -261,7 +271,11 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &mut App) ->
                     resume_tui(terminal)?;
                     terminal.clear()?;
                 }
-                ExternalAction::EditRemote { address, paths } => {
+                ExternalAction::EditRemote {
+                    address,
+                    ssh_options,
+                    paths,
+                } => {
                     suspend_tui(terminal)?;
                     let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
                     let remote_paths: Vec<String> = paths
@@ -271,6 +285,7 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &mut App) ->
                     let remote_paths_str = remote_paths.join(" ");
                     let remote_cmd = format!("{} {}", editor, remote_paths_str);
                     let _ = std::process::Command::new("ssh")
+                        .args(&ssh_options)
                         .arg("-t")
                         .arg(address)
                         .arg(remote_cmd)
@@ -1,3 +1,4 @@
+use repx_core::store::progress::WorkerProgress;
 use serde::Deserialize;
 use serde::Serialize;
 use std::{collections::HashMap, str::FromStr};
@@ -19,12 +20,18 @@ pub struct StatusCounts {
 pub enum TuiScheduler {
     Local,
     Slurm,
+    Pbs,
+    Condor,
+    SshPool,
 }
 impl TuiScheduler {
     pub fn as_str(&self) -> &'static str {
         match self {
             TuiScheduler::Local => "local",
             TuiScheduler::Slurm => "slurm",
+            TuiScheduler::Pbs => "pbs",
+            TuiScheduler::Condor => "condor",
+            TuiScheduler::SshPool => "ssh-pool",
         }
     }
 }
@@ -34,6 +41,9 @@ impl FromStr for TuiScheduler {
         match s {
             "local" => Ok(TuiScheduler::Local),
             "slurm" => Ok(TuiScheduler::Slurm),
+            "pbs" => Ok(TuiScheduler::Pbs),
+            "condor" => Ok(TuiScheduler::Condor),
+            "ssh-pool" => Ok(TuiScheduler::SshPool),
             _ => Err(()),
         }
     }
@@ -80,6 +90,7 @@ pub struct TuiJob {
     pub context_depends_on: String,
     pub context_dependents: String,
     pub logs: Vec<String>,
+    pub progress: Option<WorkerProgress>,
 }
 #[derive(Clone, Debug)]
 pub enum TuiRowItem {
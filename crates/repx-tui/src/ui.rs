@@ -4,6 +4,7 @@ use crate::{
     widgets::{color, BrailleGraph, GraphDirection, StackedBarChart},
 };
 use chrono::Local;
+use indicatif::HumanBytes;
 use ratatui::{
     prelude::*,
     widgets::{
@@ -12,6 +13,7 @@ use ratatui::{
     },
 };
 use repx_core::model::Lab;
+use repx_core::store::progress::WorkerProgress;
 use repx_core::theme::ElementStyle;
 use std::collections::{BTreeMap, HashSet};
 use std::str::FromStr;
@@ -403,6 +405,16 @@ fn draw_system_logs_panel(f: &mut Frame, area: Rect, app: &App) {
         .border_type(BorderType::Rounded)
         .border_style(style)
         .title(" System Logs ");
+    let block = match &app.sync_progress {
+        Some(progress) => block.title_bottom(format!(
+            " Syncing {}/{} ({}/s) {} ",
+            HumanBytes(progress.bytes_transferred),
+            HumanBytes(progress.total_bytes),
+            HumanBytes(progress.bytes_per_second),
+            progress.path.display()
+        )),
+        None => block,
+    };
     let inner_area = block.inner(area);
     f.render_widget(block, area);
 
@@ -755,14 +767,22 @@ fn draw_right_column(f: &mut Frame, area: Rect, app: &mut App) {
     let table_area = right_chunks[0];
     let scrollbar_area = right_chunks[1];
     let jobs_table = if app.jobs_state.is_tree_view {
-        let header = Row::new(vec!["", "jobid:", "Item:", "Parameters:", "Status:"])
-            .style(Style::default().add_modifier(Modifier::BOLD));
+        let header = Row::new(vec![
+            "",
+            "jobid:",
+            "Item:",
+            "Parameters:",
+            "Status:",
+            "Progress:",
+        ])
+        .style(Style::default().add_modifier(Modifier::BOLD));
         let constraints = [
             Constraint::Length(1),
             Constraint::Length(8),
             Constraint::Length(35),
             Constraint::Min(20),
             Constraint::Length(10),
+            Constraint::Length(PROGRESS_CELL_WIDTH),
         ];
         let rows = build_tree_rows(
             app,
@@ -790,6 +810,7 @@ fn draw_right_column(f: &mut Frame, area: Rect, app: &mut App) {
             "Run:",
             "Parameters:",
             "Status:",
+            "Progress:",
         ])
         .style(Style::default().add_modifier(Modifier::BOLD));
         let constraints = [
@@ -799,6 +820,7 @@ fn draw_right_column(f: &mut Frame, area: Rect, app: &mut App) {
             Constraint::Length(15),
             Constraint::Min(20),
             Constraint::Length(10),
+            Constraint::Length(PROGRESS_CELL_WIDTH),
         ];
         let rows = build_flat_rows(
             app,
@@ -867,6 +889,33 @@ fn format_params_single_line(v: &serde_json::Value) -> String {
     }
 }
 
+const PROGRESS_CELL_WIDTH: u16 = 14;
+const PROGRESS_BAR_WIDTH: usize = 8;
+
+fn progress_cell<'a>(app: &App, progress: Option<&WorkerProgress>) -> Cell<'a> {
+    let Some(progress) = progress else {
+        return Cell::from("");
+    };
+    let filled = (progress.fraction() * PROGRESS_BAR_WIDTH as f64).round() as usize;
+    let filled = filled.min(PROGRESS_BAR_WIDTH);
+    let style = if progress.failed > 0 {
+        get_style(app, &app.theme.elements.job_status.failed)
+    } else {
+        get_style(app, &app.theme.elements.job_status.running)
+    };
+    Cell::from(Line::from(vec![
+        Span::styled("█".repeat(filled), style),
+        Span::styled(
+            "░".repeat(PROGRESS_BAR_WIDTH - filled),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Span::raw(format!(
+            " {:>3}%",
+            (progress.fraction() * 100.0).floor() as u32
+        )),
+    ]))
+}
+
 fn build_flat_rows<'a>(
     app: &App,
     display_rows: &'a [TuiDisplayRow],
@@ -887,11 +936,13 @@ fn build_flat_rows<'a>(
                 Cell::from(" ")
             };
             let status_style = match job.status.as_str() {
-                "Succeeded" => get_style(app, &app.theme.elements.job_status.succeeded),
-                "Failed" => get_style(app, &app.theme.elements.job_status.failed),
+                "Succeeded" | "Partial" => get_style(app, &app.theme.elements.job_status.succeeded),
+                "Failed" | "Slurm Failed" | "Dep Unsatisfied" => {
+                    get_style(app, &app.theme.elements.job_status.failed)
+                }
                 "Submit Failed" => get_style(app, &app.theme.elements.job_status.submit_failed),
                 "Pending" => get_style(app, &app.theme.elements.job_status.pending),
-                "Running" => get_style(app, &app.theme.elements.job_status.running),
+                "Running" | "Requeued" => get_style(app, &app.theme.elements.job_status.running),
                 "Queued" => get_style(app, &app.theme.elements.job_status.queued),
                 "Blocked" => get_style(app, &app.theme.elements.job_status.blocked),
                 "Submitting..." => get_style(app, &app.theme.elements.job_status.submitting),
@@ -907,6 +958,7 @@ fn build_flat_rows<'a>(
                 Cell::from(job.run.clone()),
                 Cell::from(params_str),
                 status_cell,
+                progress_cell(app, job.progress.as_ref()),
             ])
         })
         .collect()
@@ -961,6 +1013,7 @@ fn build_tree_rows<'a>(
                     ])),
                     Cell::from(""),
                     Cell::from(""),
+                    Cell::from(""),
                 ]));
 
                 ancestor_is_last_stack.push(row_data.is_last_child);
@@ -1001,11 +1054,17 @@ fn build_tree_rows<'a>(
                 let display_text = job.name.clone();
                 let item_style = Style::default();
                 let status_style = match job.status.as_str() {
-                    "Succeeded" => get_style(app, &app.theme.elements.job_status.succeeded),
-                    "Failed" => get_style(app, &app.theme.elements.job_status.failed),
+                    "Succeeded" | "Partial" => {
+                        get_style(app, &app.theme.elements.job_status.succeeded)
+                    }
+                    "Failed" | "Slurm Failed" | "Dep Unsatisfied" => {
+                        get_style(app, &app.theme.elements.job_status.failed)
+                    }
                     "Submit Failed" => get_style(app, &app.theme.elements.job_status.submit_failed),
                     "Pending" => get_style(app, &app.theme.elements.job_status.pending),
-                    "Running" => get_style(app, &app.theme.elements.job_status.running),
+                    "Running" | "Requeued" => {
+                        get_style(app, &app.theme.elements.job_status.running)
+                    }
                     "Queued" => get_style(app, &app.theme.elements.job_status.queued),
                     "Blocked" => get_style(app, &app.theme.elements.job_status.blocked),
                     "Submitting..." => get_style(app, &app.theme.elements.job_status.submitting),
@@ -1024,6 +1083,7 @@ fn build_tree_rows<'a>(
                     ])),
                     params,
                     status,
+                    progress_cell(app, job.progress.as_ref()),
                 ]));
             }
         }